@@ -3,24 +3,6 @@
 
 #include <stdio.h>
 #include <stdlib.h>
-
-typedef struct ToCString { 
-	const char*(*ToCString_to_c_string)(struct ToCString*);
-} ToCString;
-
-const char* ToCString_to_c_string(ToCString* self);
-
-typedef struct String { 
-	ToCString base_ToCString;
-	const char* c_str;
-} String;
-
-String* String_create(const char* c_str);
-
-void String_destroy(String* self);
-
-const char* String_to_c_string_impl(String* self);
-
-const char* String_to_c_string(String* self);
+#include <stdbool.h>
 
 #endif // TALY_GEN_C_main_H
\ No newline at end of file
@@ -0,0 +1,1010 @@
+use crate::{util::position::Positioned, lexer::tokens::Keyword, bindgen::{c_lexer::CToken, error::BindgenError}};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             C Type                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, PartialEq)]
+enum CType {
+    Void,
+    Char,
+    Int,
+    Long,
+    UnsignedInt,
+    UnsignedLong,
+    Size,
+    Float,
+    Bool,
+    Named(String), // `struct tag`, `union tag` or a typedef coming from another header
+    Pointer(Box<CType>),
+    Const(Box<CType>),
+    Unsupported(String)
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Declaration                                          //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug)]
+enum Declaration {
+    Record {
+        c_name: String,
+        fields: Vec<(String, CType)>
+    },
+    Function {
+        name: String,
+        params: Vec<(Option<String>, CType)>,
+        return_type: CType,
+        variadic: bool
+    },
+    Variable {
+        name: String,
+        data_type: CType
+    },
+    Constant(String),
+    /// A declaration which could not be parsed, with the line it starts on and why
+    Skipped(usize, String)
+}
+
+/// A function declarator: its parameters and whether it is variadic.
+type FunctionSuffix = (Vec<(Option<String>, CType)>, bool);
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Bindgen                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+const IGNORED_QUALIFIERS: [&str; 16] = [
+    "extern", "static", "inline", "__inline", "__inline__", "volatile", "__volatile__", "restrict",
+    "__restrict", "__restrict__", "_Restrict_", "_Restrict_arr_", "_Noreturn", "__extension__",
+    "register", "auto"
+];
+
+const SKIPPED_ATTRIBUTES: [&str; 6] = ["__attribute__", "__attribute", "__asm__", "__asm", "__declspec", "_Alignas"];
+
+/// glibc declares some functions as `__REDIRECT (name, (params), alias)` to bind them to another
+/// symbol, the header still exposes them under `name`.
+const REDIRECT_MACROS: [&str; 3] = ["__REDIRECT", "__REDIRECT_NTH", "__REDIRECT_NTHNL"];
+
+const TYPE_WORDS: [&str; 13] = [
+    "void", "char", "int", "float", "double", "short", "long", "signed", "unsigned", "_Bool", "bool",
+    "__int128", "_Complex"
+];
+
+/// Parses the practical subset of C found in headers: functions, structs, unions, enums, typedefs,
+/// globals and `#define` integer constants. Anything else is skipped, a declaration which cannot be
+/// parsed is left as a comment in the bindings.
+pub struct Bindgen {
+    tokens: Vec<Positioned<CToken>>,
+    index: usize,
+    typedefs: Vec<(String, CType)>,
+    record_aliases: Vec<(String, String)>,
+    declarations: Vec<Declaration>
+}
+
+impl Bindgen {
+
+    pub fn new(tokens: Vec<Positioned<CToken>>) -> Self {
+        let mut declarations = Vec::new();
+
+        // Directives can appear anywhere, the defines are collected first
+        let mut filtered = Vec::new();
+        for token in tokens {
+            if let CToken::Directive(line) = &token.data {
+                if let Some(name) = Self::parse_define(line) {
+                    declarations.push(Declaration::Constant(name));
+                }
+            } else {
+                filtered.push(token);
+            }
+        }
+
+        Self {
+            tokens: filtered,
+            index: 0,
+            typedefs: Vec::new(),
+            record_aliases: Vec::new(),
+            declarations
+        }
+    }
+
+    /* Cursor Movement */
+    fn current(&self) -> Option<Positioned<CToken>> {
+        self.tokens.get(self.index).cloned()
+    }
+
+    fn peek(&self, x: usize) -> Option<Positioned<CToken>> {
+        self.tokens.get(self.index + x).cloned()
+    }
+
+    fn advance(&mut self) {
+        self.index += 1;
+    }
+
+    fn is_symbol(&self, symbol: char) -> bool {
+        self.current().is_some_and(|current| current.data == CToken::Symbol(symbol))
+    }
+
+    fn current_id(&self) -> Option<String> {
+        match self.current()?.data {
+            CToken::Identifier(id) => Some(id),
+            _ => None
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: char) -> Result<(), BindgenError> {
+        match self.current() {
+            Some(current) if current.data == CToken::Symbol(symbol) => {
+                self.advance();
+                Ok(())
+            },
+            Some(current) => Err(BindgenError::UnexpectedToken(Box::new(current.convert(current.data.short_name())), Some(symbol.to_string()))),
+            None => Err(BindgenError::UnexpectedEOF(Some(symbol.to_string())))
+        }
+    }
+
+    /// Skips a balanced group starting at the current opening symbol.
+    fn skip_group(&mut self) -> Result<(), BindgenError> {
+        let mut depth = 0;
+        while let Some(current) = self.current() {
+            match current.data {
+                CToken::Symbol('(') | CToken::Symbol('[') | CToken::Symbol('{') => depth += 1,
+                CToken::Symbol(')') | CToken::Symbol(']') | CToken::Symbol('}') => depth -= 1,
+                _ => {}
+            }
+            self.advance();
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+        Err(BindgenError::UnexpectedEOF(Some("closing bracket".to_string())))
+    }
+
+    /// Skips until one of the symbols is found outside of any group, without consuming it.
+    fn skip_until(&mut self, symbols: &[char]) -> Result<(), BindgenError> {
+        while let Some(current) = self.current() {
+            match current.data {
+                CToken::Symbol(chr) if symbols.contains(&chr) => return Ok(()),
+                CToken::Symbol('(') | CToken::Symbol('[') | CToken::Symbol('{') => self.skip_group()?,
+                _ => self.advance()
+            }
+        }
+        Err(BindgenError::UnexpectedEOF(Some(symbols.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" or "))))
+    }
+
+    /* Preprocessor */
+    fn parse_define(line: &str) -> Option<String> {
+        let rest = line.strip_prefix("define")?;
+        if !rest.starts_with([' ', '\t']) {
+            return None;
+        }
+        let rest = rest.trim_start();
+        let name: String = rest.chars().take_while(|x| x.is_ascii_alphanumeric() || *x == '_').collect();
+        // Function-like macros cannot be bound
+        if name.is_empty() || rest[name.len()..].starts_with('(') {
+            return None;
+        }
+
+        let mut value = rest[name.len()..].trim();
+        while value.starts_with('(') && value.ends_with(')') {
+            value = value[1..value.len() - 1].trim();
+        }
+        value = value.strip_prefix(['-', '+']).unwrap_or(value).trim_start();
+        let digits = value.trim_end_matches(['u', 'U', 'l', 'L']);
+        let valid = if let Some(hex) = digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+            !hex.is_empty() && hex.chars().all(|x| x.is_ascii_hexdigit())
+        } else {
+            !digits.is_empty() && digits.chars().all(|x| x.is_ascii_digit())
+        };
+
+        if valid {
+            Some(name)
+        } else {
+            None
+        }
+    }
+
+    /* Types */
+    fn parse_record(&mut self, kind: &str) -> Result<CType, BindgenError> {
+        self.advance();
+        self.skip_attributes()?;
+        let mut c_name = String::new();
+        if let Some(tag) = self.current_id() {
+            c_name = format!("{} {}", kind, tag);
+            self.advance();
+        }
+
+        if self.is_symbol('{') {
+            self.advance();
+            let mut fields = Vec::new();
+            while !self.is_symbol('}') {
+                if self.current().is_none() {
+                    return Err(BindgenError::UnexpectedEOF(Some("}".to_string())));
+                }
+                let Some(base) = self.parse_specifiers()? else {
+                    self.skip_until(&[';', '}'])?;
+                    if self.is_symbol(';') {
+                        self.advance();
+                    }
+                    continue;
+                };
+                loop {
+                    let (name, data_type, function) = self.parse_declarator(base.clone(), false)?;
+                    let data_type = if function.is_some() { CType::Unsupported("function".to_string()) } else { data_type };
+                    // Bitfields have no addressable layout
+                    let data_type = if self.is_symbol(':') {
+                        self.skip_until(&[',', ';', '}'])?;
+                        CType::Unsupported("bitfield".to_string())
+                    } else {
+                        data_type
+                    };
+                    if let Some(name) = name {
+                        fields.push((name, data_type));
+                    }
+                    if self.is_symbol(',') {
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+                self.skip_until(&[';', '}'])?;
+                if self.is_symbol(';') {
+                    self.advance();
+                }
+            }
+            self.advance();
+
+            self.declarations.push(Declaration::Record { c_name: c_name.clone(), fields });
+        }
+
+        Ok(CType::Named(c_name))
+    }
+
+    fn parse_enum(&mut self) -> Result<CType, BindgenError> {
+        self.advance();
+        self.skip_attributes()?;
+        if self.current_id().is_some() {
+            self.advance();
+        }
+
+        if self.is_symbol('{') {
+            self.advance();
+            while !self.is_symbol('}') {
+                let Some(name) = self.current_id() else {
+                    return match self.current() {
+                        Some(current) => Err(BindgenError::UnexpectedToken(Box::new(current.convert(current.data.short_name())), Some("Enumerator".to_string()))),
+                        None => Err(BindgenError::UnexpectedEOF(Some("}".to_string())))
+                    };
+                };
+                self.declarations.push(Declaration::Constant(name));
+                self.advance();
+                self.skip_until(&[',', '}'])?;
+                if self.is_symbol(',') {
+                    self.advance();
+                }
+            }
+            self.advance();
+        }
+
+        // Enums are ints in C
+        Ok(CType::Int)
+    }
+
+    fn skip_attributes(&mut self) -> Result<(), BindgenError> {
+        while let Some(id) = self.current_id() {
+            if !SKIPPED_ATTRIBUTES.contains(&id.as_str()) {
+                break;
+            }
+            self.advance();
+            if self.is_symbol('(') {
+                self.skip_group()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_specifiers(&mut self) -> Result<Option<CType>, BindgenError> {
+        let mut words = Vec::new();
+        let mut base = None;
+        let mut unknown_name = false;
+        let mut is_const = false;
+
+        while let Some(id) = self.current_id() {
+            match id.as_str() {
+                "const" => is_const = true,
+                _ if IGNORED_QUALIFIERS.contains(&id.as_str()) => {},
+                _ if SKIPPED_ATTRIBUTES.contains(&id.as_str()) => {
+                    self.skip_attributes()?;
+                    continue;
+                },
+                "struct" | "union" => {
+                    base = Some(self.parse_record(&id)?);
+                    continue;
+                },
+                "enum" => {
+                    base = Some(self.parse_enum()?);
+                    continue;
+                },
+                _ if TYPE_WORDS.contains(&id.as_str()) => {
+                    // The previous identifier was a macro, not a type
+                    if unknown_name {
+                        base = None;
+                        unknown_name = false;
+                    }
+                    words.push(id);
+                },
+                _ if base.is_none() && words.is_empty() => {
+                    if id == "size_t" {
+                        base = Some(CType::Size);
+                    } else if let Some((_, data_type)) = self.typedefs.iter().find(|(name, _)| *name == id) {
+                        base = Some(data_type.clone());
+                    } else {
+                        base = Some(CType::Named(id));
+                        unknown_name = true;
+                    }
+                },
+                _ => break
+            }
+            self.advance();
+        }
+
+        let base = if !words.is_empty() {
+            let has = |word: &str| words.iter().any(|x| x == word);
+            let longs = words.iter().filter(|x| *x == "long").count();
+            if ["double", "short", "__int128", "_Complex"].iter().any(|x| has(x)) || longs > 1 || (has("char") && has("unsigned")) {
+                CType::Unsupported(words.join(" "))
+            } else if has("void") {
+                CType::Void
+            } else if has("char") {
+                CType::Char
+            } else if has("float") {
+                CType::Float
+            } else if has("_Bool") || has("bool") {
+                CType::Bool
+            } else if longs == 1 && has("unsigned") {
+                CType::UnsignedLong
+            } else if longs == 1 {
+                CType::Long
+            } else if has("unsigned") {
+                CType::UnsignedInt
+            } else {
+                CType::Int
+            }
+        } else if let Some(base) = base {
+            base
+        } else {
+            return Ok(None);
+        };
+
+        if is_const {
+            Ok(Some(CType::Const(Box::new(base))))
+        } else {
+            Ok(Some(base))
+        }
+    }
+
+    fn parse_params(&mut self) -> Result<FunctionSuffix, BindgenError> {
+        self.expect_symbol('(')?;
+        let mut params = Vec::new();
+        let mut variadic = false;
+
+        while !self.is_symbol(')') {
+            if self.current().is_some_and(|current| current.data == CToken::Ellipsis) {
+                variadic = true;
+                self.advance();
+                continue;
+            }
+
+            let Some(base) = self.parse_specifiers()? else {
+                return match self.current() {
+                    Some(current) => Err(BindgenError::UnexpectedToken(Box::new(current.convert(current.data.short_name())), Some("Parameter".to_string()))),
+                    None => Err(BindgenError::UnexpectedEOF(Some(")".to_string())))
+                };
+            };
+            let (name, data_type, function) = self.parse_declarator(base, true)?;
+            let data_type = if function.is_some() { CType::Unsupported("function".to_string()) } else { data_type };
+            params.push((name, data_type));
+
+            if !self.is_symbol(',') && !self.is_symbol(')') {
+                self.skip_until(&[',', ')'])?;
+            }
+            if self.is_symbol(',') {
+                self.advance();
+            }
+        }
+        self.advance();
+
+        // `f(void)` takes no parameters
+        if params.len() == 1 && params[0].0.is_none() && params[0].1 == CType::Void {
+            params.clear();
+        }
+
+        Ok((params, variadic))
+    }
+
+    fn parse_declarator(&mut self, base: CType, param: bool) -> Result<(Option<String>, CType, Option<FunctionSuffix>), BindgenError> {
+        let mut data_type = base;
+
+        // Pointers
+        while let Some(current) = self.current() {
+            match current.data {
+                CToken::Symbol('*') => data_type = CType::Pointer(Box::new(data_type)),
+                CToken::Identifier(id) if id == "const" => data_type = CType::Const(Box::new(data_type)),
+                CToken::Identifier(id) if IGNORED_QUALIFIERS.contains(&id.as_str()) => {},
+                _ => break
+            }
+            self.advance();
+        }
+
+        // Function pointers and other grouped declarators
+        if self.is_symbol('(') {
+            let start = self.index;
+            self.skip_group()?;
+            let name = self.tokens[start..self.index].iter().rev().find_map(|token| match &token.data {
+                CToken::Identifier(id) if id != "const" => Some(id.clone()),
+                _ => None
+            });
+            while self.is_symbol('(') || self.is_symbol('[') {
+                self.skip_group()?;
+            }
+            return Ok((name, CType::Unsupported("function pointer".to_string()), None));
+        }
+
+        let mut name = None;
+        if let Some(id) = self.current_id() {
+            if REDIRECT_MACROS.contains(&id.as_str()) && self.peek(1).is_some_and(|next| next.data == CToken::Symbol('(')) {
+                return self.parse_redirect(data_type);
+            }
+            name = Some(id);
+            self.advance();
+        }
+        self.skip_attributes()?;
+
+        // Suffixes
+        let mut function = None;
+        while self.is_symbol('[') || self.is_symbol('(') {
+            if self.is_symbol('[') {
+                self.skip_group()?;
+                data_type = if param {
+                    // Arrays decay to pointers when passed
+                    CType::Pointer(Box::new(data_type))
+                } else {
+                    CType::Unsupported("array".to_string())
+                };
+            } else {
+                function = Some(self.parse_params()?);
+            }
+        }
+        self.skip_attributes()?;
+
+        Ok((name, data_type, function))
+    }
+
+    /// `__REDIRECT (name, (params), alias)`
+    fn parse_redirect(&mut self, return_type: CType) -> Result<(Option<String>, CType, Option<FunctionSuffix>), BindgenError> {
+        self.advance();
+        self.expect_symbol('(')?;
+        let name = self.current_id();
+        if name.is_none() {
+            return match self.current() {
+                Some(current) => Err(BindgenError::UnexpectedToken(Box::new(current.convert(current.data.short_name())), Some("Identifier".to_string()))),
+                None => Err(BindgenError::UnexpectedEOF(Some("Identifier".to_string())))
+            };
+        }
+        self.advance();
+        self.expect_symbol(',')?;
+        let function = self.parse_params()?;
+        self.skip_until(&[')'])?;
+        self.advance();
+        self.skip_attributes()?;
+
+        Ok((name, return_type, Some(function)))
+    }
+
+    /* Declarations */
+    fn parse_typedef(&mut self) -> Result<(), BindgenError> {
+        self.advance();
+        let Some(base) = self.parse_specifiers()? else {
+            return self.skip_statement();
+        };
+
+        loop {
+            let (name, data_type, function) = self.parse_declarator(base.clone(), false)?;
+            if let Some(name) = name {
+                let data_type = match data_type {
+                    _ if function.is_some() => CType::Unsupported("function".to_string()),
+                    // typedef struct { .. } name;
+                    CType::Named(c_name) if c_name.is_empty() => {
+                        for declaration in self.declarations.iter_mut().rev() {
+                            if let Declaration::Record { c_name, .. } = declaration {
+                                if c_name.is_empty() {
+                                    *c_name = name.clone();
+                                }
+                                break;
+                            }
+                        }
+                        CType::Named(name.clone())
+                    },
+                    // typedef struct tag name;
+                    CType::Named(c_name) if c_name.starts_with("struct ") || c_name.starts_with("union ") => {
+                        if !self.record_aliases.iter().any(|(record, _)| *record == c_name) {
+                            self.record_aliases.push((c_name, name.clone()));
+                        }
+                        CType::Named(name.clone())
+                    },
+                    data_type => data_type
+                };
+                self.typedefs.push((name, data_type));
+            }
+
+            if self.is_symbol(',') {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        self.skip_statement()
+    }
+
+    /// Skips to the end of the statement: after its `;` or after the body of a function, but not
+    /// past the `}` closing an enclosing block.
+    fn skip_statement(&mut self) -> Result<(), BindgenError> {
+        let mut depth = 0usize;
+        let mut body = false;
+        let mut previous = None;
+        while let Some(current) = self.current() {
+            match current.data {
+                CToken::Symbol('}') if depth == 0 => return Ok(()),
+                CToken::Symbol('{') if depth == 0 => {
+                    body = previous == Some(CToken::Symbol(')'));
+                    depth += 1;
+                },
+                CToken::Symbol('(') | CToken::Symbol('[') | CToken::Symbol('{') => depth += 1,
+                CToken::Symbol(')') | CToken::Symbol(']') => depth = depth.saturating_sub(1),
+                CToken::Symbol('}') => {
+                    depth -= 1;
+                    if depth == 0 && body {
+                        self.advance();
+                        return Ok(());
+                    }
+                },
+                CToken::Symbol(';') if depth == 0 => {
+                    self.advance();
+                    return Ok(());
+                },
+                _ => {}
+            }
+            previous = Some(current.data);
+            self.advance();
+        }
+        Err(BindgenError::UnexpectedEOF(Some(";".to_string())))
+    }
+
+    fn parse_declaration(&mut self) -> Result<(), BindgenError> {
+        let Some(current) = self.current() else {
+            return Ok(());
+        };
+
+        match current.data {
+            CToken::Symbol(';') | CToken::Symbol('}') => {
+                self.advance();
+                return Ok(());
+            },
+            // extern "C" {
+            CToken::Identifier(id) if id == "extern" && self.peek(1).is_some_and(|x| x.data == CToken::Literal) => {
+                self.advance();
+                self.advance();
+                if self.is_symbol('{') {
+                    self.advance();
+                }
+                return Ok(());
+            },
+            CToken::Identifier(id) if id == "typedef" => return self.parse_typedef(),
+            // A macro like `__BEGIN_DECLS` before a typedef
+            CToken::Identifier(_) if self.peek(1).is_some_and(|next| next.data == CToken::Identifier("typedef".to_string())) => {
+                self.advance();
+                return Ok(());
+            },
+            _ => {}
+        }
+
+        let Some(base) = self.parse_specifiers()? else {
+            return self.skip_statement();
+        };
+
+        loop {
+            let (name, data_type, function) = self.parse_declarator(base.clone(), false)?;
+            let named = name.is_some();
+            if let Some(name) = name {
+                if let Some((params, variadic)) = function {
+                    self.declarations.push(Declaration::Function { name, params, return_type: data_type, variadic });
+                } else {
+                    self.declarations.push(Declaration::Variable { name, data_type });
+                }
+            }
+
+            // Initializer
+            if self.is_symbol('=') {
+                self.skip_until(&[',', ';'])?;
+            }
+
+            match self.current() {
+                Some(current) if current.data == CToken::Symbol(',') => self.advance(),
+                Some(current) if current.data == CToken::Symbol(';') => {
+                    self.advance();
+                    break;
+                },
+                // Inline definition
+                Some(current) if current.data == CToken::Symbol('{') => {
+                    self.skip_group()?;
+                    break;
+                },
+                Some(_) => return self.skip_statement(),
+                // A macro like `__END_DECLS` closing the header
+                None if !named => break,
+                None => return Err(BindgenError::UnexpectedEOF(Some(";".to_string())))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the errors of the declarations which were skipped.
+    pub fn parse(&mut self) -> Vec<BindgenError> {
+        let mut errors = Vec::new();
+        while let Some(current) = self.current() {
+            let start = self.index;
+            if let Err(err) = self.parse_declaration() {
+                // Skip the whole declaration, from its first token
+                self.index = start;
+                _ = self.skip_statement();
+                self.declarations.push(Declaration::Skipped(current.start.line, err.message()));
+                errors.push(err);
+            }
+        }
+
+        errors
+    }
+
+    /* Writer */
+    fn resolve_record(&self, c_name: &str) -> String {
+        self.record_aliases.iter().find(|(record, _)| record == c_name).map_or(c_name.to_string(), |(_, alias)| alias.clone())
+    }
+
+    fn class_name(c_name: &str) -> String {
+        Self::escape_name(c_name.strip_prefix("struct ").or(c_name.strip_prefix("union ")).unwrap_or(c_name))
+    }
+
+    /// C reserves the names starting with `_` for the implementation, they are internals.
+    fn is_reserved(name: &str) -> bool {
+        name.starts_with('_')
+    }
+
+    /// Taly identifiers cannot start with `_` nor be keywords.
+    fn escape_name(name: &str) -> String {
+        let stripped = name.trim_start_matches('_');
+        if stripped.is_empty() || stripped.starts_with(|chr: char| chr.is_ascii_digit()) {
+            format!("c{}", name)
+        } else if Keyword::from_string(stripped).is_some() || stripped == "true" || stripped == "false" {
+            format!("{}_", stripped)
+        } else {
+            stripped.to_string()
+        }
+    }
+
+    /// Symbol prefix when the Taly name differs from the C one.
+    fn symbol_prefix(name: &str) -> String {
+        if Self::escape_name(name) != name {
+            format!("\"{}\" ", name)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Taly classes are already pointers, so `struct tag*` becomes `tag`.
+    fn taly_type(&self, data_type: &CType, top: bool, opaque: &mut Vec<String>) -> Result<String, String> {
+        match data_type {
+            CType::Void => Err("void".to_string()),
+            CType::Char => Ok("c_char".to_string()),
+            CType::Int => Ok("c_int".to_string()),
+            CType::Long => Ok("c_long".to_string()),
+            CType::UnsignedInt => Ok("c_uint".to_string()),
+            CType::UnsignedLong => Ok("c_ulong".to_string()),
+            CType::Size => Ok("c_size_t".to_string()),
+            CType::Float => Ok("c_float".to_string()),
+            CType::Bool => Ok("Bool".to_string()),
+            CType::Named(c_name) => Err(c_name.clone()),
+            CType::Unsupported(name) => Err(name.clone()),
+            CType::Const(inner) if top => self.taly_type(inner, top, opaque),
+            CType::Const(inner) => Ok(format!("const {}", self.taly_type(inner, false, opaque)?)),
+            CType::Pointer(inner) => match inner.as_ref() {
+                CType::Named(c_name) => Ok(self.class_type(c_name, opaque)),
+                CType::Const(pointee) => match pointee.as_ref() {
+                    CType::Named(c_name) => Ok(self.class_type(c_name, opaque)),
+                    CType::Void => Ok("*const void".to_string()),
+                    _ => Ok(format!("*{}", self.taly_type(inner, false, opaque)?))
+                },
+                CType::Void => Ok("*void".to_string()),
+                inner => Ok(format!("*{}", self.taly_type(inner, false, opaque)?))
+            }
+        }
+    }
+
+    fn class_type(&self, c_name: &str, opaque: &mut Vec<String>) -> String {
+        let c_name = self.resolve_record(c_name);
+        let name = Self::class_name(&c_name);
+        if !opaque.contains(&c_name) {
+            opaque.push(c_name);
+        }
+        name
+    }
+
+    fn write_class(c_name: &str) -> String {
+        let name = Self::class_name(c_name);
+        if c_name.strip_prefix("struct ").is_some_and(|tag| tag == name) {
+            format!("extern struct {}\n", name)
+        } else if c_name != name {
+            format!("extern \"{}\" class {}\n", c_name, name)
+        } else {
+            format!("extern class {}\n", name)
+        }
+    }
+
+    /// Writes a Taly module of extern declarations, `include` being the path used in `use "c-.."`.
+    pub fn write(&self, include: &str) -> String {
+        let mut referenced = Vec::new();
+        let mut classes = Vec::new();
+        let mut class_names = Vec::new();
+        let mut values = Vec::new();
+        let mut body = String::new();
+
+        for declaration in self.declarations.iter() {
+            match declaration {
+                Declaration::Record { c_name, fields } => {
+                    let c_name = self.resolve_record(c_name);
+                    if c_name.is_empty() || classes.contains(&c_name) || class_names.contains(&Self::class_name(&c_name)) {
+                        continue;
+                    }
+                    classes.push(c_name.clone());
+                    class_names.push(Self::class_name(&c_name));
+                    body.push_str(&Self::write_class(&c_name));
+                    for (name, data_type) in fields.iter() {
+                        match self.taly_type(data_type, true, &mut referenced) {
+                            Ok(taly_type) => {
+                                // Constant fields and renamed fields need an explicit extern
+                                let constant = matches!(data_type, CType::Const(_));
+                                let prefix = if constant || name != &Self::escape_name(name) {
+                                    format!("extern {}", Self::symbol_prefix(name))
+                                } else {
+                                    String::new()
+                                };
+                                let var_type = if constant { "const" } else { "var" };
+                                body.push_str(&format!("\t{}{} {}: {}\n", prefix, var_type, Self::escape_name(name), taly_type));
+                            },
+                            Err(unsupported) => body.push_str(&format!("\t# Skipped field '{}': unsupported type '{}'\n", name, unsupported))
+                        }
+                    }
+                },
+                Declaration::Function { name, params, return_type, variadic } => {
+                    if values.contains(name) || Self::is_reserved(name) {
+                        continue;
+                    }
+                    values.push(name.clone());
+
+                    let mut buf = format!("extern {}fn {}(", Self::symbol_prefix(name), Self::escape_name(name));
+                    let mut unsupported = None;
+                    let mut param_names = Vec::new();
+                    for (index, (param_name, data_type)) in params.iter().enumerate() {
+                        if index != 0 {
+                            buf.push_str(", ");
+                        }
+                        // `__x` and `x` both become `x`
+                        let param_name = match param_name.as_ref().map(|x| Self::escape_name(x)) {
+                            Some(param_name) if !param_names.contains(&param_name) => param_name,
+                            _ => format!("arg{}", index)
+                        };
+                        param_names.push(param_name.clone());
+                        match self.taly_type(data_type, true, &mut referenced) {
+                            Ok(data_type) => buf.push_str(&format!("{}: {}", param_name, data_type)),
+                            Err(name) => unsupported = unsupported.or(Some(name))
+                        }
+                    }
+                    if *variadic {
+                        if !params.is_empty() {
+                            buf.push_str(", ");
+                        }
+                        buf.push_str("...");
+                    }
+                    buf.push(')');
+                    if *return_type != CType::Void {
+                        match self.taly_type(return_type, true, &mut referenced) {
+                            Ok(data_type) => buf.push_str(&format!(": {}", data_type)),
+                            Err(name) => unsupported = unsupported.or(Some(name))
+                        }
+                    }
+
+                    if let Some(unsupported) = unsupported {
+                        body.push_str(&format!("# Skipped '{}': unsupported type '{}'\n", name, unsupported));
+                    } else {
+                        body.push_str(&buf);
+                        body.push('\n');
+                    }
+                },
+                Declaration::Variable { name, data_type } => {
+                    if values.contains(name) || Self::is_reserved(name) {
+                        continue;
+                    }
+                    values.push(name.clone());
+
+                    let var_type = if let CType::Const(_) = data_type { "const" } else { "var" };
+                    match self.taly_type(data_type, true, &mut referenced) {
+                        Ok(taly_type) => body.push_str(&format!("extern {}{} {}: {}\n", Self::symbol_prefix(name), var_type, Self::escape_name(name), taly_type)),
+                        Err(unsupported) => body.push_str(&format!("# Skipped '{}': unsupported type '{}'\n", name, unsupported))
+                    }
+                },
+                Declaration::Constant(name) => {
+                    if values.contains(name) || Self::is_reserved(name) {
+                        continue;
+                    }
+                    values.push(name.clone());
+                    body.push_str(&format!("extern {}const {}: c_int\n", Self::symbol_prefix(name), Self::escape_name(name)));
+                },
+                Declaration::Skipped(line, reason) => body.push_str(&format!("# Skipped the declaration at line {}: {}\n", line, reason)),
+            }
+        }
+
+        let mut buf = format!("# Generated by `taly-lang bindgen`\nuse \"c-{}\"\n\n", include);
+
+        // Handles only known through pointers, `__FILE` and `FILE` both being the class `FILE`
+        let mut opaque = false;
+        referenced.sort_by_key(|c_name| Self::is_reserved(c_name));
+        for c_name in referenced.iter() {
+            let name = Self::class_name(c_name);
+            if !classes.contains(c_name) && !class_names.contains(&name) {
+                buf.push_str(&Self::write_class(c_name));
+                class_names.push(name);
+                opaque = true;
+            }
+        }
+        if opaque {
+            buf.push('\n');
+        }
+
+        buf.push_str(&body);
+        buf
+    }
+
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Tests                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::{bindgen::c_lexer::CLexer, lexer::lexer::Lexer, parser::{parser::Parser, node::Node}, session::session::{Session, Stage}, util::source_file::SourceFile};
+
+    use super::Bindgen;
+
+    fn bind(src: &str, include: &str) -> String {
+        let Ok(tokens) = CLexer::new(src).tokenize() else {
+            panic!("Could not tokenize the header");
+        };
+        let mut bindgen = Bindgen::new(tokens);
+        bindgen.parse();
+        bindgen.write(include)
+    }
+
+    fn parse_taly(src: &str) -> Vec<Node> {
+        let Ok(tokens) = Lexer::new(src).tokenize() else {
+            panic!("Could not tokenize the bindings");
+        };
+        let Ok(ast) = Parser::new(tokens).parse() else {
+            panic!("Could not parse the bindings");
+        };
+        ast.into_iter().map(|node| node.data).collect()
+    }
+
+    /// Runs the bindings, imported as a module, up to the checker.
+    fn check_taly(src: &str, include: &str) {
+        let path = format!("{}.taly", include);
+        let mut session = Session::new(SourceFile::new("main.taly".to_string(), format!("use \"{}\"\n", path)));
+        session.add_module(SourceFile::new(path, src.to_string()));
+        if let Err(err) = session.run(Stage::Check) {
+            panic!("Could not check the bindings: {}", err.kind());
+        }
+    }
+
+    #[test]
+    fn shapes_fixture() {
+        let bindings = bind(include_str!("../../tests/fixtures/bindgen/shapes.h"), "shapes");
+        assert_eq!(bindings, include_str!("../../tests/fixtures/bindgen/shapes.taly"));
+        check_taly(&bindings, "shapes");
+    }
+
+    #[test]
+    fn opaque_fixture() {
+        let bindings = bind(include_str!("../../tests/fixtures/bindgen/opaque.h"), "opaque");
+        assert_eq!(bindings, include_str!("../../tests/fixtures/bindgen/opaque.taly"));
+        check_taly(&bindings, "opaque");
+    }
+
+    #[test]
+    fn system_fixture() {
+        let bindings = bind(include_str!("../../tests/fixtures/bindgen/system.h"), "system");
+        assert_eq!(bindings, include_str!("../../tests/fixtures/bindgen/system.taly"));
+        check_taly(&bindings, "system");
+    }
+
+    #[test]
+    fn unparseable_declarations_are_skipped() {
+        let Ok(tokens) = CLexer::new("int first(void);\nint broken(int, (x));\nstruct s { int x; } s_value;\nint last(void);\n").tokenize() else {
+            panic!("Could not tokenize the header");
+        };
+        let mut bindgen = Bindgen::new(tokens);
+        assert_eq!(bindgen.parse().len(), 1);
+        let bindings = bindgen.write("skipped");
+        assert!(bindings.contains("extern fn first(): c_int\n# Skipped the declaration at line 2: Unexpected token '(', should be 'Parameter'\n"));
+        assert!(bindings.contains("extern struct s\n\tvar x: c_int\n"));
+        assert!(bindings.contains("extern fn last(): c_int\n"));
+    }
+
+    #[test]
+    fn integer_types() {
+        let bindings = bind("long a(unsigned b, unsigned long int c, size_t d);\nlong long e(void);\nunsigned char f(void);\n", "integers");
+        assert!(bindings.contains("extern fn a(b: c_uint, c: c_ulong, d: c_size_t): c_long\n"));
+        assert!(bindings.contains("# Skipped 'e': unsupported type 'long long'\n"));
+        assert!(bindings.contains("# Skipped 'f': unsupported type 'unsigned char'\n"));
+    }
+
+    #[test]
+    fn defines() {
+        let bindings = bind("#define A 1\n#define B (-0x10UL)\n#define C \"c\"\n#define D(x) x\n#define E\n", "defines");
+        assert!(bindings.contains("extern const A: c_int\n"));
+        assert!(bindings.contains("extern const B: c_int\n"));
+        assert!(!bindings.contains(" C:"));
+        assert!(!bindings.contains(" D:"));
+        assert!(!bindings.contains(" E:"));
+    }
+
+    #[test]
+    fn externs_are_parsed() {
+        let bindings = bind("typedef struct { int x; } Box;\nextern Box *boxes;\nint box_get(const Box *box);\n", "box");
+        let ast = parse_taly(&bindings);
+        assert!(ast.iter().any(|node| matches!(node, Node::ClassDefinition { name, external: Some(_), body, .. } if name.data == "Box" && body.len() == 1)));
+        assert!(ast.iter().any(|node| matches!(node, Node::VariableDefinition { name, external: Some(_), .. } if name.data == "boxes")));
+        assert!(ast.iter().any(|node| matches!(node, Node::FunctionDefinition { name, external: Some(_), parameters, .. } if name.data == "box_get" && parameters.len() == 1)));
+    }
+
+    #[test]
+    fn reserved_names() {
+        let bindings = bind("struct _IO_FILE { int _flags; };\nint __uflow(struct _IO_FILE *__fp);\nint fgetc(struct _IO_FILE *__fp);\nint pair(int __x, int x);\n", "reserved");
+        assert!(bindings.contains("extern \"struct _IO_FILE\" class IO_FILE\n\textern \"_flags\" var flags: c_int\n"));
+        assert!(!bindings.contains("uflow"));
+        assert!(bindings.contains("extern fn fgetc(fp: IO_FILE): c_int\n"));
+        assert!(bindings.contains("extern fn pair(x: c_int, arg1: c_int): c_int\n"));
+        check_taly(&bindings, "reserved");
+    }
+
+    #[test]
+    fn reserved_aliases_are_merged() {
+        let bindings = bind("struct _IO_FILE;\ntypedef struct _IO_FILE __FILE;\ntypedef struct _IO_FILE FILE;\nint __fgetc(__FILE *__fp);\nint fclose(FILE *__fp);\nint fputc(int c, __FILE *__fp);\n", "stdio");
+        assert_eq!(bindings.matches("class FILE").count(), 1);
+        check_taly(&bindings, "stdio");
+    }
+
+    #[test]
+    fn keywords_are_escaped() {
+        let bindings = bind("int match(int end);\nstruct range { int end; };\n", "range");
+        assert!(bindings.contains("extern \"match\" fn match_(end_: c_int): c_int\n"));
+        assert!(bindings.contains("\textern \"end\" var end_: c_int\n"));
+        assert!(!parse_taly(&bindings).is_empty());
+    }
+
+}
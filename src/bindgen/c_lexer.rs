@@ -0,0 +1,196 @@
+use crate::{util::position::{Position, Positioned}, bindgen::error::BindgenError};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             C Token                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CToken {
+    Identifier(String),
+    Integer(String),
+    Literal, // Strings, chars and decimals are only ever skipped
+    Directive(String),
+    Symbol(char),
+    Ellipsis
+}
+
+impl CToken {
+
+    pub fn short_name(&self) -> String {
+        match self {
+            CToken::Identifier(id) => id.clone(),
+            CToken::Integer(num) => num.clone(),
+            CToken::Literal => "literal".to_string(),
+            CToken::Directive(line) => format!("#{}", line),
+            CToken::Symbol(chr) => chr.to_string(),
+            CToken::Ellipsis => "...".to_string(),
+        }
+    }
+
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             C Lexer                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub struct CLexer {
+    chars: Vec<char>,
+    pos: Position
+}
+
+impl CLexer {
+
+    pub fn new(src: &str) -> Self {
+        Self {
+            chars: src.chars().collect(),
+            pos: Position::default()
+        }
+    }
+
+    fn peek(&self, x: usize) -> char {
+        self.chars.get(self.pos.index + x).cloned().unwrap_or('\0')
+    }
+
+    fn current(&self) -> char {
+        self.peek(0)
+    }
+
+    fn advance(&mut self) {
+        self.pos.advance(self.current())
+    }
+
+    fn skip_block_comment(&mut self) -> Result<(), BindgenError> {
+        self.advance();
+        self.advance();
+        while !(self.current() == '*' && self.peek(1) == '/') {
+            if self.current() == '\0' {
+                return Err(BindgenError::UnexpectedEOF(Some("*/".to_string())));
+            }
+            self.advance();
+        }
+        self.advance();
+        self.advance();
+        Ok(())
+    }
+
+    fn make_directive(&mut self) -> Result<Positioned<CToken>, BindgenError> {
+        let start = self.pos.clone();
+        self.advance();
+        let mut buf = String::new();
+        loop {
+            match self.current() {
+                '\n' | '\0' => break,
+                // Line continuation
+                '\\' if self.peek(1) == '\n' => {
+                    self.advance();
+                    buf.push(' ');
+                },
+                '/' if self.peek(1) == '/' => {
+                    while self.current() != '\n' && self.current() != '\0' {
+                        self.advance();
+                    }
+                    break;
+                },
+                '/' if self.peek(1) == '*' => {
+                    self.skip_block_comment()?;
+                    buf.push(' ');
+                    continue;
+                },
+                chr => buf.push(chr)
+            }
+            self.advance();
+        }
+        Ok(Positioned::new(CToken::Directive(buf.trim().to_string()), start, self.pos.clone()))
+    }
+
+    fn make_identifier(&mut self) -> Positioned<CToken> {
+        let start = self.pos.clone();
+        let mut buf = String::new();
+        while self.current().is_ascii_alphanumeric() || self.current() == '_' {
+            buf.push(self.current());
+            self.advance();
+        }
+        Positioned::new(CToken::Identifier(buf), start, self.pos.clone())
+    }
+
+    fn make_number(&mut self) -> Positioned<CToken> {
+        let start = self.pos.clone();
+        let mut buf = String::new();
+        while self.current().is_ascii_alphanumeric() || self.current() == '.' {
+            buf.push(self.current());
+            // Exponent sign
+            if (self.current() == 'e' || self.current() == 'E') && !buf.starts_with("0x") && (self.peek(1) == '-' || self.peek(1) == '+') {
+                self.advance();
+                buf.push(self.current());
+            }
+            self.advance();
+        }
+
+        let decimal = buf.contains('.') || (!buf.starts_with("0x") && !buf.starts_with("0X") && (buf.contains('e') || buf.contains('E')));
+        let token = if decimal { CToken::Literal } else { CToken::Integer(buf) };
+        Positioned::new(token, start, self.pos.clone())
+    }
+
+    fn make_literal(&mut self, quote: char) -> Result<Positioned<CToken>, BindgenError> {
+        let start = self.pos.clone();
+        self.advance();
+        while self.current() != quote {
+            match self.current() {
+                '\0' | '\n' => return Err(BindgenError::UnexpectedEOF(Some(quote.to_string()))),
+                '\\' => {
+                    self.advance();
+                    self.advance();
+                },
+                _ => self.advance()
+            }
+        }
+        self.advance();
+        Ok(Positioned::new(CToken::Literal, start, self.pos.clone()))
+    }
+
+    pub fn tokenize(&mut self) -> Result<Vec<Positioned<CToken>>, BindgenError> {
+        let mut tokens = Vec::new();
+
+        loop {
+            let current = self.current();
+            match current {
+                '\0' => break,
+                // glibc separates sections with form feeds
+                ' ' | '\t' | '\r' | '\n' | '\x0b' | '\x0c' => self.advance(),
+                '/' if self.peek(1) == '/' => {
+                    while self.current() != '\n' && self.current() != '\0' {
+                        self.advance();
+                    }
+                },
+                '/' if self.peek(1) == '*' => self.skip_block_comment()?,
+                '#' => tokens.push(self.make_directive()?),
+                '"' | '\'' => tokens.push(self.make_literal(current)?),
+                '.' if self.peek(1) == '.' && self.peek(2) == '.' => {
+                    let start = self.pos.clone();
+                    for _ in 0..3 {
+                        self.advance();
+                    }
+                    tokens.push(Positioned::new(CToken::Ellipsis, start, self.pos.clone()));
+                },
+                _ if current.is_ascii_digit() => tokens.push(self.make_number()),
+                _ if current.is_ascii_alphabetic() || current == '_' => tokens.push(self.make_identifier()),
+                '{' | '}' | '(' | ')' | '[' | ']' | ';' | ',' | '*' | '=' | ':' | '<' | '>' | '+' | '-' | '/' | '%' | '&' | '|' | '^' | '~' | '!' | '?' | '.' => {
+                    let start = self.pos.clone();
+                    self.advance();
+                    tokens.push(Positioned::new(CToken::Symbol(current), start, self.pos.clone()));
+                },
+                _ => {
+                    let start = self.pos.clone();
+                    let mut end = self.pos.clone();
+                    end.advance(current);
+                    return Err(BindgenError::UnexpectedChar(Positioned::new(current, start, end)));
+                }
+            }
+        }
+
+        Ok(tokens)
+    }
+
+}
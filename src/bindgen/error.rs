@@ -0,0 +1,59 @@
+use crate::util::{position::Positioned, error::{ErrorFormat, ErrorType}, source_file::SourceFile};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Bindgen Error                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+pub enum BindgenError {
+    UnexpectedChar(Positioned<char>),
+    UnexpectedToken(Box<Positioned<String>>, Option<String>),
+    UnexpectedEOF(Option<String>)
+}
+
+impl BindgenError {
+
+    pub fn message(&self) -> String {
+        match self {
+            BindgenError::UnexpectedChar(found) => format!("Unexpected char '{}'", found.data),
+            BindgenError::UnexpectedToken(found, expected) => {
+                let mut buf = format!("Unexpected token '{}'", found.data);
+                if let Some(expected) = expected {
+                    buf.push_str(format!(", should be '{}'", expected).as_str());
+                }
+                buf
+            },
+            BindgenError::UnexpectedEOF(expected) => {
+                let mut buf = "Unexpected EOF".to_string();
+                if let Some(expected) = expected {
+                    buf.push_str(format!(", should be '{}'", expected).as_str());
+                }
+                buf
+            },
+        }
+    }
+
+    fn position(&self) -> Option<Positioned<()>> {
+        match self {
+            BindgenError::UnexpectedChar(found) => Some(found.convert(())),
+            BindgenError::UnexpectedToken(found, _) => Some(found.convert(())),
+            BindgenError::UnexpectedEOF(_) => None
+        }
+    }
+
+    fn print(&self, src: &SourceFile, error_type: ErrorType) {
+        ErrorFormat::new(error_type)
+            .add_message(format!("{}!", self.message()), self.position())
+            .set_step("Bindgen".to_string())
+            .print(src);
+    }
+
+    pub fn print_error(&self, src: &SourceFile) {
+        self.print(src, ErrorType::Error);
+    }
+
+    /// Declarations which could not be parsed are skipped, not fatal.
+    pub fn print_warning(&self, src: &SourceFile) {
+        self.print(src, ErrorType::Warning);
+    }
+
+}
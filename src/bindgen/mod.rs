@@ -0,0 +1,3 @@
+pub mod error;
+pub mod c_lexer;
+pub mod bindgen;
\ No newline at end of file
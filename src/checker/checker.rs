@@ -1,4 +1,4 @@
-use crate::{symbolizer::{scope::{Scope, ScopeType, Scoped}, trace::Trace}, ir::output::IROutput, util::{position::Positioned, reference::MutRef}, parser::node::{Node, ValueNode, Operator, VarType, AccessModifier, ElifBranch, DataType}, checker::error::CheckerError};
+use crate::{symbolizer::scope::{ScopeType, Scoped, SymbolTable, Visibility}, ir::output::IROutput, util::{position::Positioned, mangle, suggestion, id::{ScopeId, SymbolId}}, parser::node::{Node, ValueNode, Operator, VarType, AccessModifier, ElifBranch, DataType, LambdaCapture, FunctionDefinitionParameter}, checker::{error::CheckerError, types}};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                            Node Info                                           //
@@ -7,8 +7,8 @@ use crate::{symbolizer::{scope::{Scope, ScopeType, Scoped}, trace::Trace}, ir::o
 struct NodeInfo {
     pub checked: Positioned<Node>,
     pub data_type: Option<Scoped<Positioned<DataType>>>,
-    pub selected: Option<MutRef<Scope>>,
-    pub function_called: Option<MutRef<Scope>>
+    pub selected: Option<SymbolId>,
+    pub function_called: Option<SymbolId>
 }
 
 
@@ -17,29 +17,34 @@ struct NodeInfo {
 //                                             Checker                                            //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
 
-pub struct Checker {
+pub struct Checker<'a> {
     ir_output: IROutput,
-    scope: MutRef<Scope>,
+    table: &'a mut SymbolTable,
+    scope: ScopeId,
     index: usize,
-    trace: Trace,
-    inferred: Vec<(Trace, Positioned<DataType>)>,
+    visibility: Visibility,
     selected: bool,
-    base_scope: Option<MutRef<Scope>>,
+    base_scope: Option<ScopeId>,
     block_parent: bool,
+    lambdas: Vec<(ScopeId, Vec<LambdaCapture>)>,
+    /// The next definition is generated, its name can be reserved
+    generated: bool,
 }
 
-impl Checker {
+impl<'a> Checker<'a> {
 
-    pub fn new(ir_output: IROutput, scope: MutRef<Scope>) -> Self {
+    pub fn new(ir_output: IROutput, table: &'a mut SymbolTable) -> Self {
         Self {
             ir_output,
-            scope,
+            table,
+            scope: SymbolTable::ROOT,
             index: 0,
-            trace: Trace::default(),
-            inferred: Vec::new(),
+            visibility: Visibility::Declared,
             selected: false,
             base_scope: None,
-            block_parent: false
+            block_parent: false,
+            lambdas: Vec::new(),
+            generated: false
         }
     }
 
@@ -52,17 +57,11 @@ impl Checker {
     }
     
     fn check_data_type(&mut self, found_node: Positioned<()>, expected: Positioned<DataType>, found: Option<Positioned<DataType>>) -> Result<(), CheckerError> {
-        if let Some(found) = found.clone() {
-            match (&expected.data, &found.data) {
-                (DataType::Custom(expected_str), DataType::Custom(found_str)) => match (found_str.as_str(), expected_str.as_str()) {
-                    ("c_string", "String") | ("String", "c_string") => Ok(()),
-                    ("c_int", "I32") | ("I32", "c_int") => Ok(()),
-                    ("c_float", "F32") | ("F32", "c_float") => Ok(()),
-                    (lhs, rhs) if lhs == rhs => Ok(()),
-                    (_, _) => Err(CheckerError::UnexpectedType(found_node.convert(Some(found_str.clone())), Some(expected.convert(expected_str.clone()))))
-                },
-                (DataType::Function { return_type: _expected_return_type, params: _expected_params }, DataType::Function { return_type: _found_return_type, params: _found_params }) => todo!("Check type for function"),
-                (_, _) => Err(CheckerError::UnexpectedType(found_node.convert(Some(found.data.to_string())), Some(expected.convert(expected.data.to_string()))))
+        if let Some(found) = found {
+            if types::is_assignable(&expected.data, &found.data) {
+                Ok(())
+            } else {
+                Err(CheckerError::UnexpectedType(found_node.convert(Some(found.data.to_string())), Some(expected.convert(expected.data.to_string()))))
             }
         } else {
             Err(CheckerError::UnexpectedType(found_node.convert(None), Some(expected.convert(expected.data.to_string()))))
@@ -113,14 +112,14 @@ impl Checker {
             }),
             ValueNode::Type(str) => {
                 let selected = match str.as_str() {
-                    "c_string" | "c_int" | "c_float" => None,
+                    "c_string" | "c_int" | "c_uint" | "c_long" | "c_ulong" | "c_size_t" | "c_float" => None,
                     _ => {
-                        if let Some(class) = self.scope.get().get_class(self.trace.clone(), str.clone()) {
+                        if let Some(class) = self.table.get_class(self.scope, self.visibility, str.clone()) {
                             Some(class)
-                        } else if let Some(space) = self.scope.get().get_space(self.trace.clone(), str.clone()) {
+                        } else if let Some(space) = self.table.get_space(self.scope, self.visibility, str.clone()) {
                             Some(space)
                         } else {
-                            return Err(CheckerError::SymbolNotFound(node.convert(str.clone())));
+                            return Err(self.symbol_not_found(node.convert(str.clone())));
                         }
                     }
                 };
@@ -134,36 +133,80 @@ impl Checker {
         }
     }
 
+    /// Suggests the closest name visible from the current scope.
+    fn symbol_not_found(&self, name: Positioned<String>) -> CheckerError {
+        let names = self.table.visible_names(self.scope);
+        let suggestion = suggestion::closest(&name.data, names.iter().map(|name| name.as_str()));
+        CheckerError::SymbolNotFound(name, suggestion)
+    }
+
+    /// `emitted` names are found as is in the C code, they cannot be C keywords.
+    fn check_reserved_name(&self, name: &Positioned<String>, emitted: bool) -> Result<(), CheckerError> {
+        if mangle::is_reserved(&name.data) || (emitted && mangle::is_c_reserved(&name.data)) {
+            return Err(CheckerError::ReservedName(name.clone()));
+        }
+        Ok(())
+    }
+
+    /// Types keep their Taly name in C, a mangled symbol can still be named like one of them.
+    fn check_name_conflict(&mut self, name: &Positioned<String>, processed_name: &str) -> Result<(), CheckerError> {
+        let scope = self.table.get_class(self.scope, Visibility::All, processed_name.to_string())
+            .or_else(|| self.table.get_interface(self.scope, Visibility::All, processed_name.to_string()));
+        if let Some(scope) = scope {
+            match &self.table.get(scope).scope {
+                ScopeType::Class { name: other, .. } |
+                ScopeType::Interface { name: other, .. } => return Err(CheckerError::NameConflict(name.clone(), processed_name.to_string(), other.clone())),
+                _ => unreachable!()
+            }
+        }
+        Ok(())
+    }
+
+    fn check_parameters(&self, parameters: Vec<FunctionDefinitionParameter>) -> Result<Vec<FunctionDefinitionParameter>, CheckerError> {
+        let mut new_parameters = Vec::new();
+        for param in parameters {
+            self.check_reserved_name(&param.name, true)?;
+            new_parameters.push(FunctionDefinitionParameter {
+                name: param.name.convert(mangle::escape(&param.name.data)),
+                data_type: param.data_type
+            });
+        }
+        Ok(new_parameters)
+    }
+
     fn check_function_definition(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
-        let Node::FunctionDefinition { name, external, constructor, parameters, return_type, body, access } = node.data.clone() else {
+        let Node::FunctionDefinition { name, external, constructor, parameters, return_type, body, access, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
-        // Enter Scope
-        let processed_name;
-        if let Some(function) = self.scope.get().enter_function(Trace::full(), name.data.clone(), true, true) {
-            function.get().parent = Some(self.scope.clone()); // FIXME: Somehow fix the problem
-            self.scope = function;
-            processed_name = self.scope.get().process_name();
+        let parameters = if external.is_none() {
+            self.check_reserved_name(&name, self.table.get(self.scope).is_root())?;
+            self.check_parameters(parameters)?
         } else {
-            unreachable!("Symbol '{}' not found in {:#?}", name.data, self.scope.get().scope);
+            parameters
+        };
+
+        // Enter Scope
+        let Some(function) = scope else {
+            unreachable!("Function '{}' was not symbolized", name.data);
+        };
+        self.table.get_mut(function).parent = Some(self.scope); // FIXME: Somehow fix the problem
+        self.table.declare(function);
+        self.scope = function.scope();
+        let processed_name = self.table.process_name(self.scope);
+        if external.is_none() {
+            self.check_name_conflict(&name, &processed_name)?;
         }
 
         // Check Body
         let mut new_body = Vec::new();
-        self.trace = Trace::new(0, self.trace.clone());
         for child in body {
-            let checked_child = self.check_node(child.clone())?;
+            let checked_child = self.check_node(child)?;
             new_body.push(checked_child.checked);
-            if !child.data.is_generated() {
-                self.trace.index += 1;
-            }
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
 
         // Exit Scope
-        if let Some(parent) = self.scope.get().parent.clone() {
+        if let Some(parent) = self.table.parent(self.scope) {
             self.scope = parent;
         } else {
             unreachable!("Not parent after entering function!");
@@ -179,7 +222,9 @@ impl Checker {
                     parameters, 
                     return_type, 
                     body: new_body,
-                    access
+                    access,
+                    module,
+                    scope
                 }))}),
                 data_type: None,
                 selected: None,
@@ -188,31 +233,32 @@ impl Checker {
     }
 
     fn check_function_call(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
-        let Node::FunctionCall { name, parameters } = node.data.clone() else {
+        let Node::FunctionCall { name, parameters, .. } = node.data.clone() else {
             unreachable!()
         };
 
         // Find scope-symbol
         let processed_name;
         let function = if self.selected {
-            if let Some(function) = self.scope.get().enter_function(self.trace.clone(), name.data.clone(), true, self.scope.get().is_variable()) {
-                processed_name = function.get().process_name();
+            if let Some(function) = self.table.enter_function(self.scope, self.visibility, name.data.clone(), true, self.table.get(self.scope).is_variable()) {
+                processed_name = self.table.process_name(function);
                 function
             } else {
-                return Err(CheckerError::SymbolNotFound(name));
+                return Err(self.symbol_not_found(name));
             }
         } else {
-            if let Some(function) = self.scope.get().get_function(self.trace.clone(), name.data.clone(), false) {
-                processed_name = function.get().process_name();
+            if let Some(function) = self.table.get_function(self.scope, self.visibility, name.data.clone(), false) {
+                processed_name = self.table.process_name(function);
                 function
             } else {
-                return Err(CheckerError::SymbolNotFound(name));
+                return self.check_closure_call(node);
             }
         };
 
-        let ScopeType::Function { params: def_params, return_type: def_return_type, .. } = &function.get().scope else {
+        let ScopeType::Function { params: def_params, return_type: def_return_type, external: def_external, .. } = self.table.get(function).scope.clone() else {
             unreachable!()
         };
+        let variadic = def_external.as_ref().is_some_and(|external| external.variadic);
 
         // Set scope to base scope
         if let Some(base_scope) = self.base_scope.take() {
@@ -236,15 +282,15 @@ impl Checker {
 
             if let Some(def_param) = def_params.get(index) {
                 self.check_data_type(param.convert(()), def_param.data_type.clone(), checked_param.data_type.map(|x| x.data))?;
-            } else {
-                return Err(CheckerError::TooManyParameters(parameters_len, def_params.len(), name.clone(), function.get().pos.clone()));
+            } else if !variadic {
+                return Err(CheckerError::TooManyParameters(parameters_len, def_params.len(), name.clone(), self.table.get(function).pos.clone()));
             }
 
             checked_parameters.push(checked_param.checked);
             index += 1;
         }
-        if index != def_params.len() {
-            return Err(CheckerError::NotEnoughParameters(parameters_len, def_params.len(), name.clone(), function.get().pos.clone()));
+        if index < def_params.len() || (!variadic && index != def_params.len()) {
+            return Err(CheckerError::NotEnoughParameters(parameters_len, def_params.len(), name.clone(), self.table.get(function).pos.clone()));
         }
 
         return Ok(NodeInfo { 
@@ -252,7 +298,8 @@ impl Checker {
                 name: processed_name, 
                 node: Box::new(node.convert(Node::FunctionCall { 
                     name, 
-                    parameters: checked_parameters
+                    parameters: checked_parameters,
+                    scope: Some(function)
                 }))
             }), 
             data_type: def_return_type.clone(),
@@ -261,28 +308,118 @@ impl Checker {
         })
     }
 
+    fn check_closure_call(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
+        let Node::FunctionCall { name, parameters, .. } = node.data.clone() else {
+            unreachable!()
+        };
+
+        // Only variables holding a function can be called
+        let Some(variable) = self.table.get_variable(self.scope, self.visibility, name.data.clone(), false) else {
+            return Err(self.symbol_not_found(name));
+        };
+
+        let ScopeType::Variable { data_type: def_data_type, .. } = &self.table.get(variable).scope else {
+            unreachable!()
+        };
+
+        let Some(def_data_type) = def_data_type.clone() else {
+            return Err(CheckerError::CannotInferType(name));
+        };
+
+        let DataType::Function { return_type: def_return_type, params: def_params } = def_data_type.data.data.clone() else {
+            return Err(CheckerError::UnexpectedType(name.convert(Some(def_data_type.data.data.to_string())), Some(name.convert("fn".to_string()))));
+        };
+
+        let checked_closure = self.check_variable_call(name.convert(Node::VariableCall { name: name.data.clone(), scope: None }))?;
+
+        // Check parameters (number + type)
+        let parameters_len = parameters.len();
+        let mut index = 0;
+        let mut checked_parameters = Vec::new();
+        for mut param in parameters {
+            if let Node::_Optional(inner) = param.data {
+                if parameters_len > def_params.len() {
+                    continue;
+                } else {
+                    param = *inner;
+                }
+            } 
+            let checked_param = self.check_node(param.clone())?;
+
+            if let Some(def_param) = def_params.get(index) {
+                self.check_data_type(param.convert(()), def_param.clone(), checked_param.data_type.map(|x| x.data))?;
+            } else {
+                return Err(CheckerError::TooManyParameters(parameters_len, def_params.len(), name.clone(), self.table.get(variable).pos.clone()));
+            }
+
+            checked_parameters.push(checked_param.checked);
+            index += 1;
+        }
+        if index != def_params.len() {
+            return Err(CheckerError::NotEnoughParameters(parameters_len, def_params.len(), name.clone(), self.table.get(variable).pos.clone()));
+        }
+
+        let data_type = def_return_type.map(|return_type| Scoped {
+            data: *return_type.clone(),
+            scope: match return_type.data {
+                DataType::Custom(inner) => self.table.get_class(self.scope, Visibility::All, inner),
+                _ => None
+            }
+        });
+
+        Ok(NodeInfo { 
+            checked: node.convert(Node::_ClosureCall { 
+                closure: Box::new(checked_closure.checked), 
+                data_type: def_data_type.data, 
+                parameters: checked_parameters 
+            }), 
+            data_type,
+            selected: None,
+            function_called: None
+        })
+    }
+
     fn check_variable_definition(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
-        let Node::VariableDefinition { var_type, name, value, access, .. } = node.data.clone() else {
+        let Node::VariableDefinition { var_type, name, value, access, external, module, scope, .. } = node.data.clone() else {
             unreachable!()
         };
 
         // Find scope-symbol
-        let Some(variable) = self.scope.get().get_variable(self.trace.clone(), name.data.clone(), true) else {
-            unreachable!("Symbol not found: {}", name.data)
+        let Some(variable) = scope else {
+            unreachable!("Variable '{}' was not symbolized", name.data)
         };
+        self.table.declare(variable);
 
-        let ScopeType::Variable { data_type: def_data_type, .. } = &mut variable.get().scope else {
+        // Globals are namespaced by their space, locals are escaped
+        let name = if external.is_none() {
+            let in_space = self.table.parent(variable).is_some_and(|parent| matches!(self.table.get(parent).scope, ScopeType::Space { .. }));
+            if !std::mem::take(&mut self.generated) {
+                self.check_reserved_name(&name, !in_space)?;
+            }
+            let processed_name = self.table.process_name(variable);
+            if self.table.is_global(variable) {
+                self.check_name_conflict(&name, &processed_name)?;
+            }
+            name.convert(processed_name)
+        } else {
+            name
+        };
+
+        let ScopeType::Variable { data_type: mut def_data_type, .. } = self.table.get(variable).scope.clone() else {
             unreachable!()
         };
 
         let value_checked = if let Some(value) = value {
             let info = self.check_node(*value.clone())?;
-            if let Some(def_data_type) = def_data_type {
+            if let Some(def_data_type) = &def_data_type {
                 // Check type
                 self.check_data_type(value.convert(()), def_data_type.data.clone(), info.data_type.map(|x| x.data))?;
             } else if let Some(info_data_type) = info.data_type {
                 // Infer Type
-                *def_data_type = Some(info_data_type.clone());
+                if let ScopeType::Variable { data_type, .. } = &mut self.table.get_mut(variable).scope {
+                    *data_type = Some(info_data_type.clone());
+                }
+                def_data_type = Some(info_data_type);
             }
             Some(Box::new(info.checked))
         } else {
@@ -293,9 +430,12 @@ impl Checker {
             checked: node.convert(Node::VariableDefinition { 
                 var_type, 
                 name, 
-                data_type: def_data_type.clone().map(|x| x.data).clone(), 
+                data_type: def_data_type.map(|x| x.data), 
                 value: value_checked,
-                access
+                access,
+                external,
+                module,
+                scope
             }), data_type: None,
             selected: None,
             function_called: None
@@ -303,54 +443,138 @@ impl Checker {
     }
 
     fn check_variable_call(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
-        let Node::VariableCall(name) = node.data.clone() else {
+        let Node::VariableCall { name, .. } = node.data.clone() else {
             unreachable!()
         };
 
         let variable = if self.block_parent {
-            self.scope.get().enter_variable(self.trace.clone(), name.clone(), true, self.scope.get().is_variable())
+            self.table.enter_variable(self.scope, self.visibility, name.clone(), true, self.table.get(self.scope).is_variable())
         } else {
-            self.scope.get().get_variable(self.trace.clone(), name.clone(), self.scope.get().is_variable())
+            self.table.get_variable(self.scope, self.visibility, name.clone(), self.table.get(self.scope).is_variable())
         };
 
         if let Some(variable) = variable {
-            let ScopeType::Variable { var_type: def_var_type, name: def_name, data_type: def_data_type, initialized: def_initialized } = &variable.get().scope else {
+            let ScopeType::Variable { var_type: def_var_type, name: def_name, data_type: def_data_type, initialized: def_initialized, .. } = self.table.get(variable).scope.clone() else {
                 unreachable!()
             };
     
             if def_var_type.data == VarType::Constant && !def_initialized {
-                return Err(CheckerError::VariableNotInitialized(def_name.clone()));
+                return Err(CheckerError::VariableNotInitialized(def_name));
+            }
+
+            if !self.block_parent && !self.lambdas.is_empty() {
+                if let Some(info) = self.check_capture(node.clone(), variable)? {
+                    return Ok(info);
+                }
             }
     
+            // Externs can be bound to another C symbol
+            let processed_name = self.table.process_name(variable);
             Ok(NodeInfo {
-                checked: node.convert(Node::VariableCall(name.clone())),
-                data_type: def_data_type.clone(),
+                checked: node.convert(Node::VariableCall { name: processed_name, scope: Some(variable) }),
+                data_type: def_data_type,
                 selected: Some(variable),
                 function_called: None
             })
-        } else if let Some(class) = self.scope.get().get_class(self.trace.clone(), name.clone()) {
+        } else if let Some(class) = self.table.get_class(self.scope, self.visibility, name.clone()) {
             Ok(NodeInfo {
-                checked: node.convert(Node::VariableCall(name.clone())),
+                checked: node.convert(Node::VariableCall { name: name.clone(), scope: Some(class) }),
                 data_type: None,
                 selected: Some(class),
                 function_called: None
             })
-        } else if let Some(space) = self.scope.get().get_space(self.trace.clone(), name.clone()) {
+        } else if let Some(space) = self.table.get_space(self.scope, self.visibility, name.clone()) {
             Ok(NodeInfo {
-                checked: node.convert(Node::VariableCall(name.clone())),
+                checked: node.convert(Node::VariableCall { name: name.clone(), scope: Some(space) }),
                 data_type: None,
                 selected: Some(space),
                 function_called: None
             })
         } else {
-            return Err(CheckerError::SymbolNotFound(node.convert(name)));
+            return Err(self.symbol_not_found(node.convert(name)));
+        }
+    }
+
+    fn env_access(node: &Positioned<Node>, name: String) -> Positioned<Node> {
+        node.convert(Node::BinaryOperation { 
+            lhs: Box::new(node.convert(Node::VariableCall { name: "_env".to_string(), scope: None })), 
+            operator: node.convert(Operator::Access), 
+            rhs: Box::new(node.convert(Node::VariableCall { name, scope: None })) 
+        })
+    }
+
+    fn check_capture(&mut self, node: Positioned<Node>, variable: SymbolId) -> Result<Option<NodeInfo>, CheckerError> {
+        let ScopeType::Variable { name: def_name, data_type, .. } = &self.table.get(variable).scope else {
+            unreachable!()
+        };
+        let def_name = def_name.data.clone();
+        let data_type = data_type.clone();
+        let name = self.table.process_name(variable);
+
+        // Find the outermost lambda left before reaching the scope owning the variable
+        let mut outermost = None;
+        let mut current = self.scope;
+        loop {
+            match &self.table.get(current).scope {
+                ScopeType::Function { children, .. } |
+                ScopeType::Branch { children, .. } => {
+                    if children.contains(&variable.scope()) {
+                        break;
+                    }
+                },
+                _ => return Ok(None)
+            }
+
+            if let Some(index) = self.lambdas.iter().position(|(lambda, _)| *lambda == current) {
+                outermost = Some(index);
+            }
+
+            let Some(parent) = self.table.parent(current) else {
+                return Ok(None);
+            };
+            current = parent;
         }
+
+        let Some(outermost) = outermost else {
+            return Ok(None);
+        };
+
+        let Some(data_type) = data_type else {
+            return Err(CheckerError::CannotInferType(node.convert(def_name)));
+        };
+
+        // Every lambda in between captures the variable from its parent
+        for index in outermost..self.lambdas.len() {
+            let value = if index == outermost {
+                node.convert(Node::VariableCall { name: name.clone(), scope: Some(variable) })
+            } else {
+                Self::env_access(&node, name.clone())
+            };
+
+            let captures = &mut self.lambdas[index].1;
+            if !captures.iter().any(|capture| capture.name.data == name) {
+                captures.push(LambdaCapture { 
+                    name: node.convert(name.clone()), 
+                    data_type: data_type.data.clone(), 
+                    value 
+                });
+            }
+        }
+
+        Ok(Some(NodeInfo {
+            checked: Self::env_access(&node, name),
+            data_type: Some(data_type),
+            selected: Some(variable),
+            function_called: None
+        }))
     }
 
-    fn check_access_modifier(&mut self, node: Positioned<()>, selected: &MutRef<Scope>) -> Result<(), CheckerError> {
-        let selected = selected.get();
+    fn check_access_modifier(&mut self, node: Positioned<()>, selected: SymbolId) -> Result<(), CheckerError> {
+        // Members are accessible from anywhere inside their parent
+        let inside = self.table.parent(selected).is_some_and(|parent| self.table.is_inside(self.scope, parent));
+        let selected = self.table.get(selected);
         
-        if self.trace.follows_path(&selected.trace) {
+        if inside {
             Ok(())
         } else if let Some(access) = &selected.access {
             match &access.data {
@@ -361,11 +585,20 @@ impl Checker {
                 AccessModifier::Guarded => unimplemented!(),
             }
         } else {
-            println!("Current path: {:#?}, selected: {:#?}", self.trace, selected.trace);
             Err(CheckerError::CannotAccessPrivateMember(node, selected.pos.clone()))
         }
     }
 
+    /// Marks the variable as assigned, with its inferred type if it had none
+    fn set_initialized(&mut self, variable: SymbolId, inferred: Option<Scoped<Positioned<DataType>>>) {
+        if let ScopeType::Variable { data_type, initialized, .. } = &mut self.table.get_mut(variable).scope {
+            if inferred.is_some() {
+                *data_type = inferred;
+            }
+            *initialized = true;
+        }
+    }
+
     fn check_assignment(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
         let Node::BinaryOperation { lhs, operator, rhs } = node.data.clone() else {
             unreachable!()
@@ -375,41 +608,39 @@ impl Checker {
         let checked_rhs = self.check_node(*rhs.clone())?;
         
         if let Some(selected) = checked_lhs.selected {
-            if let ScopeType::Variable { var_type, name, data_type, initialized } = &mut selected.get().scope {
-                if var_type.data == VarType::Constant && *initialized {
-                    return Err(CheckerError::CannotAssignToConstant(node.convert(()), selected.get().pos.convert(name.data.clone())));
+            if let ScopeType::Variable { var_type, name, data_type, initialized, .. } = self.table.get(selected).scope.clone() {
+                if var_type.data == VarType::Constant && initialized {
+                    return Err(CheckerError::CannotAssignToConstant(node.convert(()), self.table.get(selected).pos.convert(name.data.clone())));
                 }
 
                 if let Some(data_type) = data_type {
                     self.check_data_type(rhs.convert(()), data_type.data.clone(), checked_rhs.data_type.map(|x| x.data))?;
 
-                    *initialized = true;
+                    self.set_initialized(selected, None);
                     return Ok(NodeInfo {
                         checked: node.convert(Node::BinaryOperation { 
                             lhs: Box::new(checked_lhs.checked), 
                             operator, 
                             rhs: Box::new(checked_rhs.checked)
                         }),
-                        data_type: Some(data_type.clone()),
+                        data_type: Some(data_type),
                         selected: None,
                         function_called: None
                     });
                 } else if let Some(rhs_type) = checked_rhs.data_type {
-                    self.inferred.push((selected.get().trace.clone(), rhs_type.data.clone()));
-                    *data_type = Some(rhs_type.clone());
-                    *initialized = true;
+                    self.set_initialized(selected, Some(rhs_type.clone()));
                     return Ok(NodeInfo {
                         checked: node.convert(Node::BinaryOperation { 
                             lhs: Box::new(checked_lhs.checked), 
                             operator, 
                             rhs: Box::new(checked_rhs.checked)
                         }),
-                        data_type: Some(rhs_type.clone()),
+                        data_type: Some(rhs_type),
                         selected: None,
                         function_called: None
                     });
                 } else {
-                    return Err(CheckerError::CannotInferType(selected.get().pos.convert(name.data.clone())));
+                    return Err(CheckerError::CannotInferType(self.table.get(selected).pos.convert(name.data.clone())));
                 }
             } else {
                 return Err(CheckerError::CannotAssignToConstantExpression(node.convert(())));
@@ -419,24 +650,115 @@ impl Checker {
         }
     }
 
+    fn get_compound_operator(operator: &Operator) -> Operator {
+        match operator {
+            Operator::AddAssign => Operator::Add,
+            Operator::SubtractAssign => Operator::Subtract,
+            Operator::MultiplyAssign => Operator::Multiply,
+            Operator::DivideAssign => Operator::Divide,
+            Operator::ModuloAssign => Operator::Modulo,
+            Operator::BitwiseAndAssign => Operator::BitwiseAnd,
+            Operator::BitwiseOrAssign => Operator::BitwiseOr,
+            Operator::BitwiseXorAssign => Operator::BitwiseXor,
+            Operator::ShiftLeftAssign => Operator::ShiftLeft,
+            Operator::ShiftRightAssign => Operator::ShiftRight,
+            _ => unreachable!()
+        }
+    }
+
+    fn check_compound_assignment(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
+        let Node::BinaryOperation { lhs, operator, rhs } = node.data.clone() else {
+            unreachable!()
+        };
+
+        let checked_lhs = self.check_node(*lhs.clone())?;
+
+        // Check if the target can be assigned
+        let Some(selected) = checked_lhs.selected else {
+            return Err(CheckerError::CannotAssignToConstantExpression(node.convert(())));
+        };
+        let ScopeType::Variable { var_type, name, initialized, .. } = self.table.get(selected).scope.clone() else {
+            return Err(CheckerError::CannotAssignToConstantExpression(node.convert(())));
+        };
+        if var_type.data == VarType::Constant {
+            return Err(CheckerError::CannotAssignToConstant(node.convert(()), self.table.get(selected).pos.convert(name.data.clone())));
+        } else if !initialized {
+            return Err(CheckerError::VariableNotInitialized(lhs.convert(name.data.clone())));
+        }
+
+        // Check as lhs = lhs op rhs
+        let expanded = node.convert(Node::BinaryOperation { 
+            lhs: lhs.clone(), 
+            operator: operator.convert(Self::get_compound_operator(&operator.data)), 
+            rhs: rhs.clone() 
+        });
+        let checked_expanded = self.check_binary_operation(expanded)?;
+        self.check_data_type(node.convert(()), checked_lhs.data_type.clone().unwrap().data, checked_expanded.data_type.clone().map(|x| x.data))?;
+
+        // Overloaded operators cannot be compounded in C
+        let checked = if let Node::BinaryOperation { rhs: checked_rhs, .. } = checked_expanded.checked.data {
+            node.convert(Node::BinaryOperation { 
+                lhs: Box::new(checked_lhs.checked), 
+                operator, 
+                rhs: checked_rhs
+            })
+        } else {
+            node.convert(Node::BinaryOperation { 
+                lhs: Box::new(checked_lhs.checked), 
+                operator: operator.convert(Operator::Assign), 
+                rhs: Box::new(checked_expanded.checked)
+            })
+        };
+
+        Ok(NodeInfo {
+            checked,
+            data_type: checked_lhs.data_type,
+            selected: None,
+            function_called: checked_expanded.function_called
+        })
+    }
+
     fn check_access(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
         let Node::BinaryOperation { lhs, operator, rhs } = node.data.clone() else {
             unreachable!()
         };
 
         let checked_lhs = self.check_node(*lhs.clone())?;
+
+        // Function values own the environment of their closure, `destroy` frees it
+        if let (Some(DataType::Function { .. }), Node::FunctionCall { name, parameters, .. }) = (checked_lhs.data_type.as_ref().map(|x| &x.data.data), &rhs.data) {
+            // The IR passes the value itself as an optional first parameter
+            if name.data == "destroy" && parameters.iter().all(|param| matches!(param.data, Node::_Optional(_))) {
+                let env = rhs.convert(Node::BinaryOperation { 
+                    lhs: Box::new(checked_lhs.checked), 
+                    operator: operator.convert(Operator::DotAccess), 
+                    rhs: Box::new(rhs.convert(Node::VariableCall { name: "env".to_string(), scope: None })) 
+                });
+                return Ok(NodeInfo {
+                    checked: node.convert(Node::_Unchecked(Box::new(node.convert(Node::FunctionCall { 
+                        name: name.convert("free".to_string()), 
+                        parameters: vec![env],
+                        scope: None
+                    })))),
+                    data_type: None,
+                    selected: None,
+                    function_called: None
+                })
+            }
+        }
+
         if let Some(selected) = checked_lhs.selected {
-            let prev_scope = self.scope.clone();
-            let prev_trace = self.trace.clone();
+            let prev_scope = self.scope;
+            let prev_visibility = self.visibility;
             let prev_selected = self.selected;
             let prev_block_parent = self.block_parent;
-            self.scope = selected;
-            self.trace = Trace::full();
+            self.scope = selected.scope();
+            self.visibility = Visibility::All;
             self.selected = true;
             self.block_parent = true;
             
             let base_scope_changed = if self.base_scope.is_none() {
-                self.base_scope = Some(prev_scope.clone());
+                self.base_scope = Some(prev_scope);
                 true
             } else {
                 false
@@ -445,7 +767,7 @@ impl Checker {
             let checked_rhs = self.check_node(*rhs.clone())?;
             
             self.scope = prev_scope;
-            self.trace = prev_trace;
+            self.visibility = prev_visibility;
             self.selected = prev_selected;
             self.block_parent = prev_block_parent;
 
@@ -453,34 +775,41 @@ impl Checker {
                 self.base_scope = None;
             }
 
-            if let Some(selected_rhs) = &checked_rhs.selected {
+            if let Some(selected_rhs) = checked_rhs.selected {
                 self.check_access_modifier(node.convert(()), selected_rhs)?;
-            } else if let Some(function_called) = &checked_rhs.function_called {
+            } else if let Some(function_called) = checked_rhs.function_called {
                 self.check_access_modifier(node.convert(()), function_called)?;
             }
 
-            return Ok(NodeInfo {
-                checked: node.convert(Node::BinaryOperation { 
+            // Globals of a space are plain C symbols
+            let checked = if checked_rhs.selected.is_some_and(|selected| self.table.is_global(selected)) {
+                checked_rhs.checked
+            } else {
+                node.convert(Node::BinaryOperation { 
                     lhs: Box::new(checked_lhs.checked), 
                     operator, 
                     rhs: Box::new(checked_rhs.checked) 
-                }),
+                })
+            };
+
+            return Ok(NodeInfo {
+                checked,
                 data_type: checked_rhs.data_type,
                 selected: checked_rhs.selected,
                 function_called: checked_rhs.function_called
             })
         } else if let Some(data_type) = checked_lhs.data_type {
             if let DataType::Custom(inner) = data_type.data.data.clone() {
-                if let Some(class) = self.scope.get().get_class(self.trace.clone(), inner) {
-                    let prev_scope = self.scope.clone();
-                    let prev_trace = self.trace.clone();
+                if let Some(class) = self.table.get_class(self.scope, self.visibility, inner) {
+                    let prev_scope = self.scope;
+                    let prev_visibility = self.visibility;
                     let prev_selected = self.selected;
-                    self.scope = class;
-                    self.trace = Trace::full();
+                    self.scope = class.scope();
+                    self.visibility = Visibility::All;
                     self.selected = true;
                     
                     let base_scope_changed = if self.base_scope.is_none() {
-                        self.base_scope = Some(prev_scope.clone());
+                        self.base_scope = Some(prev_scope);
                         true
                     } else {
                         false
@@ -489,16 +818,16 @@ impl Checker {
                     let checked_rhs = self.check_node(*rhs.clone())?;
                     
                     self.scope = prev_scope;
-                    self.trace = prev_trace;
+                    self.visibility = prev_visibility;
                     self.selected = prev_selected;
     
                     if base_scope_changed {
                         self.base_scope = None;
                     }
     
-                    if let Some(selected_rhs) = &checked_rhs.selected {
+                    if let Some(selected_rhs) = checked_rhs.selected {
                         self.check_access_modifier(node.convert(()), selected_rhs)?;
-                    } else if let Some(function_called) = &checked_rhs.function_called {
+                    } else if let Some(function_called) = checked_rhs.function_called {
                         self.check_access_modifier(node.convert(()), function_called)?;
                     }
     
@@ -513,16 +842,134 @@ impl Checker {
                         function_called: checked_rhs.function_called
                     })
                 } else {
-                    return Err(CheckerError::SymbolNotFound(data_type.clone().data.convert(data_type.data.data.to_string())));
+                    return Err(self.symbol_not_found(data_type.clone().data.convert(data_type.data.data.to_string())));
                 }
             } else {
-                return Err(CheckerError::SymbolNotFound(data_type.data.convert(data_type.data.data.to_string())));
+                return Err(self.symbol_not_found(data_type.data.convert(data_type.data.data.to_string())));
             }
         } else {
             return Err(CheckerError::CannotAccessAnythingHere(lhs.convert(())));
         }
     }
 
+    fn is_integer_type(data_type: &Option<Scoped<Positioned<DataType>>>) -> bool {
+        if let Some(data_type) = data_type {
+            if let DataType::Custom(inner) = &data_type.data.data {
+                return matches!(inner.as_str(), "c_int" | "c_uint" | "c_long" | "c_ulong" | "c_size_t" | "I32");
+            }
+        }
+        false
+    }
+
+    fn get_operator_interface(operator: &Operator) -> Option<(&'static str, &'static str)> {
+        match operator {
+            Operator::Add => Some(("Add", "add")),
+            Operator::Subtract => Some(("Sub", "sub")),
+            Operator::Multiply => Some(("Mul", "mul")),
+            Operator::Divide => Some(("Div", "div")),
+            Operator::Equal |
+            Operator::NotEqual => Some(("Eq", "eq")),
+            Operator::Less |
+            Operator::LessOrEqual |
+            Operator::Greater |
+            Operator::GreaterOrEqual => Some(("Ord", "cmp")),
+            _ => None
+        }
+    }
+
+    fn get_operand_class(&self, data_type: &Option<Scoped<Positioned<DataType>>>) -> Option<SymbolId> {
+        let scope = data_type.as_ref()?.scope?;
+        if let ScopeType::Class { .. } = self.table.get(scope).scope {
+            Some(scope)
+        } else {
+            None
+        }
+    }
+
+    fn check_operator_overload(&mut self, node: Positioned<Node>, class: SymbolId, checked_lhs: NodeInfo, checked_rhs: NodeInfo) -> Result<NodeInfo, CheckerError> {
+        let Node::BinaryOperation { lhs, operator, rhs } = node.data.clone() else {
+            unreachable!()
+        };
+
+        let Some((interface_name, method_name)) = Self::get_operator_interface(&operator.data) else {
+            unreachable!()
+        };
+
+        let ScopeType::Class { name: class_name, extensions, .. } = self.table.get(class).scope.clone() else {
+            unreachable!()
+        };
+
+        // Check if the class implements the interface of the operator
+        let mut implemented = false;
+        for extension in extensions.iter() {
+            if let ScopeType::Interface { name, .. } = &self.table.get(*extension).scope {
+                if name.data == interface_name {
+                    implemented = true;
+                    break;
+                }
+            }
+        }
+        if !implemented {
+            return Err(CheckerError::OperatorNotImplemented(operator.convert(()), class_name.data.clone(), interface_name.to_string()));
+        }
+
+        // Select the implementation (self + other)
+        let Some(method) = self.table.enter_function(class.scope(), Visibility::All, method_name.to_string(), false, true) else {
+            return Err(CheckerError::OperatorNotImplemented(operator.convert(()), class_name.data.clone(), interface_name.to_string()));
+        };
+        self.check_access_modifier(node.convert(()), method)?;
+
+        let processed_name = self.table.process_name(method);
+        let ScopeType::Function { params, return_type, .. } = self.table.get(method).scope.clone() else {
+            unreachable!()
+        };
+
+        if params.len() != 2 {
+            return Err(CheckerError::FunctionNotMatching(operator.convert(method_name.to_string()), operator.convert(interface_name.to_string()), self.table.get(method).pos.clone()));
+        }
+        self.check_data_type(rhs.convert(()), params[1].data_type.clone(), checked_rhs.data_type.map(|x| x.data))?;
+
+        let call = node.convert(Node::_Renamed {
+            name: processed_name,
+            node: Box::new(node.convert(Node::FunctionCall {
+                name: operator.convert(method_name.to_string()),
+                parameters: vec![checked_lhs.checked, checked_rhs.checked],
+                scope: Some(method)
+            }))
+        });
+
+        // Comparisons are expressed through the result of the call
+        let (checked, data_type) = match operator.data {
+            Operator::NotEqual => (node.convert(Node::UnaryOperation {
+                operator: operator.convert(Operator::BooleanNot),
+                value: Box::new(call)
+            }), return_type.clone()),
+            Operator::Less |
+            Operator::LessOrEqual |
+            Operator::Greater |
+            Operator::GreaterOrEqual => (node.convert(Node::BinaryOperation {
+                lhs: Box::new(call),
+                operator,
+                rhs: Box::new(node.convert(Node::Value(ValueNode::Integer("0".to_string()))))
+            }), Some(Scoped {
+                data: node.convert(DataType::Custom("Bool".to_string())),
+                scope: None
+            })),
+            _ => (call, return_type.clone())
+        };
+
+        if data_type.is_none() {
+            return Err(CheckerError::UnexpectedType(lhs.convert(None), None));
+        }
+
+        Ok(NodeInfo {
+            checked,
+            data_type,
+            selected: None,
+            function_called: Some(method)
+        })
+    }
+
     fn check_binary_operation(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
         let Node::BinaryOperation { lhs, operator, rhs } = node.data.clone() else {
             unreachable!()
@@ -532,13 +979,16 @@ impl Checker {
             Operator::Add |
             Operator::Subtract |
             Operator::Multiply |
-            Operator::Divide |
-            Operator::BooleanAnd |
-            Operator::BooleanOr |
-            Operator::BooleanXor => {
+            Operator::Divide => {
                 let checked_lhs = self.check_node(*lhs.clone())?;
                 let checked_rhs = self.check_node(*rhs.clone())?;
 
+                if let Some(class) = self.get_operand_class(&checked_lhs.data_type) {
+                    if Self::get_operator_interface(&operator.data).is_some() {
+                        return self.check_operator_overload(node, class, checked_lhs, checked_rhs);
+                    }
+                }
+
                 match (checked_lhs.data_type, checked_rhs.data_type) {
                     (Some(lhs_type), Some(rhs_type)) => {
                         self.check_data_type(rhs.convert(()), lhs_type.data.clone(), Some(rhs_type.data))?;
@@ -558,6 +1008,29 @@ impl Checker {
                 }
                 
             },
+            Operator::BooleanAnd |
+            Operator::BooleanOr |
+            Operator::BooleanXor => {
+                let checked_lhs = self.check_node(*lhs.clone())?;
+                let checked_rhs = self.check_node(*rhs.clone())?;
+
+                self.check_data_type(lhs.convert(()), operator.convert(DataType::Custom("Bool".to_string())), checked_lhs.data_type.map(|x| x.data))?;
+                self.check_data_type(rhs.convert(()), operator.convert(DataType::Custom("Bool".to_string())), checked_rhs.data_type.map(|x| x.data))?;
+
+                Ok(NodeInfo {
+                    checked: node.convert(Node::BinaryOperation { 
+                        lhs: Box::new(checked_lhs.checked), 
+                        operator, 
+                        rhs: Box::new(checked_rhs.checked)
+                    }),
+                    data_type: Some(Scoped {
+                        data: node.convert(DataType::Custom("Bool".to_string())),
+                        scope: None,
+                    }),
+                    selected: None,
+                    function_called: None
+                })
+            },
             Operator::Equal |
             Operator::NotEqual |
             Operator::Less |
@@ -567,6 +1040,10 @@ impl Checker {
                 let checked_lhs = self.check_node(*lhs.clone())?;
                 let checked_rhs = self.check_node(*rhs.clone())?;
 
+                if let Some(class) = self.get_operand_class(&checked_lhs.data_type) {
+                    return self.check_operator_overload(node, class, checked_lhs, checked_rhs);
+                }
+
                 match (checked_lhs.data_type, checked_rhs.data_type) {
                     (Some(lhs_type), Some(rhs_type)) => {
                         self.check_data_type(rhs.convert(()), lhs_type.data.clone(), Some(rhs_type.data))?;
@@ -577,7 +1054,7 @@ impl Checker {
                                 rhs: Box::new(checked_rhs.checked)
                             }),
                             data_type: Some(Scoped {
-                                data: node.convert(DataType::Custom("c_int".to_string())),
+                                data: node.convert(DataType::Custom("Bool".to_string())),
                                 scope: None,
                             }),
                             selected: None,
@@ -588,7 +1065,46 @@ impl Checker {
                     (_, _) => Err(CheckerError::UnexpectedType(lhs.convert(None), None)),
                 }
             }
+            Operator::Modulo |
+            Operator::BitwiseAnd |
+            Operator::BitwiseOr |
+            Operator::BitwiseXor |
+            Operator::ShiftLeft |
+            Operator::ShiftRight => {
+                let checked_lhs = self.check_node(*lhs.clone())?;
+                let checked_rhs = self.check_node(*rhs.clone())?;
+
+                if !Self::is_integer_type(&checked_lhs.data_type) {
+                    return Err(CheckerError::ExpectedIntegerType(lhs.convert(checked_lhs.data_type.map(|x| x.data.data.to_string())), operator.convert(())));
+                }
+                if !Self::is_integer_type(&checked_rhs.data_type) {
+                    return Err(CheckerError::ExpectedIntegerType(rhs.convert(checked_rhs.data_type.map(|x| x.data.data.to_string())), operator.convert(())));
+                }
+
+                let lhs_type = checked_lhs.data_type.unwrap();
+                self.check_data_type(rhs.convert(()), lhs_type.data.clone(), checked_rhs.data_type.map(|x| x.data))?;
+                Ok(NodeInfo {
+                    checked: node.convert(Node::BinaryOperation { 
+                        lhs: Box::new(checked_lhs.checked), 
+                        operator, 
+                        rhs: Box::new(checked_rhs.checked)
+                    }),
+                    data_type: Some(lhs_type),
+                    selected: None,
+                    function_called: None
+                })
+            }
             Operator::Assign => self.check_assignment(node),
+            Operator::AddAssign |
+            Operator::SubtractAssign |
+            Operator::MultiplyAssign |
+            Operator::DivideAssign |
+            Operator::ModuloAssign |
+            Operator::BitwiseAndAssign |
+            Operator::BitwiseOrAssign |
+            Operator::BitwiseXorAssign |
+            Operator::ShiftLeftAssign |
+            Operator::ShiftRightAssign => self.check_compound_assignment(node),
             Operator::Access |
             Operator::DotAccess => self.check_access(node),
             _ => unreachable!()
@@ -600,7 +1116,13 @@ impl Checker {
             unreachable!()
         };
 
-        let checked_value = self.check_node(*value)?;
+        let checked_value = self.check_node(*value.clone())?;
+
+        if operator.data == Operator::BitwiseNot && !Self::is_integer_type(&checked_value.data_type) {
+            return Err(CheckerError::ExpectedIntegerType(value.convert(checked_value.data_type.map(|x| x.data.data.to_string())), operator.convert(())));
+        } else if operator.data == Operator::BooleanNot {
+            self.check_data_type(value.convert(()), operator.convert(DataType::Custom("Bool".to_string())), checked_value.data_type.clone().map(|x| x.data))?;
+        }
 
         Ok(NodeInfo { 
             checked: node.convert(Node::UnaryOperation { 
@@ -618,20 +1140,20 @@ impl Checker {
         };
 
         // Check Type
-        let mut scope = self.scope.clone();
+        let mut scope = self.scope;
         loop {
-            if let ScopeType::Function { .. } = &scope.get().scope {
+            if let ScopeType::Function { .. } = &self.table.get(scope).scope {
                 break;
             };
 
-            if let Some(parent) = scope.get().parent.clone() {
+            if let Some(parent) = self.table.parent(scope) {
                 scope = parent;
             } else {
                 unreachable!()
             }
         }
 
-        let ScopeType::Function { return_type, .. } = &mut scope.get().scope else {
+        let ScopeType::Function { return_type, .. } = self.table.get(scope).scope.clone() else {
             unreachable!()
         };
 
@@ -639,11 +1161,11 @@ impl Checker {
             if let Some(expr) = expr {
                 let checked_expr = self.check_node(*expr.clone())?;
                 if let Some(selected) = checked_expr.selected {
-                    if let ScopeType::Variable { name, initialized, data_type, .. } = &selected.get().scope {
+                    if let ScopeType::Variable { name, initialized, data_type, .. } = self.table.get(selected).scope.clone() {
                         if !initialized { 
-                            return Err(CheckerError::VariableNotInitialized(selected.get().pos.convert(name.data.clone())))
+                            return Err(CheckerError::VariableNotInitialized(self.table.get(selected).pos.convert(name.data.clone())))
                         } if data_type.is_none() {
-                            return Err(CheckerError::CannotInferType(selected.get().pos.convert(name.data.clone())));
+                            return Err(CheckerError::CannotInferType(self.table.get(selected).pos.convert(name.data.clone())));
                         } 
                     }
                 }
@@ -669,19 +1191,22 @@ impl Checker {
     }
 
     fn check_class_definition(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
-        let Node::ClassDefinition { name, body, access, extensions } = node.data.clone() else {
+        let Node::ClassDefinition { name, body, access, extensions, external, module, scope } = node.data.clone() else {
             unreachable!()
         };
+        if external.is_none() {
+            self.check_reserved_name(&name, true)?;
+        }
 
         // Enter Scope
-        if let Some(class) = self.scope.get().enter_class(Trace::full(), name.data.clone()) {
-            self.scope = class;
-        } else {
-            unreachable!("Symbol '{}' not found", name.data);
-        }
+        let Some(class) = scope else {
+            unreachable!("Class '{}' was not symbolized", name.data);
+        };
+        self.table.declare(class);
+        self.scope = class.scope();
 
         // Check if all functions are implemented
-        let ScopeType::Class { extensions: extensions_scope, children, .. } = &mut self.scope.get().scope else {
+        let ScopeType::Class { extensions: extensions_scope, children, .. } = self.table.get(self.scope).scope.clone() else {
             unreachable!()
         };
 
@@ -689,16 +1214,18 @@ impl Checker {
         let mut index = 0;
         let mut implementations = Vec::new();
         for extension in extensions_scope.iter() {
-            let ScopeType::Interface { children: intf_children, .. } = extension.get().scope.clone() else {
+            let ScopeType::Interface { children: intf_children, .. } = &self.table.get(*extension).scope else {
                 continue;
             };
             'B: for intf_child in intf_children.iter() {
+                let intf_child = self.table.get(*intf_child);
                 let ScopeType::Function { name: intf_fun_name, params: intf_fun_params, return_type: intf_fun_return_type, .. } = &intf_child.scope else {
                     continue;
                 };
                 // Check if the class contains the same function
-                for child in children.iter_mut() {
-                    let ScopeType::Function { name: class_fun_name, params: class_fun_params, return_type: class_fun_return_type, constructor, .. } = &mut child.scope else {
+                for child in children.iter() {
+                    let child = self.table.get(*child);
+                    let ScopeType::Function { name: class_fun_name, params: class_fun_params, return_type: class_fun_return_type, constructor, .. } = &child.scope else {
                         continue;
                     };
 
@@ -753,7 +1280,6 @@ impl Checker {
 
         // Check Body
         let mut new_body = Vec::new();
-        self.trace = Trace::new(0, self.trace.clone());
         for child in body {
             let mut checked_child = self.check_node(child.clone())?;
             if let Node::FunctionDefinition { name, .. } = child.data.clone() {
@@ -765,15 +1291,10 @@ impl Checker {
                 }
             }
             new_body.push(checked_child.checked);
-            if !child.data.is_generated() {
-                self.trace.index += 1;
-            }
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
 
         // Exit Scope
-        if let Some(parent) = self.scope.get().parent.clone() {
+        if let Some(parent) = self.table.parent(self.scope) {
             self.scope = parent;
         } else {
             unreachable!("Not parent after entering function!");
@@ -784,7 +1305,10 @@ impl Checker {
                 name, 
                 body: new_body,
                 access,
-                extensions
+                extensions,
+                external,
+                module,
+                scope
             }),
             data_type: None,
             selected: None,
@@ -793,32 +1317,27 @@ impl Checker {
     }
 
     fn check_space_definition(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
-        let Node::SpaceDefinition { name, body, access } = node.data.clone() else {
+        let Node::SpaceDefinition { name, body, access, module, scope } = node.data.clone() else {
             unreachable!()
         };
+        self.check_reserved_name(&name, false)?;
 
         // Enter Scope
-        if let Some(class) = self.scope.get().enter_space(self.trace.clone(), name.data.clone()) {
-            self.scope = class;
-        } else {
-            unreachable!("Symbol '{}' not found", name.data);
-        }
+        let Some(space) = scope else {
+            unreachable!("Space '{}' was not symbolized", name.data);
+        };
+        self.table.declare(space);
+        self.scope = space.scope();
 
         // Check Body
         let mut new_body = Vec::new();
-        self.trace = Trace::new(0, self.trace.clone());
         for child in body {
-            let checked_child = self.check_node(child.clone())?;
+            let checked_child = self.check_node(child)?;
             new_body.push(checked_child.checked);
-            if !child.data.is_generated() {
-                self.trace.index += 1;
-            }
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
 
         // Exit Scope
-        if let Some(parent) = self.scope.get().parent.clone() {
+        if let Some(parent) = self.table.parent(self.scope) {
             self.scope = parent;
         } else {
             unreachable!("Not parent after entering function!");
@@ -828,7 +1347,9 @@ impl Checker {
             checked: node.convert(Node::SpaceDefinition { 
                 name, 
                 body: new_body,
-                access
+                access,
+                module,
+                scope
             }),
             data_type: None,
             selected: None,
@@ -837,60 +1358,46 @@ impl Checker {
     }
 
     fn check_if_statement(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
-        let Node::IfStatement { condition, body, elif_branches, else_body } = node.data.clone() else {
+        let Node::IfStatement { condition, body, elif_branches, else_body, scopes } = node.data.clone() else {
             unreachable!()
         };
 
         let checked_condition = self.check_node(*condition)?;
-        self.check_data_type(checked_condition.checked.convert(()), node.convert(DataType::Custom("c_int".to_string())), checked_condition.data_type.map(|x| x.data))?;
+        self.check_data_type(checked_condition.checked.convert(()), node.convert(DataType::Custom("Bool".to_string())), checked_condition.data_type.map(|x| x.data))?;
 
         let mut checked_body = Vec::new();
         
         // Enter Scope
-        self.scope = self.scope.get().get_child(self.trace.index);
+        self.scope = scopes[0];
 
-        self.trace = Trace::new(0, self.trace.clone());
         for node in body {
-            let checked_node = self.check_node(node.clone())?;
+            let checked_node = self.check_node(node)?;
             checked_body.push(checked_node.checked);
-            if !node.data.is_generated() {
-                self.trace.index += 1;
-            }
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
-        self.trace.index += 1;
 
         // Exit Scope
-        if let Some(parent) = self.scope.get().parent.clone() {
+        if let Some(parent) = self.table.parent(self.scope) {
             self.scope = parent;
         } else {
             unreachable!("Not parent after entering function!");
         }
 
         let mut checked_elif_branches = Vec::new();
-        for elif_branch in elif_branches {
+        for (elif_branch, elif_scope) in elif_branches.into_iter().zip(&scopes[1..]) {
             let checked_condition = self.check_node(elif_branch.condition)?;
-            self.check_data_type(checked_condition.checked.convert(()), node.convert(DataType::Custom("c_int".to_string())), checked_condition.data_type.map(|x| x.data))?;
+            self.check_data_type(checked_condition.checked.convert(()), node.convert(DataType::Custom("Bool".to_string())), checked_condition.data_type.map(|x| x.data))?;
             
             // Enter Scope
-            self.scope = self.scope.get().get_child(self.trace.index);
+            self.scope = *elif_scope;
 
             let mut checked_body = Vec::new();
-            self.trace = Trace::new(0, self.trace.clone());
             for node in elif_branch.body {
-                let checked_node = self.check_node(node.clone())?;
+                let checked_node = self.check_node(node)?;
                 checked_body.push(checked_node.checked);
-                if !node.data.is_generated() {
-                    self.trace.index += 1;
-                }
             }
-            let parent_trace = *self.trace.clone().parent.unwrap();
-            self.trace = parent_trace;
-            self.trace.index += 1;
 
             // Exit Scope
-            if let Some(parent) = self.scope.get().parent.clone() {
+            if let Some(parent) = self.table.parent(self.scope) {
                 self.scope = parent;
             } else {
                 unreachable!("Not parent after entering function!");
@@ -905,21 +1412,15 @@ impl Checker {
         // Enter Scope
         let mut checked_else_body = Vec::new();
         if !else_body.is_empty() {
-            self.scope = self.scope.get().get_child(self.trace.index);
+            self.scope = *scopes.last().unwrap();
     
-            self.trace = Trace::new(0, self.trace.clone());
             for node in else_body {
-                let checked_node = self.check_node(node.clone())?;
+                let checked_node = self.check_node(node)?;
                 checked_else_body.push(checked_node.checked);
-                if !node.data.is_generated() {
-                    self.trace.index += 1;
-                }
             }
-            let parent_trace = *self.trace.clone().parent.unwrap();
-            self.trace = parent_trace;
     
             // Exit Scope
-            if let Some(parent) = self.scope.get().parent.clone() {
+            if let Some(parent) = self.table.parent(self.scope) {
                 self.scope = parent;
             } else {
                 unreachable!("Not parent after entering function!");
@@ -931,7 +1432,41 @@ impl Checker {
                 condition: Box::new(checked_condition.checked), 
                 body: checked_body, 
                 elif_branches: checked_elif_branches, 
-                else_body: checked_else_body 
+                else_body: checked_else_body,
+                scopes 
+            }), 
+            data_type: None, 
+            selected: None, 
+            function_called: None 
+        })
+    }
+
+    fn check_generated_if_statement(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
+        let Node::IfStatement { condition, body, elif_branches, else_body, scopes } = node.data.clone() else {
+            unreachable!()
+        };
+
+        // Generated branches have no scope, their symbols live in the function
+        let checked_condition = self.check_node(*condition)?;
+        self.check_data_type(checked_condition.checked.convert(()), node.convert(DataType::Custom("Bool".to_string())), checked_condition.data_type.map(|x| x.data))?;
+
+        let mut checked_body = Vec::new();
+        for node in body {
+            checked_body.push(self.check_node(node)?.checked);
+        }
+
+        let mut checked_else_body = Vec::new();
+        for node in else_body {
+            checked_else_body.push(self.check_node(node)?.checked);
+        }
+
+        Ok(NodeInfo { 
+            checked: node.convert(Node::IfStatement { 
+                condition: Box::new(checked_condition.checked), 
+                body: checked_body, 
+                elif_branches, 
+                else_body: checked_else_body,
+                scopes 
             }), 
             data_type: None, 
             selected: None, 
@@ -940,32 +1475,28 @@ impl Checker {
     }
 
     fn check_while_loop(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
-        let Node::WhileLoop { condition, body } = node.data.clone() else {
+        let Node::WhileLoop { condition, body, scope } = node.data.clone() else {
             unreachable!()
         };
 
         let checked_condition = self.check_node(*condition)?;
-        self.check_data_type(checked_condition.checked.convert(()), node.convert(DataType::Custom("c_int".to_string())), checked_condition.data_type.map(|x| x.data))?;
+        self.check_data_type(checked_condition.checked.convert(()), node.convert(DataType::Custom("Bool".to_string())), checked_condition.data_type.map(|x| x.data))?;
 
         let mut checked_body = Vec::new();
         
         // Enter Scope
-        self.scope = self.scope.get().get_child(self.trace.index);
+        let Some(branch) = scope else {
+            unreachable!("Loop was not symbolized");
+        };
+        self.scope = branch;
 
-        self.trace = Trace::new(0, self.trace.clone());
         for node in body {
-            let checked_node = self.check_node(node.clone())?;
+            let checked_node = self.check_node(node)?;
             checked_body.push(checked_node.checked);
-            if !node.data.is_generated() {
-                self.trace.index += 1;
-            }
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
-        self.trace.index += 1;
 
         // Exit Scope
-        if let Some(parent) = self.scope.get().parent.clone() {
+        if let Some(parent) = self.table.parent(self.scope) {
             self.scope = parent;
         } else {
             unreachable!("Not parent after entering function!");
@@ -974,7 +1505,8 @@ impl Checker {
         Ok(NodeInfo {
             checked: node.convert(Node::WhileLoop { 
                 condition: Box::new(checked_condition.checked), 
-                body: checked_body 
+                body: checked_body,
+                scope 
             }),
             data_type: None,
             selected: None,
@@ -990,9 +1522,9 @@ impl Checker {
         // Check if in loop
         let label_found = label.is_none();
         let mut loop_found = false;
-        let mut scope = self.scope.clone();
+        let mut scope = self.scope;
         loop {
-            if let ScopeType::Branch { label: branch_label, debug_name, .. } = &scope.get().scope {
+            if let ScopeType::Branch { label: branch_label, debug_name, .. } = &self.table.get(scope).scope {
                 if debug_name == "While" {
                     loop_found = true;
                     if label_found {
@@ -1005,12 +1537,12 @@ impl Checker {
                 }
             };
 
-            if let Some(parent) = scope.get().parent.clone() {
+            if let Some(parent) = self.table.parent(scope) {
                 scope = parent;
             } else if !loop_found {
                 return Err(CheckerError::BreakStatementShouldOnlyBeFoundInLoops(node.convert(())))
             } else {
-                return Err(CheckerError::SymbolNotFound(label.unwrap()));
+                return Err(CheckerError::SymbolNotFound(label.unwrap(), None));
             }
         }
 
@@ -1030,9 +1562,9 @@ impl Checker {
         // Check if in loop
         let label_found = label.is_none();
         let mut loop_found = false;
-        let mut scope = self.scope.clone();
+        let mut scope = self.scope;
         loop {
-            if let ScopeType::Branch { label: branch_label, debug_name, .. } = &scope.get().scope {
+            if let ScopeType::Branch { label: branch_label, debug_name, .. } = &self.table.get(scope).scope {
                 if debug_name == "While" {
                     loop_found = true;
                     if label_found {
@@ -1045,12 +1577,12 @@ impl Checker {
                 }
             };
 
-            if let Some(parent) = scope.get().parent.clone() {
+            if let Some(parent) = self.table.parent(scope) {
                 scope = parent;
             } else if !loop_found {
                 return Err(CheckerError::ContinueStatementShouldOnlyBeFoundInLoops(node.convert(())))
             } else {
-                return Err(CheckerError::SymbolNotFound(label.unwrap()));
+                return Err(CheckerError::SymbolNotFound(label.unwrap(), None));
             }
         }
 
@@ -1081,32 +1613,27 @@ impl Checker {
     }
 
     fn check_interface_definition(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
-        let Node::InterfaceDefinition { name, body, access } = node.data.clone() else {
+        let Node::InterfaceDefinition { name, body, access, module, scope } = node.data.clone() else {
             unreachable!()
         };
+        self.check_reserved_name(&name, true)?;
 
         // Enter Scope
-        if let Some(interface) = self.scope.get().enter_interface(Trace::full(), name.data.clone()) {
-            self.scope = interface;
-        } else {
-            unreachable!("Symbol '{}' not found", name.data);
-        }
+        let Some(interface) = scope else {
+            unreachable!("Interface '{}' was not symbolized", name.data);
+        };
+        self.table.declare(interface);
+        self.scope = interface.scope();
 
         // Check Body
         let mut new_body = Vec::new();
-        self.trace = Trace::new(0, self.trace.clone());
         for child in body {
-            let checked_child = self.check_node(child.clone())?;
+            let checked_child = self.check_node(child)?;
             new_body.push(checked_child.checked);
-            if !child.data.is_generated() {
-                self.trace.index += 1;
-            }
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
 
         // Exit Scope
-        if let Some(parent) = self.scope.get().parent.clone() {
+        if let Some(parent) = self.table.parent(self.scope) {
             self.scope = parent;
         } else {
             unreachable!("Not parent after entering function!");
@@ -1116,7 +1643,9 @@ impl Checker {
             checked: node.convert(Node::InterfaceDefinition { 
                 name, 
                 body: new_body,
-                access
+                access,
+                module,
+                scope
             }),
             data_type: None,
             selected: None,
@@ -1124,6 +1653,66 @@ impl Checker {
         })
     }
  
+    fn check_lambda(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
+        let Node::_Lambda { function, .. } = node.data.clone() else {
+            unreachable!()
+        };
+
+        let Node::FunctionDefinition { name, external, constructor, parameters, return_type, body, access, module, scope } = function.data.clone() else {
+            unreachable!()
+        };
+        let parameters = self.check_parameters(parameters)?;
+
+        // Enter Scope
+        let Some(lambda) = scope else {
+            unreachable!("Lambda '{}' was not symbolized", name.data);
+        };
+        self.table.get_mut(lambda).parent = Some(self.scope);
+        self.table.declare(lambda);
+        let parent_scope = self.scope;
+        self.scope = lambda.scope();
+        self.lambdas.push((lambda.scope(), Vec::new()));
+
+        // Check Body
+        let mut new_body = Vec::new();
+        for child in body {
+            let checked_child = self.check_node(child)?;
+            new_body.push(checked_child.checked);
+        }
+
+        // Exit Scope
+        let (_, captures) = self.lambdas.pop().unwrap();
+        self.scope = parent_scope;
+
+        let data_type = DataType::Function { 
+            return_type: return_type.clone().map(Box::new), 
+            params: parameters.iter().map(|param| param.data_type.clone()).collect() 
+        };
+
+        Ok(NodeInfo { 
+            checked: node.convert(Node::_Lambda { 
+                function: Box::new(function.convert(Node::FunctionDefinition { 
+                    name, 
+                    external, 
+                    constructor, 
+                    parameters, 
+                    return_type, 
+                    body: new_body, 
+                    access,
+                    module,
+                    scope 
+                })), 
+                captures 
+            }), 
+            data_type: Some(Scoped {
+                data: node.convert(data_type),
+                scope: None
+            }), 
+            selected: None, 
+            function_called: None 
+        })
+    }
+
     fn check_node(&mut self, node: Positioned<Node>) -> Result<NodeInfo, CheckerError> {
         match node.data {
             Node::Value(_) => self.check_value_node(node),
@@ -1131,7 +1720,7 @@ impl Checker {
             Node::FunctionCall { .. } => self.check_function_call(node),
             Node::Use(_) => unreachable!("Should have been separated in the IR Generator and should have panicked in the symbolizer!"),
             Node::VariableDefinition { .. } => self.check_variable_definition(node),
-            Node::VariableCall(_) => self.check_variable_call(node),
+            Node::VariableCall { .. } => self.check_variable_call(node),
             Node::BinaryOperation { .. } => self.check_binary_operation(node),
             Node::UnaryOperation { .. } => self.check_unary_operation(node),
             Node::Return(_) => self.check_return(node),
@@ -1144,6 +1733,13 @@ impl Checker {
             Node::Continue(_) => self.check_continue(node),
             Node::Label { .. } => self.check_label(node),
             Node::InterfaceDefinition { .. } => self.check_interface_definition(node),
+            Node::Lambda { .. } => unreachable!("Should have been processed in the IR Generator!"),
+            Node::Error => Ok(NodeInfo { 
+                checked: node, 
+                data_type: None, 
+                selected: None,
+                function_called: None
+            }),
             Node::_Unchecked(inner) => {
                 if let Node::_Generated(inner2) = inner.data {
                     Ok(NodeInfo { 
@@ -1164,11 +1760,21 @@ impl Checker {
             Node::_Optional(_) => unreachable!("Unexpected _Optional"),
             Node::_Renamed { .. } => unreachable!("Unexpected _Renamed"),
             Node::_Implementation { .. } => unreachable!("Unexpected _Implementation"),
-            Node::_Generated(inner) => self.check_node(*inner),
+            Node::_Lambda { .. } => self.check_lambda(node),
+            Node::_ClosureCall { .. } => unreachable!("Unexpected _ClosureCall"),
+            Node::_Generated(inner) => {
+                if let Node::IfStatement { .. } = inner.data {
+                    self.check_generated_if_statement(*inner)
+                } else {
+                    self.generated = matches!(inner.data, Node::VariableDefinition { .. });
+                    self.check_node(*inner)
+                }
+            }
         }
     }
 
-    fn check_inference(&mut self, scope: &Scope) -> Result<(), CheckerError> {
+    fn check_inference(&mut self, id: ScopeId) -> Result<(), CheckerError> {
+        let scope = self.table.get(id);
         match &scope.scope {
             ScopeType::Root { children } |
             ScopeType::Class { children, .. } |
@@ -1176,8 +1782,8 @@ impl Checker {
             ScopeType::Space {children, .. } |
             ScopeType::Branch { children, .. } |
             ScopeType::Interface { children, .. } => {
-                for scope in children.iter() {
-                    self.check_inference(scope)?;
+                for child in children.clone() {
+                    self.check_inference(child)?;
                 }
             },
             ScopeType::Variable { name, data_type, initialized, .. } => {
@@ -1192,50 +1798,60 @@ impl Checker {
         Ok(())
     }
 
+    /// Gives their inferred type to the variables defined without one.
+    fn set_inferred_types(&self, nodes: &mut [Positioned<Node>]) {
+        for node in nodes.iter_mut() {
+            self.set_inferred_type(node);
+        }
+    }
+
+    fn set_inferred_type(&self, node: &mut Positioned<Node>) {
+        match &mut node.data {
+            Node::VariableDefinition { data_type, value, scope, .. } => {
+                if let (None, Some(variable)) = (data_type.as_ref(), scope) {
+                    if let ScopeType::Variable { data_type: Some(inferred), .. } = &self.table.get(*variable).scope {
+                        *data_type = Some(inferred.data.clone());
+                    }
+                }
+                // Lambdas are defined in the value
+                if let Some(value) = value {
+                    self.set_inferred_type(value);
+                }
+            },
+            Node::FunctionDefinition { body, .. } |
+            Node::ClassDefinition { body, .. } |
+            Node::SpaceDefinition { body, .. } |
+            Node::WhileLoop { body, .. } => self.set_inferred_types(body),
+            Node::IfStatement { body, elif_branches, else_body, .. } => {
+                self.set_inferred_types(body);
+                for branch in elif_branches.iter_mut() {
+                    self.set_inferred_types(&mut branch.body);
+                }
+                self.set_inferred_types(else_body);
+            },
+            Node::Label { inner, .. } |
+            Node::_Renamed { node: inner, .. } |
+            Node::_Implementation(inner) |
+            Node::_Generated(inner) |
+            Node::_Lambda { function: inner, .. } => self.set_inferred_type(inner),
+            _ => {}
+        }
+    }
+
     pub fn check(&mut self) -> Result<IROutput, CheckerError> {
-        let root_scope = self.scope.clone(); // Saving root scope for later
 
         let mut output = IROutput { includes: self.ir_output.includes.clone() , ast: Vec::new() };
 
         while let Some(node) = self.current() {
-            output.ast.push(self.check_node(node.clone())?.checked);
+            output.ast.push(self.check_node(node)?.checked);
             self.advance();
-            if !node.data.is_generated() {
-                self.trace.index += 1;
-            }
         }
 
         // Check if all variables have been initialized and types inferred
-        self.check_inference(root_scope.get())?;
-
-        // Check if types have been inferred (and set the type to the node)
-        for (mut trace, data_type) in self.inferred.clone() {
-            let mut trace_back = Vec::new();
-            while trace.parent.is_some() {
-                trace_back.push(trace.index);
-                trace = *trace.clone().parent.unwrap();
-            }
-            trace_back.push(trace.index);
-
-            let mut list = MutRef::new(&mut output.ast);
-            for i in 0..(trace_back.len() - 1) {
-                let j = (trace_back.len() - 1) - i;
-                let trace_index = trace_back.get(j).unwrap();
-
-                let node = list.get().get_mut(*trace_index).unwrap();
-                match &mut node.data {
-                    Node::FunctionDefinition { body, .. } => list = MutRef::new(body),
-                    _ => unreachable!()
-                }
-            }
+        self.check_inference(SymbolTable::ROOT)?;
 
-            let node = list.get().get_mut(trace_back[0]).unwrap();
-            let Node::VariableDefinition { data_type: def_data_type, .. } = &mut node.data else {
-                unreachable!()
-            };
-
-            *def_data_type = Some(data_type);
-        }
+        // Set the inferred types to the nodes
+        self.set_inferred_types(&mut output.ast);
 
         Ok(output)
     }
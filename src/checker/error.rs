@@ -2,10 +2,11 @@
 //                                          Checker Error                                         //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
 
-use crate::util::{position::Positioned, source_file::SourceFile, error::{ErrorFormat, ErrorType}};
+use crate::util::{position::Positioned, source_file::SourceFile, error::{ErrorFormat, ErrorType}, mangle};
 
 pub enum CheckerError {
-    SymbolNotFound(Positioned<String>),
+    /// Symbol and the closest visible name, if any
+    SymbolNotFound(Positioned<String>, Option<String>),
     UnexpectedType(Positioned<Option<String>>, Option<Positioned<String>>),
     TooManyParameters(usize, usize, Positioned<String>, Positioned<()>),
     NotEnoughParameters(usize, usize, Positioned<String>, Positioned<()>),
@@ -21,16 +22,76 @@ pub enum CheckerError {
     LabelNotFound(Positioned<String>),
     FunctionNotImplemented(Positioned<String>, Positioned<String>),
     FunctionNotMatching(Positioned<String>, Positioned<String>, Positioned<()>),
+    OperatorNotImplemented(Positioned<()>, String, String),
+    ExpectedIntegerType(Positioned<Option<String>>, Positioned<()>),
+    ReservedName(Positioned<String>),
+    NameConflict(Positioned<String>, String, Positioned<String>),
 }
 
 impl CheckerError {
 
+    /// Name of the variant, used by the diagnostic tests.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CheckerError::SymbolNotFound(..) => "SymbolNotFound",
+            CheckerError::UnexpectedType(..) => "UnexpectedType",
+            CheckerError::TooManyParameters(..) => "TooManyParameters",
+            CheckerError::NotEnoughParameters(..) => "NotEnoughParameters",
+            CheckerError::VariableNotInitialized(..) => "VariableNotInitialized",
+            CheckerError::CannotAssignToConstantExpression(..) => "CannotAssignToConstantExpression",
+            CheckerError::CannotAssignToConstant(..) => "CannotAssignToConstant",
+            CheckerError::CannotInferType(..) => "CannotInferType",
+            CheckerError::CannotAccessAnythingHere(..) => "CannotAccessAnythingHere",
+            CheckerError::CannotAccessPrivateMember(..) => "CannotAccessPrivateMember",
+            CheckerError::CannotAccessProtectedMember(..) => "CannotAccessProtectedMember",
+            CheckerError::BreakStatementShouldOnlyBeFoundInLoops(..) => "BreakStatementShouldOnlyBeFoundInLoops",
+            CheckerError::ContinueStatementShouldOnlyBeFoundInLoops(..) => "ContinueStatementShouldOnlyBeFoundInLoops",
+            CheckerError::LabelNotFound(..) => "LabelNotFound",
+            CheckerError::FunctionNotImplemented(..) => "FunctionNotImplemented",
+            CheckerError::FunctionNotMatching(..) => "FunctionNotMatching",
+            CheckerError::OperatorNotImplemented(..) => "OperatorNotImplemented",
+            CheckerError::ExpectedIntegerType(..) => "ExpectedIntegerType",
+            CheckerError::ReservedName(..) => "ReservedName",
+            CheckerError::NameConflict(..) => "NameConflict",
+        }
+    }
+
+    /// Span of the main message of the error.
+    pub fn span(&self) -> Option<Positioned<()>> {
+        match self {
+            CheckerError::SymbolNotFound(symbol, _) => Some(symbol.convert(())),
+            CheckerError::UnexpectedType(found, _) => Some(found.convert(())),
+            CheckerError::TooManyParameters(_, _, call, _) => Some(call.convert(())),
+            CheckerError::NotEnoughParameters(_, _, call, _) => Some(call.convert(())),
+            CheckerError::VariableNotInitialized(name) => Some(name.convert(())),
+            CheckerError::CannotAssignToConstantExpression(expr) => Some(expr.clone()),
+            CheckerError::CannotAssignToConstant(expr, _) => Some(expr.clone()),
+            CheckerError::CannotInferType(var) => Some(var.convert(())),
+            CheckerError::CannotAccessAnythingHere(node) => Some(node.clone()),
+            CheckerError::CannotAccessPrivateMember(node, _) => Some(node.clone()),
+            CheckerError::CannotAccessProtectedMember(node, _) => Some(node.clone()),
+            CheckerError::BreakStatementShouldOnlyBeFoundInLoops(node) => Some(node.clone()),
+            CheckerError::ContinueStatementShouldOnlyBeFoundInLoops(node) => Some(node.clone()),
+            CheckerError::LabelNotFound(name) => Some(name.convert(())),
+            CheckerError::FunctionNotImplemented(fun, _) => Some(fun.convert(())),
+            CheckerError::FunctionNotMatching(fun, _, _) => Some(fun.convert(())),
+            CheckerError::OperatorNotImplemented(operator, _, _) => Some(operator.clone()),
+            CheckerError::ExpectedIntegerType(found, _) => Some(found.convert(())),
+            CheckerError::ReservedName(name) => Some(name.convert(())),
+            CheckerError::NameConflict(name, _, _) => Some(name.convert(())),
+        }
+    }
+
     pub fn print_error(&self, src: &SourceFile) {
         match self {
-            CheckerError::SymbolNotFound(symbol) => {
-                ErrorFormat::new(ErrorType::Error)
+            CheckerError::SymbolNotFound(symbol, suggestion) => {
+                let mut error_msg = ErrorFormat::new(ErrorType::Error)
                     .add_message(format!("Symbol '{}' not found", symbol.data), Some(symbol.convert(())))
-                    .set_step("Checker".to_string()).print(src);
+                    .set_step("Checker".to_string());
+                if let Some(suggestion) = suggestion {
+                    error_msg = error_msg.add_hint(format!("did you mean '{}'?", suggestion));
+                }
+                error_msg.print(src);
             },
             CheckerError::UnexpectedType(found, expected) => {
                 let mut error_msg = ErrorFormat::new(ErrorType::Error)
@@ -46,13 +107,13 @@ impl CheckerError {
             CheckerError::TooManyParameters(found, expected, call, definition) => {
                 ErrorFormat::new(ErrorType::Error)
                     .add_message(format!("Too many parameters, found '{}', expected '{}', for '{}'", found, expected, call.data), Some(call.convert(())))
-                    .add_message(format!("Definition here:"), Some(definition.clone()))
+                    .add_message("Definition here:".to_string(), Some(definition.clone()))
                     .set_step("Checker".to_string()).print(src);
             },
             CheckerError::NotEnoughParameters(found, expected, call, definition) => {
                 ErrorFormat::new(ErrorType::Error)
                     .add_message(format!("Not enough parameters, found '{}', expected '{}', for '{}'", found, expected, call.data), Some(call.convert(())))
-                    .add_message(format!("Definition here:"), Some(definition.clone()))
+                    .add_message("Definition here:".to_string(), Some(definition.clone()))
                     .set_step("Checker".to_string()).print(src);
             },
             CheckerError::VariableNotInitialized(name) => {
@@ -62,13 +123,13 @@ impl CheckerError {
             }
             CheckerError::CannotAssignToConstantExpression(expr) => {
                 ErrorFormat::new(ErrorType::Error)
-                    .add_message(format!("Cannot assign to constant expression! "), Some(expr.clone()))
+                    .add_message("Cannot assign to constant expression! ".to_string(), Some(expr.clone()))
                     .set_step("Checker".to_string()).print(src);
             }
             CheckerError::CannotAssignToConstant(expr, constant) => {
                 ErrorFormat::new(ErrorType::Error)
                     .add_message(format!("Cannot assign to constant '{}'! ", constant.data), Some(expr.clone()))
-                    .add_message(format!("Defined here: "), Some(constant.convert(())))
+                    .add_message("Defined here: ".to_string(), Some(constant.convert(())))
                     .set_step("Checker".to_string()).print(src);
             }
             CheckerError::CannotInferType(var) => {
@@ -78,29 +139,29 @@ impl CheckerError {
             }
             CheckerError::CannotAccessAnythingHere(node) => {
                 ErrorFormat::new(ErrorType::Error)
-                    .add_message(format!("Cannot selected anything here:"), Some(node.clone()))
+                    .add_message("Cannot selected anything here:".to_string(), Some(node.clone()))
                     .set_step("Checker".to_string()).print(src)
             }
             CheckerError::CannotAccessPrivateMember(node, definition) => {
                 ErrorFormat::new(ErrorType::Error)
-                    .add_message(format!("Cannot access private member:"), Some(node.clone()))
-                    .add_message(format!("Defined private here:"), Some(definition.clone()))
+                    .add_message("Cannot access private member:".to_string(), Some(node.clone()))
+                    .add_message("Defined private here:".to_string(), Some(definition.clone()))
                     .set_step("Checker".to_string()).print(src)
             },
             CheckerError::CannotAccessProtectedMember(node, definition) => {
                 ErrorFormat::new(ErrorType::Error)
-                    .add_message(format!("Cannot access protected member:"), Some(node.clone()))
-                    .add_message(format!("Defined private here:"), Some(definition.clone()))
+                    .add_message("Cannot access protected member:".to_string(), Some(node.clone()))
+                    .add_message("Defined private here:".to_string(), Some(definition.clone()))
                     .set_step("Checker".to_string()).print(src)
             },
             CheckerError::BreakStatementShouldOnlyBeFoundInLoops(node) => {
                 ErrorFormat::new(ErrorType::Error)
-                    .add_message(format!("Break statement should only be found in loops!"), Some(node.clone()))
+                    .add_message("Break statement should only be found in loops!".to_string(), Some(node.clone()))
                     .set_step("Checker".to_string()).print(src)
             },
             CheckerError::ContinueStatementShouldOnlyBeFoundInLoops(node) => {
                 ErrorFormat::new(ErrorType::Error)
-                    .add_message(format!("Continue statement should only be found in loops!"), Some(node.clone()))
+                    .add_message("Continue statement should only be found in loops!".to_string(), Some(node.clone()))
                     .set_step("Checker".to_string()).print(src)
             }
             CheckerError::LabelNotFound(name) => {
@@ -118,7 +179,29 @@ impl CheckerError {
                 ErrorFormat::new(ErrorType::Error)
                     .add_message(format!("Function '{}' is not implemented correctly!", fun.data), Some(fun.convert(())))
                     .add_message(format!("From '{}':", from.data), Some(from.convert(())))
-                    .add_message(format!("Defined here:"), Some(defined.convert(())))
+                    .add_message("Defined here:".to_string(), Some(defined.convert(())))
+                    .set_step("Checker".to_string()).print(src)
+            }
+            CheckerError::OperatorNotImplemented(operator, class, interface) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message(format!("Operator is not implemented for '{}', implement '{}'!", class, interface), Some(operator.clone()))
+                    .set_step("Checker".to_string()).print(src)
+            }
+            CheckerError::ExpectedIntegerType(found, operator) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message(format!("Expected integer type, found '{}':", found.data.clone().unwrap_or("-NoType".to_string())), Some(found.convert(())))
+                    .add_message("Required by operator:".to_string(), Some(operator.clone()))
+                    .set_step("Checker".to_string()).print(src)
+            }
+            CheckerError::ReservedName(name) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message(format!("'{}' is a reserved name!", name.data), Some(name.convert(())))
+                    .set_step("Checker".to_string()).print(src)
+            }
+            CheckerError::NameConflict(name, c_name, other) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message(format!("'{}' is named '{}' in C, like '{}'!", mangle::demangle(c_name), c_name, other.data), Some(name.convert(())))
+                    .add_message("Defined here:".to_string(), Some(other.convert(())))
                     .set_step("Checker".to_string()).print(src)
             }
         }
@@ -1,2 +1,3 @@
 pub mod error;
-pub mod checker;
\ No newline at end of file
+pub mod checker;
+pub mod types;
\ No newline at end of file
@@ -0,0 +1,265 @@
+use crate::{parser::node::DataType, util::position::{Positioned, Position}};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                        Type Equivalence                                        //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Equality where the C types and their Taly counterparts are interchangeable.
+pub fn type_equivalent(lhs: &DataType, rhs: &DataType) -> bool {
+    match (lhs, rhs) {
+        (DataType::Custom(lhs), DataType::Custom(rhs)) => match (lhs.as_str(), rhs.as_str()) {
+            ("c_string", "String") | ("String", "c_string") => true,
+            ("c_int", "I32") | ("I32", "c_int") => true,
+            // Integer literals also initialize the other C integers
+            ("c_uint" | "c_long" | "c_ulong" | "c_size_t", "I32") | ("I32", "c_uint" | "c_long" | "c_ulong" | "c_size_t") => true,
+            ("c_float", "F32") | ("F32", "c_float") => true,
+            (lhs, rhs) => lhs == rhs
+        },
+        (DataType::Custom(custom), other) | (other, DataType::Custom(custom)) if is_string(custom) => *other == c_string(),
+        (DataType::Pointer(lhs), DataType::Pointer(rhs)) => type_equivalent(&lhs.data, &rhs.data),
+        (DataType::Const(lhs), DataType::Const(rhs)) => type_equivalent(&lhs.data, &rhs.data),
+        (DataType::Function { return_type: lhs_return_type, params: lhs_params }, DataType::Function { return_type: rhs_return_type, params: rhs_params }) => {
+            let same_return_type = match (lhs_return_type, rhs_return_type) {
+                (None, None) => true,
+                (Some(lhs), Some(rhs)) => type_equivalent(&lhs.data, &rhs.data),
+                (_, _) => false
+            };
+
+            same_return_type &&
+                lhs_params.len() == rhs_params.len() &&
+                lhs_params.iter().zip(rhs_params.iter()).all(|(lhs, rhs)| type_equivalent(&lhs.data, &rhs.data))
+        },
+        (_, _) => false
+    }
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                        Type Assignability                                      //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// String literals and `c_string` are `*const c_char` once generated.
+fn is_string(name: &str) -> bool {
+    name == "c_string" || name == "String"
+}
+
+/// Type pointed to by strings.
+fn const_c_char() -> DataType {
+    let pos = Positioned::new((), Position::default(), Position::default());
+    DataType::Const(Box::new(pos.convert(DataType::Custom("c_char".to_string()))))
+}
+
+fn c_string() -> DataType {
+    let pos = Positioned::new((), Position::default(), Position::default());
+    DataType::Pointer(Box::new(pos.convert(const_c_char())))
+}
+
+fn is_pointer(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Pointer(_) => true,
+        DataType::Custom(custom) => is_string(custom),
+        DataType::Const(inner) => is_pointer(&inner.data),
+        _ => false
+    }
+}
+
+/// Whether a value of type `found` can be used where `expected` is required.
+/// Parameters and return types of functions are invariant for now: closures are called through
+/// a casted C function pointer, so both signatures have to be identical once generated.
+/// Pointers follow C: `const` can be added to the pointed type and any pointer converts to `*void`,
+/// `*const void` for the pointers to `const`.
+pub fn is_assignable(expected: &DataType, found: &DataType) -> bool {
+    if type_equivalent(expected, found) {
+        return true;
+    }
+
+    match (expected, found) {
+        // Values are copied, so the top-level `const` does not matter
+        (DataType::Const(expected), found) => is_assignable(&expected.data, found),
+        (expected, DataType::Const(found)) => is_assignable(expected, &found.data),
+        (DataType::Pointer(expected), found) if is_pointer(found) => {
+            let (expected, constant) = match &expected.data {
+                DataType::Const(inner) => (&inner.data, true),
+                other => (other, false)
+            };
+            let void = matches!(expected, DataType::Custom(custom) if custom == "void");
+
+            let found = match found {
+                DataType::Pointer(found) => found.data.clone(),
+                _ => const_c_char()
+            };
+            match &found {
+                // `const` cannot be removed
+                DataType::Const(_) => void && constant,
+                _ if void => true,
+                found => type_equivalent(expected, found)
+            }
+        },
+        (_, _) => false
+    }
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Tests                                              //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::node::DataType, util::position::{Positioned, Position}};
+
+    use super::{type_equivalent, is_assignable};
+
+    fn positioned(data_type: DataType) -> Positioned<DataType> {
+        Positioned::new(data_type, Position::default(), Position::default())
+    }
+
+    fn custom(name: &str) -> DataType {
+        DataType::Custom(name.to_string())
+    }
+
+    fn function(params: Vec<DataType>, return_type: Option<DataType>) -> DataType {
+        DataType::Function {
+            return_type: return_type.map(|x| Box::new(positioned(x))),
+            params: params.into_iter().map(positioned).collect()
+        }
+    }
+
+    #[test]
+    fn custom_types() {
+        assert_eq!(custom("I32"), custom("I32"));
+        assert_ne!(custom("I32"), custom("String"));
+        assert_ne!(custom("I32"), custom("c_int"));
+    }
+
+    #[test]
+    fn function_parameters_are_ordered() {
+        let lhs = function(vec![custom("I32"), custom("String")], None);
+        let rhs = function(vec![custom("String"), custom("I32")], None);
+        assert_ne!(lhs, rhs);
+        assert!(!is_assignable(&lhs, &rhs));
+        assert_eq!(lhs, lhs.clone());
+    }
+
+    #[test]
+    fn function_arity() {
+        let one = function(vec![custom("I32")], None);
+        let two = function(vec![custom("I32"), custom("I32")], None);
+        assert_ne!(one, two);
+        assert_ne!(two, one);
+        assert!(!is_assignable(&one, &two));
+        assert!(!is_assignable(&two, &one));
+        assert_eq!(function(vec![], None), function(vec![], None));
+    }
+
+    #[test]
+    fn function_return_types() {
+        let int = function(vec![custom("I32")], Some(custom("I32")));
+        let bool = function(vec![custom("I32")], Some(custom("Bool")));
+        let void = function(vec![custom("I32")], None);
+        assert_ne!(int, bool);
+        assert_ne!(int, void);
+        assert_ne!(void, int);
+        assert!(!is_assignable(&int, &void));
+        assert!(is_assignable(&int, &int.clone()));
+    }
+
+    #[test]
+    fn function_and_custom() {
+        let fun = function(vec![], None);
+        assert_ne!(fun, custom("I32"));
+        assert!(!is_assignable(&custom("I32"), &fun));
+        assert!(!is_assignable(&fun, &custom("I32")));
+    }
+
+    #[test]
+    fn nested_function_types() {
+        let callback = function(vec![custom("I32")], Some(custom("I32")));
+        let other_callback = function(vec![custom("Bool")], Some(custom("I32")));
+        let lhs = function(vec![callback.clone()], Some(callback.clone()));
+        let rhs = function(vec![other_callback.clone()], Some(callback.clone()));
+        assert_eq!(lhs, lhs.clone());
+        assert_ne!(lhs, rhs);
+        assert!(!is_assignable(&lhs, &rhs));
+    }
+
+    #[test]
+    fn c_aliases() {
+        assert!(type_equivalent(&custom("c_int"), &custom("I32")));
+        assert!(type_equivalent(&custom("String"), &custom("c_string")));
+        assert!(type_equivalent(&custom("F32"), &custom("c_float")));
+        assert!(!type_equivalent(&custom("F32"), &custom("c_int")));
+        assert!(is_assignable(&custom("I32"), &custom("c_int")));
+
+        let lhs = function(vec![custom("c_int")], Some(custom("c_float")));
+        let rhs = function(vec![custom("I32")], Some(custom("F32")));
+        assert_ne!(lhs, rhs);
+        assert!(is_assignable(&lhs, &rhs));
+    }
+
+    fn pointer(inner: DataType) -> DataType {
+        DataType::Pointer(Box::new(positioned(inner)))
+    }
+
+    fn constant(inner: DataType) -> DataType {
+        DataType::Const(Box::new(positioned(inner)))
+    }
+
+    #[test]
+    fn pointers() {
+        let char_ptr = pointer(custom("c_char"));
+        let const_char_ptr = pointer(constant(custom("c_char")));
+        assert_eq!(char_ptr, pointer(custom("c_char")));
+        assert_ne!(char_ptr, const_char_ptr);
+        assert_ne!(char_ptr, custom("c_char"));
+
+        // const can be added, not removed
+        assert!(is_assignable(&const_char_ptr, &char_ptr));
+        assert!(!is_assignable(&char_ptr, &const_char_ptr));
+
+        // Strings are `*const c_char`
+        assert!(is_assignable(&const_char_ptr, &custom("c_string")));
+        assert!(is_assignable(&const_char_ptr, &custom("String")));
+        assert!(is_assignable(&custom("c_string"), &const_char_ptr));
+        assert!(!is_assignable(&char_ptr, &custom("String")));
+
+        // Any pointer converts to *void
+        assert!(is_assignable(&pointer(custom("void")), &char_ptr));
+        assert!(is_assignable(&pointer(constant(custom("void"))), &custom("c_string")));
+        assert!(!is_assignable(&pointer(custom("void")), &custom("c_int")));
+        assert!(!is_assignable(&char_ptr, &pointer(custom("void"))));
+
+        // Pointers to `const` only convert to *const void
+        assert!(is_assignable(&pointer(constant(custom("void"))), &const_char_ptr));
+        assert!(!is_assignable(&pointer(custom("void")), &const_char_ptr));
+        assert!(!is_assignable(&pointer(custom("void")), &custom("c_string")));
+        assert!(!is_assignable(&pointer(custom("void")), &pointer(constant(custom("void")))));
+    }
+
+    #[test]
+    fn constants() {
+        assert!(is_assignable(&constant(custom("c_int")), &custom("I32")));
+        assert!(is_assignable(&custom("c_int"), &constant(custom("I32"))));
+        assert!(!is_assignable(&custom("c_int"), &constant(custom("Bool"))));
+        assert_ne!(constant(custom("c_int")), custom("c_int"));
+    }
+
+    #[test]
+    fn equality_ignores_positions() {
+        let far = Position { index: 10, line: 2, column: 4, ..Position::default() };
+        let lhs = pointer(custom("c_char"));
+        let rhs = DataType::Pointer(Box::new(Positioned::new(custom("c_char"), far.clone(), far)));
+        assert_eq!(lhs, rhs);
+    }
+
+    #[test]
+    fn display() {
+        let fun = function(vec![custom("I32"), custom("String")], Some(custom("Bool")));
+        assert_eq!(fun.to_string(), "fn(I32, String): Bool");
+        assert_eq!(function(vec![], None).to_string(), "fn()");
+        assert_eq!(pointer(constant(custom("c_char"))).to_string(), "*const c_char");
+    }
+
+}
@@ -0,0 +1,53 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Evaluator Error                                        //
+//////////////////////////////////////////////////////////////////////////////////////////////////// 
+
+use crate::util::{position::Positioned, source_file::SourceFile, error::{ErrorFormat, ErrorType}};
+
+pub enum EvaluatorError {
+    IntegerOverflow(Positioned<()>),
+    DivisionByZero(Positioned<()>),
+    NonConstantInitializer(Positioned<()>)
+}
+
+impl EvaluatorError {
+
+    /// Name of the variant, used by the diagnostic tests.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EvaluatorError::IntegerOverflow(..) => "IntegerOverflow",
+            EvaluatorError::DivisionByZero(..) => "DivisionByZero",
+            EvaluatorError::NonConstantInitializer(..) => "NonConstantInitializer",
+        }
+    }
+
+    /// Span of the main message of the error.
+    pub fn span(&self) -> Option<Positioned<()>> {
+        match self {
+            EvaluatorError::IntegerOverflow(expr) => Some(expr.clone()),
+            EvaluatorError::DivisionByZero(expr) => Some(expr.clone()),
+            EvaluatorError::NonConstantInitializer(expr) => Some(expr.clone()),
+        }
+    }
+
+    pub fn print_error(&self, src: &SourceFile) {
+        match self {
+            EvaluatorError::IntegerOverflow(node) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message("Integer overflow in constant expression!".to_string(), Some(node.clone()))
+                    .set_step("Const Evaluator".to_string()).print(src);
+            },
+            EvaluatorError::DivisionByZero(node) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message("Division by zero in constant expression!".to_string(), Some(node.clone()))
+                    .set_step("Const Evaluator".to_string()).print(src);
+            },
+            EvaluatorError::NonConstantInitializer(node) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message("Globals can only be initialized with constant expressions!".to_string(), Some(node.clone()))
+                    .set_step("Const Evaluator".to_string()).print(src);
+            },
+        }
+    }
+
+}
\ No newline at end of file
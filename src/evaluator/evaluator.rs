@@ -0,0 +1,429 @@
+use crate::{ir::output::IROutput, util::position::Positioned, parser::node::{Node, ValueNode, Operator, VarType, DataType}, evaluator::error::EvaluatorError};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Constant                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// C integer types a constant can have, `long` being 64 bits wide (LP64).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegerType {
+    Int,
+    UInt,
+    Long,
+    ULong,
+    SizeT
+}
+
+impl IntegerType {
+
+    /// Integer literals are typed `I32` by the checker.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "c_int" | "I32" => Some(IntegerType::Int),
+            "c_uint" => Some(IntegerType::UInt),
+            "c_long" => Some(IntegerType::Long),
+            "c_ulong" => Some(IntegerType::ULong),
+            "c_size_t" => Some(IntegerType::SizeT),
+            _ => None
+        }
+    }
+
+    fn bits(self) -> u32 {
+        match self {
+            IntegerType::Int | IntegerType::UInt => 32,
+            IntegerType::Long | IntegerType::ULong | IntegerType::SizeT => 64
+        }
+    }
+
+    fn min(self) -> i128 {
+        match self {
+            IntegerType::Int => i32::MIN as i128,
+            IntegerType::Long => i64::MIN as i128,
+            IntegerType::UInt | IntegerType::ULong | IntegerType::SizeT => 0
+        }
+    }
+
+    fn max(self) -> i128 {
+        match self {
+            IntegerType::Int => i32::MAX as i128,
+            IntegerType::UInt => u32::MAX as i128,
+            IntegerType::Long => i64::MAX as i128,
+            IntegerType::ULong | IntegerType::SizeT => u64::MAX as i128
+        }
+    }
+
+    /// Type of an operation, the widest of both operands like the C conversions.
+    fn common(lhs: Option<Self>, rhs: Option<Self>) -> Option<Self> {
+        match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => Some(if rhs.max() > lhs.max() { rhs } else { lhs }),
+            (lhs, rhs) => lhs.or(rhs)
+        }
+    }
+
+}
+
+/// Integers keep their type, none for untyped literals which fit any type holding their value and
+/// are otherwise checked as `long`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Constant {
+    Integer(i128, Option<IntegerType>),
+    Bool(bool)
+}
+
+impl Constant {
+
+    fn to_node(self) -> Node {
+        match self {
+            // The suffix keeps the C type, variadic calls read the argument by its size
+            Constant::Integer(value, integer_type) => Node::Value(ValueNode::Integer(format!("{}{}", value, match integer_type {
+                Some(IntegerType::UInt) => "u",
+                Some(IntegerType::Long) => "l",
+                Some(IntegerType::ULong | IntegerType::SizeT) => "ul",
+                Some(IntegerType::Int) | None => ""
+            }))),
+            Constant::Bool(value) => Node::Value(ValueNode::Bool(value)),
+        }
+    }
+
+    /// Integer of the type, if the value fits.
+    fn integer(value: i128, integer_type: Option<IntegerType>) -> Option<Constant> {
+        let range = integer_type.unwrap_or(IntegerType::Long);
+        (range.min()..=range.max()).contains(&value).then_some(Constant::Integer(value, integer_type))
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Const Evaluator                                        //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Folds constant expressions and substitutes `const` bindings by their value.
+pub struct ConstEvaluator {
+    ir_output: IROutput,
+    /// Innermost last, `None` for bindings that are not constant (they hide outer constants)
+    scopes: Vec<Vec<(String, Option<Constant>)>>
+}
+
+impl ConstEvaluator {
+
+    pub fn new(ir_output: IROutput) -> Self {
+        Self {
+            ir_output,
+            scopes: vec![Vec::new()]
+        }
+    }
+
+    fn bind(&mut self, name: String, value: Option<Constant>) {
+        self.scopes.last_mut().unwrap().push((name, value));
+    }
+
+    fn lookup(&self, name: &str) -> Option<Constant> {
+        for scope in self.scopes.iter().rev() {
+            if let Some((_, value)) = scope.iter().rev().find(|(bound, _)| bound == name) {
+                return *value;
+            }
+        }
+        None
+    }
+
+    fn fold_unary(pos: &Positioned<()>, operator: &Operator, value: Constant) -> Result<Option<Constant>, EvaluatorError> {
+        let overflow = || EvaluatorError::IntegerOverflow(pos.clone());
+
+        Ok(match (operator, value) {
+            (Operator::Add, Constant::Integer(..)) => Some(value),
+            (Operator::Subtract, Constant::Integer(value, integer_type)) => Some(Constant::integer(-value, integer_type).ok_or_else(overflow)?),
+            // Complement within the bits of the type
+            (Operator::BitwiseNot, Constant::Integer(value, integer_type)) => match integer_type {
+                Some(integer_type) if integer_type.min() == 0 => Some(Constant::Integer(integer_type.max() - value, Some(integer_type))),
+                _ => Some(Constant::Integer(!value, integer_type))
+            },
+            (Operator::BooleanNot, Constant::Bool(value)) => Some(Constant::Bool(!value)),
+            _ => None
+        })
+    }
+
+    fn fold_binary(pos: &Positioned<()>, operator: &Operator, lhs: Constant, rhs: Constant) -> Result<Option<Constant>, EvaluatorError> {
+        let overflow = || EvaluatorError::IntegerOverflow(pos.clone());
+
+        Ok(match (lhs, rhs) {
+            (Constant::Integer(lhs, lhs_type), Constant::Integer(rhs, rhs_type)) => {
+                let integer_type = IntegerType::common(lhs_type, rhs_type);
+                let bits = integer_type.unwrap_or(IntegerType::Long).bits();
+                let value = match operator {
+                    Operator::Add => lhs.checked_add(rhs),
+                    Operator::Subtract => lhs.checked_sub(rhs),
+                    Operator::Multiply => lhs.checked_mul(rhs),
+                    Operator::Divide | Operator::Modulo if rhs == 0 => return Err(EvaluatorError::DivisionByZero(pos.clone())),
+                    Operator::Divide => lhs.checked_div(rhs),
+                    Operator::Modulo => lhs.checked_rem(rhs),
+                    Operator::BitwiseAnd => Some(lhs & rhs),
+                    Operator::BitwiseOr => Some(lhs | rhs),
+                    Operator::BitwiseXor => Some(lhs ^ rhs),
+                    Operator::ShiftLeft | Operator::ShiftRight if !(0..bits as i128).contains(&rhs) => return Err(overflow()),
+                    Operator::ShiftLeft => lhs.checked_shl(rhs as u32),
+                    Operator::ShiftRight => lhs.checked_shr(rhs as u32),
+                    Operator::Equal => return Ok(Some(Constant::Bool(lhs == rhs))),
+                    Operator::NotEqual => return Ok(Some(Constant::Bool(lhs != rhs))),
+                    Operator::Greater => return Ok(Some(Constant::Bool(lhs > rhs))),
+                    Operator::GreaterOrEqual => return Ok(Some(Constant::Bool(lhs >= rhs))),
+                    Operator::Less => return Ok(Some(Constant::Bool(lhs < rhs))),
+                    Operator::LessOrEqual => return Ok(Some(Constant::Bool(lhs <= rhs))),
+                    _ => return Ok(None)
+                };
+                Some(value.and_then(|value| Constant::integer(value, integer_type)).ok_or_else(overflow)?)
+            },
+            (Constant::Bool(lhs), Constant::Bool(rhs)) => match operator {
+                Operator::BooleanAnd => Some(Constant::Bool(lhs && rhs)),
+                Operator::BooleanOr => Some(Constant::Bool(lhs || rhs)),
+                Operator::BooleanXor | Operator::NotEqual => Some(Constant::Bool(lhs != rhs)),
+                Operator::Equal => Some(Constant::Bool(lhs == rhs)),
+                _ => None
+            },
+            _ => None
+        })
+    }
+
+    fn is_assignment(operator: &Operator) -> bool {
+        matches!(operator,
+            Operator::Assign | Operator::AddAssign | Operator::SubtractAssign | Operator::MultiplyAssign | Operator::DivideAssign |
+            Operator::ModuloAssign | Operator::BitwiseAndAssign | Operator::BitwiseOrAssign | Operator::BitwiseXorAssign |
+            Operator::ShiftLeftAssign | Operator::ShiftRightAssign
+        )
+    }
+
+    /// Folds the expression in place and returns its value if it is constant.
+    fn fold_expr(&mut self, node: &mut Positioned<Node>) -> Result<Option<Constant>, EvaluatorError> {
+        let pos = node.convert(());
+        let value = match &mut node.data {
+            Node::Value(ValueNode::Integer(value)) => {
+                let value = value.trim_end_matches(['u', 'l']).parse().ok().and_then(|value| Constant::integer(value, None));
+                Some(value.ok_or_else(|| EvaluatorError::IntegerOverflow(pos.clone()))?)
+            },
+            Node::Value(ValueNode::Bool(value)) => Some(Constant::Bool(*value)),
+            Node::VariableCall { name, .. } => self.lookup(name),
+            Node::UnaryOperation { operator, value } => {
+                let operator = operator.data.clone();
+                match self.fold_expr(value)? {
+                    Some(value) => Self::fold_unary(&pos, &operator, value)?,
+                    None => None
+                }
+            },
+            // Members are not bindings
+            Node::BinaryOperation { lhs, operator, rhs } if operator.data == Operator::Access || operator.data == Operator::DotAccess => {
+                self.fold_expr(lhs)?;
+                if let Node::FunctionCall { parameters, .. } = &mut rhs.data {
+                    for param in parameters.iter_mut() {
+                        self.fold_expr(param)?;
+                    }
+                }
+                None
+            },
+            Node::BinaryOperation { operator, rhs, .. } if Self::is_assignment(&operator.data) => {
+                self.fold_expr(rhs)?;
+                None
+            },
+            Node::BinaryOperation { lhs, operator, rhs } => {
+                let operator = operator.data.clone();
+                match (self.fold_expr(lhs)?, self.fold_expr(rhs)?) {
+                    (Some(lhs), Some(rhs)) => Self::fold_binary(&pos, &operator, lhs, rhs)?,
+                    _ => None
+                }
+            },
+            Node::FunctionCall { parameters, .. } => {
+                for param in parameters.iter_mut() {
+                    self.fold_expr(param)?;
+                }
+                None
+            },
+            Node::_ClosureCall { closure, parameters, .. } => {
+                self.fold_expr(closure)?;
+                for param in parameters.iter_mut() {
+                    self.fold_expr(param)?;
+                }
+                None
+            },
+            Node::_Lambda { function, captures } => {
+                for capture in captures.iter_mut() {
+                    self.fold_expr(&mut capture.value)?;
+                }
+                self.evaluate_node(function)?;
+                None
+            },
+            Node::_Unchecked(inner) |
+            Node::_Optional(inner) |
+            Node::_Generated(inner) => self.fold_expr(inner)?,
+            Node::_Renamed { node: inner, .. } => self.fold_expr(inner)?,
+            _ => None
+        };
+
+        // Substitute the folded value
+        if let Some(value) = value {
+            if !matches!(node.data, Node::Value(_)) {
+                node.data = value.to_node();
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn evaluate_variable_definition(&mut self, node: &mut Positioned<Node>, global: bool) -> Result<(), EvaluatorError> {
+        let Node::VariableDefinition { var_type, name, data_type, value, external, .. } = &mut node.data else {
+            unreachable!()
+        };
+
+        let folded = match value {
+            Some(value) => {
+                let folded = self.fold_expr(value)?;
+                // C only accepts constant initializers for globals
+                if global && external.is_none() && folded.is_none() && Self::references_binding(value) {
+                    return Err(EvaluatorError::NonConstantInitializer(value.convert(())));
+                }
+                // The value takes the type of the variable
+                let integer_type = match data_type.as_ref().map(|data_type| &data_type.data) {
+                    Some(DataType::Custom(inner)) => IntegerType::from_name(inner),
+                    _ => None
+                };
+                match (folded, integer_type) {
+                    (Some(Constant::Integer(folded, _)), Some(integer_type)) => {
+                        Some(Constant::integer(folded, Some(integer_type)).ok_or_else(|| EvaluatorError::IntegerOverflow(value.convert(())))?)
+                    },
+                    _ => folded
+                }
+            },
+            None => None
+        };
+
+        let constant = if var_type.data == VarType::Constant { folded } else { None };
+        self.bind(name.data.clone(), constant);
+        Ok(())
+    }
+
+    fn references_binding(node: &Positioned<Node>) -> bool {
+        match &node.data {
+            Node::VariableCall { .. } => true,
+            Node::UnaryOperation { value, .. } => Self::references_binding(value),
+            Node::BinaryOperation { lhs, rhs, .. } => Self::references_binding(lhs) || Self::references_binding(rhs),
+            _ => false
+        }
+    }
+
+    fn evaluate_body(&mut self, body: &mut [Positioned<Node>]) -> Result<(), EvaluatorError> {
+        self.scopes.push(Vec::new());
+        for node in body.iter_mut() {
+            self.evaluate_node(node)?;
+        }
+        self.scopes.pop();
+        Ok(())
+    }
+
+    fn evaluate_node(&mut self, node: &mut Positioned<Node>) -> Result<(), EvaluatorError> {
+        match &mut node.data {
+            Node::FunctionDefinition { parameters, body, .. } => {
+                // Parameters hide the constants of the same name
+                self.scopes.push(parameters.iter().map(|param| (param.name.data.clone(), None)).collect());
+                self.evaluate_body(body)?;
+                self.scopes.pop();
+            },
+            Node::VariableDefinition { .. } => self.evaluate_variable_definition(node, false)?,
+            Node::Return(Some(value)) => {
+                self.fold_expr(value)?;
+            },
+            Node::IfStatement { condition, body, elif_branches, else_body, .. } => {
+                self.fold_expr(condition)?;
+                self.evaluate_body(body)?;
+                for branch in elif_branches.iter_mut() {
+                    self.fold_expr(&mut branch.condition)?;
+                    self.evaluate_body(&mut branch.body)?;
+                }
+                self.evaluate_body(else_body)?;
+            },
+            Node::WhileLoop { condition, body, .. } => {
+                self.fold_expr(condition)?;
+                self.evaluate_body(body)?;
+            },
+            Node::Label { inner, .. } => self.evaluate_node(inner)?,
+            Node::ClassDefinition { body, .. } |
+            Node::InterfaceDefinition { body, .. } => self.evaluate_body(body)?,
+            Node::SpaceDefinition { body, .. } => self.evaluate_globals(body, false)?,
+            Node::_Implementation(inner) |
+            Node::_Unchecked(inner) |
+            Node::_Optional(inner) => self.evaluate_node(inner)?,
+            Node::_Renamed { node: inner, .. } => self.evaluate_node(inner)?,
+            _ => {
+                self.fold_expr(node)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Globals are bound first so every function sees them.
+    fn evaluate_globals(&mut self, nodes: &mut [Positioned<Node>], bind: bool) -> Result<(), EvaluatorError> {
+        for node in nodes.iter_mut() {
+            match &mut node.data {
+                Node::VariableDefinition { .. } if bind => self.evaluate_variable_definition(node, true)?,
+                Node::VariableDefinition { .. } => {},
+                Node::SpaceDefinition { body, .. } if bind => self.evaluate_globals(body, true)?,
+                _ if bind => {},
+                _ => self.evaluate_node(node)?
+            }
+        }
+        Ok(())
+    }
+
+    pub fn evaluate(&mut self) -> Result<IROutput, EvaluatorError> {
+        let mut ast = std::mem::take(&mut self.ir_output.ast);
+        self.evaluate_globals(&mut ast, true)?;
+        self.evaluate_globals(&mut ast, false)?;
+
+        Ok(IROutput {
+            includes: self.ir_output.includes.clone(),
+            ast
+        })
+    }
+
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Tests                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::util::position::{Positioned, Position};
+
+    use super::*;
+
+    fn pos() -> Positioned<()> {
+        Positioned::new((), Position::default(), Position::default())
+    }
+
+    fn fold(operator: Operator, lhs: Constant, rhs: Constant) -> Option<Constant> {
+        ConstEvaluator::fold_binary(&pos(), &operator, lhs, rhs).unwrap_or_else(|err| panic!("Failed to fold, {}", err.kind()))
+    }
+
+    fn overflows(operator: Operator, lhs: Constant, rhs: Constant) -> bool {
+        matches!(ConstEvaluator::fold_binary(&pos(), &operator, lhs, rhs), Err(EvaluatorError::IntegerOverflow(_)))
+    }
+
+    #[test]
+    fn integers_are_checked_against_their_type() {
+        let int = |value| Constant::Integer(value, Some(IntegerType::Int));
+        let long = |value| Constant::Integer(value, Some(IntegerType::Long));
+        assert!(overflows(Operator::Multiply, int(65536), int(65536)));
+        assert_eq!(fold(Operator::Multiply, long(65536), int(65536)), Some(long(1 << 32)));
+        assert_eq!(fold(Operator::Add, Constant::Integer(3_000_000_000, None), Constant::Integer(1, None)), Some(Constant::Integer(3_000_000_001, None)));
+        assert!(overflows(Operator::Subtract, Constant::Integer(0, Some(IntegerType::UInt)), Constant::Integer(1, None)));
+        assert!(overflows(Operator::ShiftLeft, int(1), int(32)));
+    }
+
+    #[test]
+    fn literals_take_the_type_of_the_variable() {
+        assert_eq!(Constant::integer(3_000_000_000, Some(IntegerType::Long)), Some(Constant::Integer(3_000_000_000, Some(IntegerType::Long))));
+        assert_eq!(Constant::integer(3_000_000_000, Some(IntegerType::Int)), None);
+        assert_eq!(Constant::integer(-1, Some(IntegerType::SizeT)), None);
+        let complement = ConstEvaluator::fold_unary(&pos(), &Operator::BitwiseNot, Constant::Integer(0, Some(IntegerType::UInt))).unwrap_or(None);
+        assert_eq!(complement, Some(Constant::Integer(u32::MAX as i128, Some(IntegerType::UInt))));
+    }
+
+}
@@ -0,0 +1,2 @@
+pub mod error;
+pub mod evaluator;
\ No newline at end of file
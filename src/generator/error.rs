@@ -0,0 +1,54 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Generator Error                                        //
+//////////////////////////////////////////////////////////////////////////////////////////////////// 
+
+use crate::util::{position::Positioned, source_file::SourceFile, error::{ErrorFormat, ErrorType}};
+
+pub enum GeneratorError {
+    /// Structures embedding each other by value, in order
+    ValueTypeCycle(Vec<Positioned<String>>),
+    /// Library symbol (its C name) named like another definition
+    NameConflict(Box<Positioned<String>>, Box<Positioned<()>>)
+}
+
+impl GeneratorError {
+
+    /// Name of the variant, used by the diagnostic tests.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GeneratorError::ValueTypeCycle(..) => "ValueTypeCycle",
+            GeneratorError::NameConflict(..) => "NameConflict",
+        }
+    }
+
+    /// Span of the main message of the error.
+    pub fn span(&self) -> Option<Positioned<()>> {
+        match self {
+            GeneratorError::ValueTypeCycle(cycle) => cycle.first().map(|name| name.convert(())),
+            GeneratorError::NameConflict(name, _) => Some(name.convert(())),
+        }
+    }
+
+    pub fn print_error(&self, src: &SourceFile) {
+        match self {
+            GeneratorError::ValueTypeCycle(cycle) => {
+                let mut error_msg = ErrorFormat::new(ErrorType::Error)
+                    .add_message(format!("'{}' embeds itself by value, it cannot be laid out:", cycle[0].data), Some(cycle[0].convert(())))
+                    .set_step("Generator".to_string());
+
+                for structure in cycle.iter().skip(1) {
+                    error_msg = error_msg.add_message(format!("Through '{}':", structure.data), Some(structure.convert(())));
+                }
+
+                error_msg.print(src);
+            },
+            GeneratorError::NameConflict(name, other) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message(format!("Symbol is named '{}' in C, like another definition:", name.data), Some(name.convert(())))
+                    .add_message("Defined here:".to_string(), Some(other.as_ref().clone()))
+                    .set_step("Generator".to_string()).print(src);
+            },
+        }
+    }
+
+}
\ No newline at end of file
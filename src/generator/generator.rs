@@ -1,4 +1,4 @@
-use crate::{ir::output::{IROutput, IncludeType}, generator::project::{Project, File}, util::position::Positioned, parser::node::{Node, ValueNode, Operator, DataType}};
+use crate::{ir::output::{IROutput, IncludeType}, generator::{project::{Project, File, Structure}, library::{Library, SymbolPrefixer}, source_map::SourceMap, error::GeneratorError}, util::{position::Positioned, mangle}, parser::node::{Node, ValueNode, Operator, DataType, AccessModifier, VarType}};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                           Generator                                         //
@@ -6,18 +6,195 @@ use crate::{ir::output::{IROutput, IncludeType}, generator::project::{Project, F
 
 pub struct Generator {
     ir_output: IROutput,
-    index: usize
+    index: usize,
+    library: Option<Library>,
+    public: bool,
+    /// Taly source the `#line` directives point to, none are emitted without it
+    source: Option<String>,
+    spans: Vec<Positioned<()>>
 }
 
 impl Generator {
 
-    pub fn new(ir_output: IROutput) -> Self {
+    pub fn new(ir_output: IROutput, library: Option<Library>, source: Option<String>) -> Self {
         Self {
             ir_output,
-            index: 0
+            index: 0,
+            library,
+            public: true,
+            source,
+            spans: Vec::new()
         }
     }
 
+    fn file_name(&self) -> String {
+        self.library.as_ref().map_or("main".to_string(), |library| library.name.clone())
+    }
+
+    /// File of an imported module, `util/strings.taly` is `util_strings`.
+    fn module_file_name(module: &str) -> String {
+        let path = module.strip_suffix(".taly").unwrap_or(module);
+        path.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect()
+    }
+
+    /// Programs get one file per top-level class, space and imported module, a library stays a
+    /// single translation unit so its private symbols can be `static`.
+    fn file_for(&self, node: &Positioned<Node>) -> String {
+        match &node.data {
+            _ if self.library.is_some() => self.file_name(),
+            // Closures and lambda environments stay in the module
+            Node::ClassDefinition { name, .. } if !mangle::is_generated(&name.data) => name.data.clone(),
+            Node::SpaceDefinition { name, .. } => name.data.clone(),
+            Node::FunctionDefinition { module: Some(module), .. } |
+            Node::VariableDefinition { module: Some(module), .. } |
+            Node::InterfaceDefinition { module: Some(module), .. } => Self::module_file_name(module),
+            _ => self.file_name()
+        }
+    }
+
+    /// Structures and symbols defined by the node, used to link the files together.
+    fn collect_symbols(node: &Positioned<Node>, file: &mut File) {
+        match &node.data {
+            Node::FunctionDefinition { name, external: None, .. } |
+            Node::VariableDefinition { name, external: None, .. } => file.symbols.push(name.data.clone()),
+            Node::_Implementation(inner) => {
+                if let Node::FunctionDefinition { name, .. } = &inner.data {
+                    file.symbols.push(name.data.clone());
+                    file.symbols.push(mangle::implementation(&name.data));
+                }
+            },
+            Node::ClassDefinition { name, body, external: None, .. } => {
+                file.types.push(name.data.clone());
+                for child in body.iter() {
+                    // Fields are not symbols
+                    if !matches!(child.data, Node::VariableDefinition { .. }) {
+                        Self::collect_symbols(child, file);
+                    }
+                }
+            },
+            Node::SpaceDefinition { body, .. } => {
+                for child in body.iter() {
+                    Self::collect_symbols(child, file);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn collect_type(data_type: &DataType, types: &mut Vec<String>) {
+        match data_type {
+            DataType::Custom(name) => types.push(name.strip_prefix("_NOPTR_").unwrap_or(name).to_string()),
+            DataType::Function { return_type, params } => {
+                if let Some(return_type) = return_type {
+                    Self::collect_type(&return_type.data, types);
+                }
+                for param in params.iter() {
+                    Self::collect_type(&param.data, types);
+                }
+            },
+            DataType::Pointer(inner) |
+            DataType::Const(inner) => Self::collect_type(&inner.data, types),
+        }
+    }
+
+    /// Structures and symbols used by the node, the structures of its declarations (signatures,
+    /// fields and globals) being also used by the header.
+    fn collect_references(node: &Positioned<Node>, declaration: bool, file: &mut File) {
+        match &node.data {
+            Node::Value(ValueNode::Type(name)) => file.references.push(name.strip_prefix("_NOPTR_").unwrap_or(name).to_string()),
+            Node::FunctionDefinition { parameters, return_type, body, .. } => {
+                let mut types = Vec::new();
+                for param in parameters.iter() {
+                    Self::collect_type(&param.data_type.data, &mut types);
+                }
+                if let Some(return_type) = return_type {
+                    Self::collect_type(&return_type.data, &mut types);
+                }
+                file.references.extend(types.iter().cloned());
+                file.header_types.extend(types);
+                for child in body.iter() {
+                    Self::collect_references(child, false, file);
+                }
+            },
+            Node::VariableDefinition { data_type, value, .. } => {
+                let mut types = Vec::new();
+                if let Some(data_type) = data_type {
+                    Self::collect_type(&data_type.data, &mut types);
+                }
+                file.references.extend(types.iter().cloned());
+                if declaration {
+                    file.header_types.extend(types);
+                }
+                if let Some(value) = value {
+                    Self::collect_references(value, false, file);
+                }
+            },
+            Node::FunctionCall { name, parameters, .. } => {
+                file.references.push(name.data.clone());
+                for param in parameters.iter() {
+                    Self::collect_references(param, false, file);
+                }
+            },
+            // Functions can be referenced by address
+            Node::VariableCall { name, .. } => file.references.push(name.strip_prefix('&').unwrap_or(name).to_string()),
+            Node::BinaryOperation { lhs, operator, rhs } => {
+                Self::collect_references(lhs, false, file);
+                // Members are not symbols, only the arguments of method calls are used
+                match &rhs.data {
+                    Node::FunctionCall { parameters, .. } if operator.data == Operator::Access || operator.data == Operator::DotAccess => {
+                        for param in parameters.iter() {
+                            Self::collect_references(param, false, file);
+                        }
+                    },
+                    Node::VariableCall { .. } if operator.data == Operator::Access || operator.data == Operator::DotAccess => {},
+                    _ => Self::collect_references(rhs, false, file)
+                }
+            },
+            Node::UnaryOperation { value, .. } => Self::collect_references(value, false, file),
+            Node::Return(Some(value)) => Self::collect_references(value, false, file),
+            Node::ClassDefinition { body, .. } |
+            Node::SpaceDefinition { body, .. } |
+            Node::InterfaceDefinition { body, .. } => {
+                for child in body.iter() {
+                    Self::collect_references(child, true, file);
+                }
+            },
+            Node::IfStatement { condition, body, elif_branches, else_body, .. } => {
+                Self::collect_references(condition, false, file);
+                for branch in elif_branches.iter() {
+                    Self::collect_references(&branch.condition, false, file);
+                    for child in branch.body.iter() {
+                        Self::collect_references(child, false, file);
+                    }
+                }
+                for child in body.iter().chain(else_body.iter()) {
+                    Self::collect_references(child, false, file);
+                }
+            },
+            Node::WhileLoop { condition, body, .. } => {
+                Self::collect_references(condition, false, file);
+                for child in body.iter() {
+                    Self::collect_references(child, false, file);
+                }
+            },
+            Node::Label { inner, .. } => Self::collect_references(inner, false, file),
+            Node::_Implementation(inner) => Self::collect_references(inner, declaration, file),
+            Node::_ClosureCall { closure, data_type, parameters } => {
+                Self::collect_references(closure, false, file);
+                Self::collect_type(&data_type.data, &mut file.references);
+                for param in parameters.iter() {
+                    Self::collect_references(param, false, file);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Whether a definition goes to the public header, only `pub` ones do in a library.
+    fn is_public(&self, access: &Option<Positioned<AccessModifier>>) -> bool {
+        self.library.is_none() || (self.public && access.as_ref().is_some_and(|access| access.data == AccessModifier::Public))
+    }
+
     fn current(&self) -> Option<Positioned<Node>> {
         self.ir_output.ast.get(self.index).cloned()
     }
@@ -31,8 +208,14 @@ impl Generator {
             DataType::Custom(inner) => {
                 match inner.as_str() {
                     "c_string" => "const char*".to_string(),
-                    "c_int" => "int".to_string(),
-                    "c_float" => "float".to_string(),
+                    "c_char" => "char".to_string(),
+                    "c_int" | "I32" => "int".to_string(),
+                    "c_uint" => "unsigned int".to_string(),
+                    "c_long" => "long".to_string(),
+                    "c_ulong" => "unsigned long".to_string(),
+                    "c_size_t" => "size_t".to_string(),
+                    "c_float" | "F32" => "float".to_string(),
+                    "Bool" => "bool".to_string(),
                     "void" => "void".to_string(),
                     _ => {
                         if inner.starts_with("_NOPTR_") {
@@ -65,6 +248,11 @@ impl Generator {
                 buf.push_str(")");
                 buf
             },
+            DataType::Pointer(inner) => format!("{}*", Self::generate_type(inner.data, None)),
+            DataType::Const(inner) => match inner.data {
+                DataType::Pointer(_) => format!("{} const", Self::generate_type(inner.data, None)),
+                _ => format!("const {}", Self::generate_type(inner.data, None))
+            },
         }
         
     }
@@ -84,7 +272,7 @@ impl Generator {
     }
 
     fn generate_function_call(&mut self, node: Positioned<Node>, access: bool) -> (bool, String) {
-        let Node::FunctionCall { name, parameters } = node.data.clone() else {
+        let Node::FunctionCall { name, parameters, .. } = node.data.clone() else {
             unreachable!()
         };
 
@@ -126,6 +314,52 @@ impl Generator {
         (true, buf)
     }
 
+    /// `const` applies to the pointer itself when the type is one, C reads `const Foo*` as a pointer
+    /// to a constant `Foo`.
+    fn generate_const_type(data_type: DataType, name: Option<String>) -> String {
+        let generated = Self::generate_type(data_type.clone(), name);
+        match data_type {
+            // A pointer to a constant function is not a thing, the name already sits in the type
+            DataType::Function { .. } => generated,
+            _ if generated.ends_with('*') => format!("{} const", generated),
+            _ => format!("const {}", generated)
+        }
+    }
+
+    fn generate_global_variable_definition(&mut self, node: Positioned<Node>, file: &mut File) {
+        let Node::VariableDefinition { var_type, name, data_type, value, access, .. } = node.data.clone() else {
+            unreachable!()
+        };
+
+        let data_type = data_type.expect("No type could be inferred!").data;
+        let generated_type = if var_type.data == VarType::Constant {
+            Self::generate_const_type(data_type, Some(name.data.clone()))
+        } else {
+            Self::generate_type(data_type, Some(name.data.clone()))
+        };
+
+        // Globals are shared by the files of a program, only `pub` ones leave a library
+        if self.is_public(&access) {
+            file.header.push_str("extern ");
+            file.header.push_str(&generated_type);
+            file.header.push(' ');
+            file.header.push_str(&name.data);
+            file.header.push_str(";\n\n");
+        } else {
+            file.internal.push_str("static ");
+        }
+
+        // Defined before the functions using them
+        file.internal.push_str(&generated_type);
+        file.internal.push(' ');
+        file.internal.push_str(&name.data);
+        if let Some(value) = value {
+            file.internal.push_str(" = ");
+            file.internal.push_str(&self.generate_current(*value, false).1);
+        }
+        file.internal.push_str(";\n\n");
+    }
+
     fn generate_binary_operation(&mut self, node: Positioned<Node>) -> (bool, String) {
         let Node::BinaryOperation { lhs, operator, rhs } = node.data.clone() else {
             unreachable!()
@@ -155,12 +389,29 @@ impl Generator {
             Operator::DotAccess => buf.push_str("."),
             Operator::BooleanAnd => buf.push_str(" && "),
             Operator::BooleanOr => buf.push_str(" || "),
+            Operator::BooleanXor => buf.push_str(" != "),
             Operator::Equal => buf.push_str(" == "),
             Operator::NotEqual => buf.push_str(" != "),
             Operator::Less => buf.push_str(" < "),
             Operator::LessOrEqual => buf.push_str(" <= "),
             Operator::Greater => buf.push_str(" > "),
             Operator::GreaterOrEqual => buf.push_str(" >= "),
+            Operator::Modulo => buf.push_str(" % "),
+            Operator::BitwiseAnd => buf.push_str(" & "),
+            Operator::BitwiseOr => buf.push_str(" | "),
+            Operator::BitwiseXor => buf.push_str(" ^ "),
+            Operator::ShiftLeft => buf.push_str(" << "),
+            Operator::ShiftRight => buf.push_str(" >> "),
+            Operator::AddAssign => buf.push_str(" += "),
+            Operator::SubtractAssign => buf.push_str(" -= "),
+            Operator::MultiplyAssign => buf.push_str(" *= "),
+            Operator::DivideAssign => buf.push_str(" /= "),
+            Operator::ModuloAssign => buf.push_str(" %= "),
+            Operator::BitwiseAndAssign => buf.push_str(" &= "),
+            Operator::BitwiseOrAssign => buf.push_str(" |= "),
+            Operator::BitwiseXorAssign => buf.push_str(" ^= "),
+            Operator::ShiftLeftAssign => buf.push_str(" <<= "),
+            Operator::ShiftRightAssign => buf.push_str(" >>= "),
             _ => unreachable!()
         }
         buf.push_str(&self.generate_current(*rhs, access).1);
@@ -181,6 +432,7 @@ impl Generator {
             Operator::Add => buf.push_str("+"),
             Operator::Subtract => buf.push_str("-"),
             Operator::BooleanNot => buf.push_str("!"),
+            Operator::BitwiseNot => buf.push('~'),
             _ => unreachable!()
         }
         buf.push_str(&self.generate_current(*value, false).1);
@@ -190,7 +442,7 @@ impl Generator {
     }
 
     fn generate_variable_call(&mut self, node: Positioned<Node>) -> (bool, String) {
-        let Node::VariableCall(name) = node.data.clone() else {
+        let Node::VariableCall { name, .. } = node.data.clone() else {
             unreachable!()
         };
 
@@ -259,7 +511,7 @@ impl Generator {
     }
 
     fn generate_if_statement(&mut self, node: Positioned<Node>) -> (bool, String) {
-        let Node::IfStatement { condition, body, elif_branches, else_body } = node.data.clone() else {
+        let Node::IfStatement { condition, body, elif_branches, else_body, .. } = node.data.clone() else {
             unreachable!()
         };
 
@@ -268,7 +520,7 @@ impl Generator {
         buf.push_str(&self.generate_current(*condition, false).1);
         buf.push_str(") { ");
         for node in body.clone() {
-            let node_str = self.generate_current(node, false);
+            let node_str = self.generate_statement(node);
             for line in node_str.1.lines() {
                 buf.push_str("\n\t");
                 buf.push_str(line);
@@ -287,7 +539,7 @@ impl Generator {
             buf.push_str(&self.generate_current(elif_branch.condition, false).1);
             buf.push_str(") { ");
             for node in elif_branch.body.clone() {
-                let node_str = self.generate_current(node, false);
+                let node_str = self.generate_statement(node);
                 for line in node_str.1.lines() {
                     buf.push_str("\n\t");
                     buf.push_str(line);
@@ -305,7 +557,7 @@ impl Generator {
         if !else_body.is_empty() {
             buf.push_str("else {");
             for node in else_body.clone() {
-                let node_str = self.generate_current(node, false);
+                let node_str = self.generate_statement(node);
                 for line in node_str.1.lines() {
                     buf.push_str("\n\t");
                     buf.push_str(line);
@@ -325,7 +577,7 @@ impl Generator {
     }
 
     fn generate_while_loop(&mut self, node: Positioned<Node>) -> (bool, String) {
-        let Node::WhileLoop { condition, body } = node.data.clone() else {
+        let Node::WhileLoop { condition, body, .. } = node.data.clone() else {
             unreachable!()
         };
 
@@ -334,7 +586,7 @@ impl Generator {
         buf.push_str(&self.generate_current(*condition, false).1);
         buf.push_str(") { ");
         for node in body.clone() {
-            let node_str = self.generate_current(node, false);
+            let node_str = self.generate_statement(node);
             for line in node_str.1.lines() {
                 buf.push_str("\n\t");
                 buf.push_str(line);
@@ -351,12 +603,47 @@ impl Generator {
         (false, buf)
     }
 
+    fn generate_closure_call(&mut self, node: Positioned<Node>) -> (bool, String) {
+        let Node::_ClosureCall { closure, data_type, parameters } = node.data.clone() else {
+            unreachable!()
+        };
+
+        let closure_str = self.generate_current(*closure, false).1;
+
+        let mut buf = String::new();
+        buf.push_str("((");
+        buf.push_str(&Self::generate_type(data_type.data, Some(String::new())));
+        buf.push(')');
+        buf.push_str(&closure_str);
+        buf.push_str(".fn)(");
+        buf.push_str(&closure_str);
+        buf.push_str(".env");
+        for param in parameters {
+            buf.push_str(", ");
+            buf.push_str(&self.generate_current(param, false).1);
+        }
+        buf.push(')');
+
+        (true, buf)
+    }
+
+    fn generate_statement(&mut self, node: Positioned<Node>) -> (bool, String) {
+        let Some(source) = self.source.clone() else {
+            return self.generate_current(node, false);
+        };
+
+        // Recorded before generating the inner statements to keep the order of the directives
+        self.spans.push(node.convert(()));
+        let (semicolon, buf) = self.generate_current(node.clone(), false);
+        (semicolon, format!("{}\n{}", SourceMap::directive(&source, &node.convert(())), buf))
+    }
+
     fn generate_current(&mut self, node: Positioned<Node>, access: bool) -> (bool, String) {
         match node.data {
             Node::Value(_) => self.generate_value(node),
             Node::FunctionCall { .. } => self.generate_function_call(node, access),
             Node::VariableDefinition { .. } => self.generate_variable_definition(node),
-            Node::VariableCall(_) => self.generate_variable_call(node),
+            Node::VariableCall { .. } => self.generate_variable_call(node),
             Node::BinaryOperation { .. } => self.generate_binary_operation(node),
             Node::UnaryOperation { .. } => self.generate_unary_operation(node),
             Node::Return(_) => self.generate_return(node),
@@ -365,20 +652,25 @@ impl Generator {
             Node::Break(_) => self.generate_break(node),
             Node::Continue(_) => self.generate_continue(node),
             Node::Label { .. } => self.generate_label(node),
+            Node::_ClosureCall { .. } => self.generate_closure_call(node),
             _ => unreachable!(),
         }
     }
 
     fn generate_root_function_definition(&mut self, node: Positioned<Node>) -> File {
-        let Node::FunctionDefinition { name, external, parameters, return_type, body, .. } = node.data.clone() else {
+        let Node::FunctionDefinition { name, external, parameters, return_type, body, access, .. } = node.data.clone() else {
             unreachable!()
         };
 
-        if external {
+        if external.is_some() {
             return File::new("_".to_string());
         }
 
+        let public = self.is_public(&access);
         let mut function_header = String::new();
+        if !public {
+            function_header.push_str("static ");
+        }
         function_header.push_str(&Self::generate_type(return_type.map_or(DataType::Custom("void".to_string()), |x| x.data), None));
         function_header.push(' ');
         function_header.push_str(&name.data);
@@ -397,14 +689,20 @@ impl Generator {
 
         let mut file = File::new("_".to_string());
         if name.data != "main" {
-            file.header.push_str(&function_header);
-            file.header.push_str(";\n\n");
+            let declarations = if public { &mut file.header } else { &mut file.internal };
+            declarations.push_str(&function_header);
+            declarations.push_str(";\n\n");
         }
 
+        if let Some(source) = &self.source {
+            self.spans.push(node.convert(()));
+            file.src.push_str(&SourceMap::directive(source, &node.convert(())));
+            file.src.push('\n');
+        }
         file.src.push_str(&function_header);
         file.src.push_str(" { ");
         for node in body.clone() {
-            let node_str = self.generate_current(node, false);
+            let node_str = self.generate_statement(node);
             for line in node_str.1.lines() {
                 file.src.push_str("\n\t");
                 file.src.push_str(line);
@@ -419,15 +717,20 @@ impl Generator {
         file.src.push('}');
         file.src.push('\n');
         file.src.push('\n');
+        file.spans = std::mem::take(&mut self.spans);
 
         file
     }
 
     fn generate_class_definition(&mut self, node: Positioned<Node>, file: &mut File) {
-        let Node::ClassDefinition { name, body, .. } = node.data.clone() else {
+        let Node::ClassDefinition { name, body, access, .. } = node.data.clone() else {
             unreachable!()
         };
 
+        let public = self.is_public(&access);
+        let parent_public = self.public;
+        self.public = public;
+
         // Separate fields and methods
         let mut fields = Vec::new();
         let mut methods = Vec::new();
@@ -443,7 +746,8 @@ impl Generator {
 
         // Create Structure
         let mut struct_buf = String::new();
-        struct_buf.push_str("typedef struct ");
+        let mut embedded = Vec::new();
+        struct_buf.push_str("struct ");
         struct_buf.push_str(&name.data);
         struct_buf.push_str(" { ");
         if !fields.is_empty() {
@@ -456,8 +760,13 @@ impl Generator {
 
             struct_buf.push_str("\t");
             let data_type = data_type.expect("No Type Could be inferred").data;
+            if let DataType::Custom(inner) = &data_type {
+                if let Some(inner) = inner.strip_prefix("_NOPTR_") {
+                    embedded.push(inner.to_string());
+                }
+            }
             match data_type {
-                DataType::Custom(_) => {
+                DataType::Custom(_) | DataType::Pointer(_) | DataType::Const(_) => {
                     struct_buf.push_str(&Self::generate_type(data_type, Some(name.data.clone())));
                     struct_buf.push_str(" ");
                     struct_buf.push_str(&name.data);
@@ -469,88 +778,104 @@ impl Generator {
             struct_buf.push_str(";");
             struct_buf.push_str("\n");
         }
-        struct_buf.push_str("} ");
-        struct_buf.push_str(&name.data);
-        struct_buf.push_str(";\n\n");
+        struct_buf.push_str("};\n\n");
 
         // let file = project.get_file(name.data.clone());
-        file.header.push_str(&struct_buf);
+        file.structures.push(Structure { 
+            name, 
+            body: struct_buf, 
+            embedded, 
+            public 
+        });
 
         for method in methods.iter() {
             let fun_file = self.generate_root_function_definition(method.clone());
-            file.header.push_str(&fun_file.header);
-            file.src.push_str(&fun_file.src);
+            file.append(fun_file);
         }
 
         for method_impl in methods_impl.iter_mut() {
             let base_name;
             if let Node::FunctionDefinition { name, .. } = &mut method_impl.data {
                 base_name = name.clone();
-                *name = name.convert(format!("{}_impl", name.data));
+                *name = name.convert(mangle::implementation(&name.data));
             } else {
                 unreachable!()
             }
             // Generate implementation
             let fun_file = self.generate_root_function_definition(method_impl.clone());
-            file.header.push_str(&fun_file.header);
-            file.src.push_str(&fun_file.src);
+            file.append(fun_file);
 
             // Generate function
-            let Node::FunctionDefinition { constructor, parameters, return_type, .. } = method_impl.data.clone() else {
+            let Node::FunctionDefinition { constructor, parameters, return_type, access, .. } = method_impl.data.clone() else {
                 unreachable!()
             };
 
             let mut call_params = Vec::new();
             for param in parameters.iter() {
-                call_params.push(param.name.convert(Node::VariableCall(param.name.data.clone())))
+                call_params.push(param.name.convert(Node::VariableCall { name: param.name.data.clone(), scope: None }))
+            }
+
+            let mut call = method_impl.convert(Node::FunctionCall { 
+                name: base_name.convert(mangle::implementation(&base_name.data)), 
+                parameters: call_params,
+                scope: None
+            });
+            if return_type.is_some() {
+                call = call.convert(Node::Return(Some(Box::new(call.clone()))));
             }
 
             // TODO: Something to change here (maybe move to Post Generator)
             let fun_file = self.generate_root_function_definition(method_impl.convert(Node::FunctionDefinition { 
                 name: base_name.clone(), 
-                external: false, 
+                external: None, 
                 constructor, 
                 parameters: parameters,
                 return_type: return_type, 
-                body: vec![
-                    method_impl.convert(Node::FunctionCall { 
-                        name: base_name.convert(format!("{}_impl", base_name.data)), 
-                        parameters: call_params
-                    })
-                ], 
-                access: None 
+                body: vec![call], 
+                access,
+                module: None,
+                scope: None 
             }));
-            file.header.push_str(&fun_file.header);
-            file.src.push_str(&fun_file.src);
+            file.append(fun_file);
         }
+
+        self.public = parent_public;
     }
 
     fn generate_space_definition(&mut self, node: Positioned<Node>, file: &mut File) {
-        let Node::SpaceDefinition { body, .. } = node.data.clone() else {
+        let Node::SpaceDefinition { body, access, .. } = node.data.clone() else {
             unreachable!()
         };
 
+        let parent_public = self.public;
+        self.public = self.is_public(&access);
+
         // Separate methods
         let mut methods = Vec::new();
         let mut spaces = Vec::new();
         let mut classes = Vec::new();
         let mut interfaces = Vec::new();
+        let mut globals = Vec::new();
         for node in body.iter() {
             match node.data {
                 Node::FunctionDefinition { .. } => methods.push(node.clone()),
                 Node::SpaceDefinition { .. } => spaces.push(node.clone()),
                 Node::ClassDefinition { .. } => classes.push(node.clone()),
                 Node::InterfaceDefinition { .. } => interfaces.push(node.clone()),
+                Node::VariableDefinition { .. } => globals.push(node.clone()),
                 _ => unreachable!()
             }
         }
 
         // let file = project.get_file(name.data.clone());
 
+        for global in globals.iter() {
+            self.generate_global_variable_definition(global.clone(), file);
+        }
+
         for method in methods.iter() {
             let fun_file = self.generate_root_function_definition(method.clone());
-            file.header.push_str(&fun_file.header);
-            file.src.push_str(&fun_file.src);
+            file.append(fun_file);
         }
 
         for class in classes.iter() {
@@ -564,10 +889,12 @@ impl Generator {
         for space in spaces.iter() {
             self.generate_space_definition(space.clone(), file);
         }
+
+        self.public = parent_public;
     }
 
     fn generate_interface_definition(&mut self, node: Positioned<Node>, file: &mut File) {
-        let Node::InterfaceDefinition { name, body, .. } = node.data.clone() else {
+        let Node::InterfaceDefinition { name, body, access, .. } = node.data.clone() else {
             unreachable!()
         };
 
@@ -587,7 +914,7 @@ impl Generator {
 
         // Create Structure
         let mut struct_buf = String::new();
-        struct_buf.push_str("typedef struct ");
+        struct_buf.push_str("struct ");
         struct_buf.push_str(&name.data);
         struct_buf.push_str(" { ");
         if !methods.is_empty() {
@@ -613,11 +940,14 @@ impl Generator {
             }
             struct_buf.push_str(");\n");
         }
-        struct_buf.push_str("} ");
-        struct_buf.push_str(&name.data);
-        struct_buf.push_str(";\n\n");
+        struct_buf.push_str("};\n\n");
 
-        file.header.push_str(&struct_buf);
+        file.structures.push(Structure { 
+            name, 
+            body: struct_buf, 
+            embedded: Vec::new(), 
+            public: self.is_public(&access) 
+        });
 
         // for method in methods.iter() {
         //     let fun_file = self.generate_root_function_definition(method.clone());
@@ -626,20 +956,34 @@ impl Generator {
         // }
     }
 
-    pub fn generate(&mut self) -> Project {
+    pub fn generate(&mut self) -> Result<Project, GeneratorError> {
         let mut project = Project::new();
 
+        if let Some(library) = &self.library {
+            SymbolPrefixer::new(library.prefix.clone()).prefix(&mut self.ir_output.ast)?;
+        }
+
         while let Some(node) = self.current() {
+            // Declared by the included C headers
+            if let Node::FunctionDefinition { external: Some(_), .. } |
+                   Node::ClassDefinition { external: Some(_), .. } |
+                   Node::VariableDefinition { external: Some(_), .. } = node.data {
+                self.advance();
+                continue;
+            }
+
+            let file = project.get_file(self.file_for(&node));
+            Self::collect_symbols(&node, file);
+            Self::collect_references(&node, true, file);
             match node.data {
                 Node::FunctionDefinition { .. } => {
-                    let file = self.generate_root_function_definition(node);
-                    let main_file = project.get_file("main".to_string());
-                    main_file.header.push_str(&file.header);
-                    main_file.src.push_str(&file.src);
+                    let function_file = self.generate_root_function_definition(node);
+                    file.append(function_file);
                 }
-                Node::ClassDefinition { .. } => self.generate_class_definition(node, project.get_file("main".to_string())),
-                Node::SpaceDefinition { .. } => self.generate_space_definition(node, project.get_file("main".to_string())),
-                Node::InterfaceDefinition { .. } => self.generate_interface_definition(node, project.get_file("main".to_string())),
+                Node::VariableDefinition { .. } => self.generate_global_variable_definition(node, file),
+                Node::ClassDefinition { .. } => self.generate_class_definition(node, file),
+                Node::SpaceDefinition { .. } => self.generate_space_definition(node, file),
+                Node::InterfaceDefinition { .. } => self.generate_interface_definition(node, file),
                 _ => unreachable!()
             }
             self.advance();
@@ -657,14 +1001,21 @@ impl Generator {
             include_buf.push('\n');
         }
 
-        for file in project.files.iter_mut() {
-            file.header = format!("#ifndef TALY_GEN_C_{0}_H\n#define TALY_GEN_C_{0}_H\n\n{1}\n{2}#endif // TALY_GEN_C_{0}_H", file.name, include_buf, file.header);
+        // Structures are declared first, then defined after the ones they embed
+        let order = project.order_structures()?;
+        let links: Vec<(String, String)> = project.files.iter().map(|file| project.link(file)).collect();
+        for (file, (header_links, src_links)) in project.files.iter_mut().zip(links) {
+            let header = format!("{}{}", file.layout_structures(&order, true), file.header);
+            file.header = format!("#ifndef TALY_GEN_C_{0}_H\n#define TALY_GEN_C_{0}_H\n\n{1}\n{2}{3}#endif // TALY_GEN_C_{0}_H", file.name, include_buf, header_links, header);
             if !file.src.is_empty() {
-                file.src = format!("#include \"{}.h\"\n\n{}", file.name, file.src);
+                file.src = format!("#include \"{}.h\"\n{}\n{}{}{}", file.name, src_links, file.layout_structures(&order, false), file.internal, file.src);
+            }
+            if let Some(source) = &self.source {
+                file.source_map = Some(SourceMap::new(format!("{}.c", file.name), source.clone(), &file.src, &file.spans));
             }
         }
 
-        project
+        Ok(project)
     }
 
 }
\ No newline at end of file
@@ -0,0 +1,290 @@
+use crate::{util::{position::Positioned, mangle}, parser::node::{Node, ValueNode, Operator, DataType, AccessModifier}, generator::error::GeneratorError};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Library                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LibraryKind {
+    Static,
+    Shared
+}
+
+#[derive(Clone, Debug)]
+pub struct Library {
+    pub name: String,
+    pub prefix: String,
+    pub kind: LibraryKind
+}
+
+impl Library {
+
+    pub fn new(name: String, prefix: Option<String>, kind: LibraryKind) -> Self {
+        Self {
+            name,
+            prefix: mangle::library_prefix(prefix),
+            kind
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        match self.kind {
+            LibraryKind::Static => format!("lib{}.a", self.name),
+            LibraryKind::Shared => format!("lib{}.so", self.name),
+        }
+    }
+
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Symbol Prefixer                                        //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Where variable definitions are found
+#[derive(Clone, Copy, PartialEq)]
+enum Context {
+    Global,
+    Class,
+    Function
+}
+
+/// Where definitions are collected
+#[derive(Clone, Copy, PartialEq)]
+enum Level {
+    Root,
+    Space,
+    Class
+}
+
+/// Prefixes every symbol defined by the Taly code (functions, classes, interfaces and globals) and
+/// all their references. Externs keep their C names, public root symbols their Taly spelling.
+pub struct SymbolPrefixer {
+    prefix: String,
+    symbols: Vec<String>,
+    /// Escaped names of the public root symbols and their spelling
+    exported: Vec<(String, String)>,
+    /// C names of the collected symbols, before the prefix
+    c_names: Vec<Positioned<String>>,
+    /// Parameters and locals of the current function, hiding globals
+    shadowed: Vec<String>,
+    context: Context
+}
+
+impl SymbolPrefixer {
+
+    pub fn new(prefix: String) -> Self {
+        Self {
+            prefix,
+            symbols: Vec::new(),
+            exported: Vec::new(),
+            c_names: Vec::new(),
+            shadowed: Vec::new(),
+            context: Context::Global
+        }
+    }
+
+    /// Without their escaping, public root symbols can be named like a member in C.
+    fn add_symbol(&mut self, name: &Positioned<String>, exported: bool) -> Result<(), GeneratorError> {
+        self.symbols.push(name.data.clone());
+        let c_name = if exported {
+            let spelling = mangle::exported(&name.data);
+            self.exported.push((name.data.clone(), spelling.clone()));
+            spelling
+        } else {
+            name.data.clone()
+        };
+
+        if let Some(other) = self.c_names.iter().find(|other| other.data == c_name) {
+            return Err(GeneratorError::NameConflict(Box::new(name.convert(mangle::prefixed(&self.prefix, &c_name))), Box::new(other.convert(()))));
+        }
+        self.c_names.push(name.convert(c_name));
+        Ok(())
+    }
+
+    fn collect(&mut self, nodes: &[Positioned<Node>], level: Level) -> Result<(), GeneratorError> {
+        let exported = |access: &Option<Positioned<AccessModifier>>| level == Level::Root && access.as_ref().is_some_and(|access| access.data == AccessModifier::Public);
+        for node in nodes.iter() {
+            match &node.data {
+                Node::FunctionDefinition { name, external: None, access, .. } if name.data != "main" => self.add_symbol(name, exported(access))?,
+                Node::ClassDefinition { name, body, external: None, access, .. } => {
+                    self.add_symbol(name, exported(access))?;
+                    self.collect(body, Level::Class)?;
+                },
+                Node::SpaceDefinition { body, .. } => self.collect(body, Level::Space)?,
+                // Fields are not symbols of the C code
+                Node::VariableDefinition { name, external: None, .. } if level == Level::Class => self.symbols.push(name.data.clone()),
+                Node::VariableDefinition { name, external: None, access, .. } => self.add_symbol(name, exported(access))?,
+                // Interface methods are fields, not symbols
+                Node::InterfaceDefinition { name, access, .. } => self.add_symbol(name, exported(access))?,
+                Node::_Implementation(inner) => {
+                    if let Node::FunctionDefinition { name, .. } = &inner.data {
+                        self.add_symbol(name, false)?;
+                        self.add_symbol(&name.convert(mangle::implementation(&name.data)), false)?;
+                    }
+                },
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn prefix_name(&self, name: &mut String) {
+        if self.symbols.contains(name) && !self.shadowed.contains(name) {
+            let c_name = self.exported.iter().find(|(symbol, _)| symbol == name).map_or(name.as_str(), |(_, spelling)| spelling.as_str());
+            *name = mangle::prefixed(&self.prefix, c_name);
+        }
+    }
+
+    fn prefix_type_name(&self, name: &mut String) {
+        if let Some(inner) = name.strip_prefix("_NOPTR_") {
+            let mut inner = inner.to_string();
+            self.prefix_name(&mut inner);
+            *name = format!("_NOPTR_{}", inner);
+        } else {
+            self.prefix_name(name);
+        }
+    }
+
+    fn prefix_data_type(&self, data_type: &mut DataType) {
+        match data_type {
+            DataType::Custom(custom) => self.prefix_type_name(custom),
+            DataType::Function { return_type, params } => {
+                if let Some(return_type) = return_type {
+                    self.prefix_data_type(&mut return_type.data);
+                }
+                for param in params.iter_mut() {
+                    self.prefix_data_type(&mut param.data);
+                }
+            },
+            DataType::Pointer(inner) |
+            DataType::Const(inner) => self.prefix_data_type(&mut inner.data),
+        }
+    }
+
+    fn prefix_nodes(&mut self, nodes: &mut [Positioned<Node>]) {
+        for node in nodes.iter_mut() {
+            self.prefix_node(node);
+        }
+    }
+
+    /// Members keep their names, only the arguments of method calls are prefixed.
+    fn prefix_member(&mut self, node: &mut Positioned<Node>) {
+        match &mut node.data {
+            Node::FunctionCall { parameters, .. } => self.prefix_nodes(parameters),
+            Node::VariableCall { .. } => {},
+            _ => self.prefix_node(node)
+        }
+    }
+
+    /// Fields keep their names
+    fn prefix_class_body(&mut self, body: &mut [Positioned<Node>]) {
+        let parent_context = self.context;
+        self.context = Context::Class;
+        self.prefix_nodes(body);
+        self.context = parent_context;
+    }
+
+    fn prefix_node(&mut self, node: &mut Positioned<Node>) {
+        match &mut node.data {
+            Node::Value(ValueNode::Type(name)) => self.prefix_type_name(name),
+            Node::FunctionDefinition { name, external, parameters, return_type, body, .. } => {
+                if external.is_none() {
+                    self.prefix_name(&mut name.data);
+                }
+                for param in parameters.iter_mut() {
+                    self.prefix_data_type(&mut param.data_type.data);
+                }
+                if let Some(return_type) = return_type {
+                    self.prefix_data_type(&mut return_type.data);
+                }
+
+                let parent_shadowed = self.shadowed.len();
+                let parent_context = self.context;
+                self.shadowed.extend(parameters.iter().map(|param| param.name.data.clone()));
+                self.context = Context::Function;
+                self.prefix_nodes(body);
+                self.shadowed.truncate(parent_shadowed);
+                self.context = parent_context;
+            },
+            Node::FunctionCall { name, parameters, .. } => {
+                self.prefix_name(&mut name.data);
+                self.prefix_nodes(parameters);
+            },
+            Node::VariableDefinition { name, data_type, value, external, .. } => {
+                match self.context {
+                    Context::Global if external.is_none() => self.prefix_name(&mut name.data),
+                    Context::Function => self.shadowed.push(name.data.clone()),
+                    _ => {}
+                }
+                if let Some(data_type) = data_type {
+                    self.prefix_data_type(&mut data_type.data);
+                }
+                if let Some(value) = value {
+                    self.prefix_node(value);
+                }
+            },
+            // Functions can be referenced by address
+            Node::VariableCall { name, .. } => if let Some(inner) = name.strip_prefix('&') {
+                let mut inner = inner.to_string();
+                self.prefix_name(&mut inner);
+                *name = format!("&{}", inner);
+            } else {
+                self.prefix_name(name);
+            },
+            Node::BinaryOperation { lhs, operator, rhs } => {
+                self.prefix_node(lhs);
+                if operator.data == Operator::Access || operator.data == Operator::DotAccess {
+                    self.prefix_member(rhs);
+                } else {
+                    self.prefix_node(rhs);
+                }
+            },
+            Node::UnaryOperation { value, .. } => self.prefix_node(value),
+            Node::Return(Some(value)) => self.prefix_node(value),
+            Node::ClassDefinition { name, body, external, .. } => {
+                if external.is_none() {
+                    self.prefix_name(&mut name.data);
+                }
+                self.prefix_class_body(body);
+            },
+            Node::SpaceDefinition { body, .. } => self.prefix_nodes(body),
+            Node::InterfaceDefinition { name, body, .. } => {
+                self.prefix_name(&mut name.data);
+                self.prefix_class_body(body);
+            },
+            Node::IfStatement { condition, body, elif_branches, else_body, .. } => {
+                self.prefix_node(condition);
+                self.prefix_nodes(body);
+                for branch in elif_branches.iter_mut() {
+                    self.prefix_node(&mut branch.condition);
+                    self.prefix_nodes(&mut branch.body);
+                }
+                self.prefix_nodes(else_body);
+            },
+            Node::WhileLoop { condition, body, .. } => {
+                self.prefix_node(condition);
+                self.prefix_nodes(body);
+            },
+            Node::Label { inner, .. } => self.prefix_node(inner),
+            Node::_Unchecked(inner) |
+            Node::_Optional(inner) |
+            Node::_Implementation(inner) |
+            Node::_Generated(inner) => self.prefix_node(inner),
+            Node::_ClosureCall { closure, data_type, parameters } => {
+                self.prefix_node(closure);
+                self.prefix_data_type(&mut data_type.data);
+                self.prefix_nodes(parameters);
+            },
+            _ => {}
+        }
+    }
+
+    pub fn prefix(&mut self, ast: &mut [Positioned<Node>]) -> Result<(), GeneratorError> {
+        self.collect(ast, Level::Root)?;
+        self.prefix_nodes(ast);
+        Ok(())
+    }
+
+}
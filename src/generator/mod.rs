@@ -1,2 +1,5 @@
 pub mod project;
-pub mod generator;
\ No newline at end of file
+pub mod generator;
+pub mod library;
+pub mod source_map;
+pub mod error;
\ No newline at end of file
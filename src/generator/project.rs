@@ -1,12 +1,45 @@
+use crate::{util::position::Positioned, generator::{source_map::SourceMap, error::GeneratorError}};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Structure                                           //
+//////////////////////////////////////////////////////////////////////////////////////////////////// 
+
+/// Body of a C structure, emitted after the forward declarations of every structure.
+#[derive(Clone, Debug)]
+pub struct Structure {
+    pub name: Positioned<String>,
+    pub body: String,
+    /// Structures embedded by value, they must be defined first
+    pub embedded: Vec<String>,
+    /// Private structures of a library are defined in the source file
+    pub public: bool
+}
+
+
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                               File                                             //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct File {
     pub name: String,
     pub header: String,
-    pub src: String
+    /// Emitted at the top of the source file: globals and the private declarations of a library
+    pub internal: String,
+    pub src: String,
+    /// Spans of the `#line` directives of the source file, in order
+    pub spans: Vec<Positioned<()>>,
+    pub source_map: Option<SourceMap>,
+    pub structures: Vec<Structure>,
+    /// Structures defined by the file
+    pub types: Vec<String>,
+    /// Functions and globals defined by the file
+    pub symbols: Vec<String>,
+    /// Structures used by the declarations of the header
+    pub header_types: Vec<String>,
+    /// Structures and symbols used by the file
+    pub references: Vec<String>
 }
 
 impl File {
@@ -15,10 +48,46 @@ impl File {
         Self {
             name,
             header: String::new(),
-            src: String::new()
+            internal: String::new(),
+            src: String::new(),
+            spans: Vec::new(),
+            source_map: None,
+            structures: Vec::new(),
+            types: Vec::new(),
+            symbols: Vec::new(),
+            header_types: Vec::new(),
+            references: Vec::new()
         }
     }
 
+    pub fn append(&mut self, other: File) {
+        self.header.push_str(&other.header);
+        self.internal.push_str(&other.internal);
+        self.src.push_str(&other.src);
+        self.spans.extend(other.spans);
+        self.structures.extend(other.structures);
+    }
+
+    /// Forward declarations and bodies of the structures, in the given order.
+    pub fn layout_structures(&self, order: &[String], public: bool) -> String {
+        let structures: Vec<&Structure> = order.iter()
+            .filter_map(|name| self.structures.iter().find(|structure| &structure.name.data == name))
+            .filter(|structure| structure.public == public)
+            .collect();
+
+        let mut buf = String::new();
+        for structure in structures.iter() {
+            buf.push_str(&format!("typedef struct {0} {0};\n", structure.name.data));
+        }
+        if !structures.is_empty() {
+            buf.push('\n');
+        }
+        for structure in structures.iter() {
+            buf.push_str(&structure.body);
+        }
+        buf
+    }
+
 }
 
 
@@ -27,6 +96,7 @@ impl File {
 //                                             Project                                            //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
 
+#[derive(Debug)]
 pub struct Project {
     pub files: Vec<File>
 }
@@ -61,4 +131,167 @@ impl Project {
         }
     }
 
-}
\ No newline at end of file
+    fn structure(&self, name: &str) -> Option<&Structure> {
+        self.files.iter().flat_map(|file| file.structures.iter()).find(|structure| structure.name.data == name)
+    }
+
+    /// Every structure after the ones it embeds by value.
+    pub fn order_structures(&self) -> Result<Vec<String>, GeneratorError> {
+        let mut order = Vec::new();
+        let mut visiting = Vec::new();
+        for structure in self.files.iter().flat_map(|file| file.structures.iter()) {
+            self.visit_structure(structure, &mut visiting, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    fn visit_structure(&self, structure: &Structure, visiting: &mut Vec<Positioned<String>>, order: &mut Vec<String>) -> Result<(), GeneratorError> {
+        if order.contains(&structure.name.data) {
+            return Ok(());
+        }
+        if let Some(index) = visiting.iter().position(|visited| visited.data == structure.name.data) {
+            return Err(GeneratorError::ValueTypeCycle(visiting[index..].to_vec()));
+        }
+
+        visiting.push(structure.name.clone());
+        for embedded in structure.embedded.iter() {
+            // Externs are defined by their C headers
+            if let Some(embedded) = self.structure(embedded) {
+                self.visit_structure(embedded, visiting, order)?;
+            }
+        }
+        visiting.pop();
+
+        order.push(structure.name.data.clone());
+        Ok(())
+    }
+
+    fn type_owner(&self, name: &str) -> Option<&File> {
+        self.files.iter().find(|file| file.types.iter().any(|type_name| type_name == name))
+    }
+
+    fn owner(&self, name: &str) -> Option<&File> {
+        self.type_owner(name).or_else(|| self.files.iter().find(|file| file.symbols.iter().any(|symbol| symbol == name)))
+    }
+
+    /// Forward declarations and includes of the header of `file`, then the includes of its source.
+    /// Structures used through pointers are only declared so headers can refer to each other.
+    pub fn link(&self, file: &File) -> (String, String) {
+        let mut header_includes: Vec<String> = Vec::new();
+        for embedded in file.structures.iter().flat_map(|structure| structure.embedded.iter()) {
+            if let Some(owner) = self.type_owner(embedded) {
+                if owner.name != file.name && !header_includes.contains(&owner.name) {
+                    header_includes.push(owner.name.clone());
+                }
+            }
+        }
+
+        let mut declarations: Vec<String> = Vec::new();
+        for name in file.header_types.iter() {
+            let Some(owner) = self.type_owner(name) else {
+                continue;
+            };
+            if owner.name != file.name && !header_includes.contains(&owner.name) && !declarations.contains(name) {
+                declarations.push(name.clone());
+            }
+        }
+
+        let mut src_includes: Vec<String> = Vec::new();
+        for name in file.references.iter() {
+            if let Some(owner) = self.owner(name) {
+                if owner.name != file.name && !header_includes.contains(&owner.name) && !src_includes.contains(&owner.name) {
+                    src_includes.push(owner.name.clone());
+                }
+            }
+        }
+
+        let mut header_buf = String::new();
+        for declaration in declarations.iter() {
+            header_buf.push_str(&format!("typedef struct {0} {0};\n", declaration));
+        }
+        if !declarations.is_empty() {
+            header_buf.push('\n');
+        }
+        for include in header_includes.iter() {
+            header_buf.push_str(&format!("#include \"{}.h\"\n", include));
+        }
+        if !header_includes.is_empty() {
+            header_buf.push('\n');
+        }
+
+        let mut src_buf = String::new();
+        for include in src_includes.iter() {
+            src_buf.push_str(&format!("#include \"{}.h\"\n", include));
+        }
+
+        (header_buf, src_buf)
+    }
+
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Tests                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::{generator::error::GeneratorError, util::position::{Positioned, Position}};
+
+    use super::{Project, Structure};
+
+    fn project(structures: &[(&str, &[&str])]) -> Project {
+        let mut project = Project::new();
+        for (name, embedded) in structures {
+            project.get_file(name.to_string()).structures.push(Structure {
+                name: Positioned::new(name.to_string(), Position::default(), Position::default()),
+                body: String::new(),
+                embedded: embedded.iter().map(|x| x.to_string()).collect(),
+                public: true
+            });
+        }
+        project
+    }
+
+    #[test]
+    fn embedded_structures_first() {
+        let project = project(&[("String", &["ToCString", "Show"]), ("Show", &[]), ("ToCString", &[])]);
+        let Ok(order) = project.order_structures() else {
+            panic!("Unexpected cycle");
+        };
+        assert_eq!(order, vec!["ToCString", "Show", "String"]);
+    }
+
+    #[test]
+    fn extern_structures_are_ignored() {
+        let project = project(&[("Shape", &["point"])]);
+        assert!(matches!(project.order_structures(), Ok(order) if order == vec!["Shape"]));
+    }
+
+    #[test]
+    fn link_from_references() {
+        let mut project = project(&[("Shape", &[]), ("Point", &[])]);
+        project.get_file("Shape".to_string()).types.push("Shape".to_string());
+        project.get_file("Point".to_string()).types.push("Point".to_string());
+        project.get_file("util".to_string()).symbols.push("clamp".to_string());
+
+        let main = project.get_file("main".to_string());
+        main.header_types.push("Shape".to_string());
+        main.references.extend(["Shape", "Point", "clamp", "printf"].map(|x| x.to_string()));
+
+        let main = project.files.iter().find(|file| file.name == "main").unwrap();
+        let (header, src) = project.link(main);
+        assert_eq!(header, "typedef struct Shape Shape;\n\n");
+        assert_eq!(src, "#include \"Shape.h\"\n#include \"Point.h\"\n#include \"util.h\"\n");
+    }
+
+    #[test]
+    fn value_type_cycle() {
+        let project = project(&[("A", &["B"]), ("B", &["C"]), ("C", &["B"])]);
+        let Err(GeneratorError::ValueTypeCycle(cycle)) = project.order_structures() else {
+            panic!("Cycle not found");
+        };
+        assert_eq!(cycle.iter().map(|x| x.data.as_str()).collect::<Vec<_>>(), vec!["B", "C"]);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::util::position::Positioned;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Source Map                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Lines `c_start..=c_end` of the generated C file come from `span` in the Taly source.
+#[derive(Clone, Debug)]
+pub struct SourceMapping {
+    pub c_start: usize,
+    pub c_end: usize,
+    pub span: Positioned<()>
+}
+
+#[derive(Clone, Debug)]
+pub struct SourceMap {
+    pub file: String,
+    pub source: String,
+    pub mappings: Vec<SourceMapping>
+}
+
+impl SourceMap {
+
+    pub fn directive(source: &str, span: &Positioned<()>) -> String {
+        format!("#line {} {}", span.start.line, Self::quote(source))
+    }
+
+    /// The spans are the ones of the `#line` directives found in `c_src`, in the same order.
+    pub fn new(file: String, source: String, c_src: &str, spans: &[Positioned<()>]) -> Self {
+        let mut mappings: Vec<SourceMapping> = Vec::new();
+        let mut spans = spans.iter();
+        let line_count = c_src.lines().count();
+
+        for (index, line) in c_src.lines().enumerate() {
+            if !line.trim_start().starts_with("#line ") {
+                continue;
+            }
+
+            // A directive ends the mapping of the previous one
+            let c_line = index + 1;
+            if let Some(last) = mappings.last_mut() {
+                last.c_end = c_line - 1;
+            }
+
+            let span = spans.next().expect("More directives than spans!").clone();
+            mappings.push(SourceMapping {
+                c_start: c_line + 1,
+                c_end: line_count,
+                span
+            });
+        }
+
+        Self {
+            file,
+            source,
+            mappings
+        }
+    }
+
+    fn quote(str: &str) -> String {
+        format!("\"{}\"", str.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut buf = String::new();
+        buf.push_str("{\n");
+        buf.push_str(&format!("\t\"file\": {},\n", Self::quote(&self.file)));
+        buf.push_str(&format!("\t\"source\": {},\n", Self::quote(&self.source)));
+        buf.push_str("\t\"mappings\": [");
+        for (index, mapping) in self.mappings.iter().enumerate() {
+            if index != 0 {
+                buf.push(',');
+            }
+            // Taly lines and columns are 1-based like the C ones
+            buf.push_str(&format!(
+                "\n\t\t{{ \"c_lines\": [{}, {}], \"taly_start\": [{}, {}], \"taly_end\": [{}, {}] }}",
+                mapping.c_start, mapping.c_end,
+                mapping.span.start.line, mapping.span.start.column_index + 1,
+                mapping.span.end.line, mapping.span.end.column_index + 1
+            ));
+        }
+        if !self.mappings.is_empty() {
+            buf.push_str("\n\t");
+        }
+        buf.push_str("]\n}\n");
+        buf
+    }
+
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Tests                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use crate::util::position::{Positioned, Position};
+
+    use super::SourceMap;
+
+    fn span(line: usize, column_index: usize) -> Positioned<()> {
+        let start = Position { line, column_index, ..Position::default() };
+        let end = Position { line, column_index: column_index + 4, ..Position::default() };
+        Positioned::new((), start, end)
+    }
+
+    #[test]
+    fn directives_map_the_lines_until_the_next_one() {
+        let c_src = "#include \"main.h\"\n#line 3 \"main.taly\"\nint main() {\n#line 4 \"main.taly\"\n\treturn 0;\n}\n";
+        let map = SourceMap::new("main.c".to_string(), "main.taly".to_string(), c_src, &[span(3, 0), span(4, 4)]);
+        let ranges: Vec<(usize, usize, usize)> = map.mappings.iter().map(|x| (x.c_start, x.c_end, x.span.start.line)).collect();
+        assert_eq!(ranges, vec![(3, 3, 3), (5, 6, 4)]);
+    }
+
+    #[test]
+    fn json_is_escaped() {
+        let map = SourceMap::new("main.c".to_string(), "dir\\\"quoted\".taly".to_string(), "#line 1 \"x\"\nint x;\n", &[span(1, 0)]);
+        assert_eq!(map.to_json(), concat!(
+            "{\n",
+            "\t\"file\": \"main.c\",\n",
+            "\t\"source\": \"dir\\\\\\\"quoted\\\".taly\",\n",
+            "\t\"mappings\": [\n",
+            "\t\t{ \"c_lines\": [2, 2], \"taly_start\": [1, 1], \"taly_end\": [1, 5] }\n",
+            "\t]\n",
+            "}\n"
+        ));
+    }
+}
@@ -1,23 +1,59 @@
-use crate::{util::{position::Positioned, error::{ErrorFormat, ErrorType}, source_file::SourceFile}, parser::node::Node};
+use crate::{util::{position::Positioned, error::{ErrorFormat, ErrorType}, source_file::SourceFile}, parser::node::Node, symbolizer::error::SymbolizerError};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                            IR Error                                            //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
 
 pub enum IRError {
-    UnexpectedNode(Positioned<Node>, Option<String>),
-    FileAlreadyIncluded(Positioned<String>, Positioned<()>),
+    UnexpectedNode(Box<Positioned<Node>>, Option<String>),
+    FileAlreadyIncluded(Box<Positioned<String>>, Box<Positioned<()>>),
     CannotSpecifyAccessHere(Positioned<()>),
-    DestructorAlreadyDefined(Positioned<()>, Positioned<()>),
+    DestructorAlreadyDefined(Positioned<()>, Box<Positioned<()>>),
     DestructorShouldNotReturnAnything(Positioned<()>),
     DestructorShouldNotHaveParameters(Positioned<()>),
     DestructorShouldNotBeConstructor(Positioned<()>),
     MainFunctionShouldReturnCInt(Positioned<()>),
-    CannotHaveEmptyMatchExpression(Positioned<()>)
+    CannotHaveEmptyMatchExpression(Positioned<()>),
+    NonConstantInitializer(Positioned<()>),
+    SymbolizerError(SymbolizerError)
 }
 
 impl IRError {
 
+    /// Name of the variant, used by the diagnostic tests.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IRError::UnexpectedNode(..) => "UnexpectedNode",
+            IRError::FileAlreadyIncluded(..) => "FileAlreadyIncluded",
+            IRError::CannotSpecifyAccessHere(..) => "CannotSpecifyAccessHere",
+            IRError::DestructorAlreadyDefined(..) => "DestructorAlreadyDefined",
+            IRError::DestructorShouldNotReturnAnything(..) => "DestructorShouldNotReturnAnything",
+            IRError::DestructorShouldNotHaveParameters(..) => "DestructorShouldNotHaveParameters",
+            IRError::DestructorShouldNotBeConstructor(..) => "DestructorShouldNotBeConstructor",
+            IRError::MainFunctionShouldReturnCInt(..) => "MainFunctionShouldReturnCInt",
+            IRError::CannotHaveEmptyMatchExpression(..) => "CannotHaveEmptyMatchExpression",
+            IRError::NonConstantInitializer(..) => "NonConstantInitializer",
+            IRError::SymbolizerError(..) => "SymbolizerError",
+        }
+    }
+
+    /// Span of the main message of the error.
+    pub fn span(&self) -> Option<Positioned<()>> {
+        match self {
+            IRError::UnexpectedNode(node, _) => Some(node.convert(())),
+            IRError::FileAlreadyIncluded(file, _) => Some(file.convert(())),
+            IRError::CannotSpecifyAccessHere(pos) => Some(pos.clone()),
+            IRError::DestructorAlreadyDefined(pos, _) => Some(pos.clone()),
+            IRError::DestructorShouldNotReturnAnything(pos) => Some(pos.clone()),
+            IRError::DestructorShouldNotHaveParameters(pos) => Some(pos.clone()),
+            IRError::DestructorShouldNotBeConstructor(pos) => Some(pos.clone()),
+            IRError::MainFunctionShouldReturnCInt(pos) => Some(pos.clone()),
+            IRError::CannotHaveEmptyMatchExpression(pos) => Some(pos.clone()),
+            IRError::NonConstantInitializer(pos) => Some(pos.clone()),
+            IRError::SymbolizerError(err) => err.span(),
+        }
+    }
+
     pub fn print_error(&self, src: &SourceFile) {
         match self {
             IRError::UnexpectedNode(found, expected) => {
@@ -32,7 +68,7 @@ impl IRError {
             IRError::FileAlreadyIncluded(found, previous) => {
                 ErrorFormat::new(ErrorType::Error)
                     .add_message(format!("File '{}' already included!", found.data), Some(found.convert(())))
-                    .add_message("previously included here:".to_string(), Some(previous.clone()))
+                    .add_message("previously included here:".to_string(), Some(previous.convert(())))
                     .set_step("IR Generator".to_string()).print(src);  
             }
             IRError::CannotSpecifyAccessHere(node) => {
@@ -43,7 +79,7 @@ impl IRError {
             IRError::DestructorAlreadyDefined(found, previous) => {
                 ErrorFormat::new(ErrorType::Error)
                     .add_message(format!("Destructor already defined:"), Some(found.clone()))
-                    .add_message("previously defined here:".to_string(), Some(previous.clone()))
+                    .add_message("previously defined here:".to_string(), Some(previous.convert(())))
                     .set_step("IR Generator".to_string()).print(src);  
             },
             IRError::DestructorShouldNotReturnAnything(node) => {
@@ -71,6 +107,12 @@ impl IRError {
                     .add_message(format!("Cannot have empty match expression!"), Some(node.clone()))
                     .set_step("IR Generator".to_string()).print(src);  
             },
+            IRError::NonConstantInitializer(node) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message("Globals can only be initialized with constant expressions!".to_string(), Some(node.clone()))
+                    .set_step("IR Generator".to_string()).print(src);  
+            },
+            IRError::SymbolizerError(err) => err.print_error(src),
         }
     }
 
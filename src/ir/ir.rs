@@ -1,27 +1,29 @@
-use crate::{util::{position::Positioned, reference::MutRef}, ir::{error::IRError, output::{IROutput, Include, IncludeType}}, parser::node::{Node, ValueNode, Operator, VarType, FunctionDefinitionParameter, AccessModifier, ElifBranch, DataType,}, symbolizer::{scope::{Scope, ScopeType, Scoped}, trace::Trace}};
+use crate::{util::{position::Positioned, mangle, id::{ScopeId, SymbolId}}, ir::{error::IRError, output::{IROutput, Include, IncludeType}}, parser::node::{Node, ValueNode, Operator, VarType, FunctionDefinitionParameter, AccessModifier, ElifBranch, DataType,}, symbolizer::{scope::{Scope, ScopeType, Scoped, SymbolTable}, symbolizer::Symbolizer}};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                           IR Generator                                         //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
 
-pub struct IRGenerator {
+pub struct IRGenerator<'a> {
     ast: Vec<Positioned<Node>>,
-    scope: MutRef<Scope>,
-    trace: Trace,
+    table: &'a mut SymbolTable,
+    scope: ScopeId,
     index: usize,
     temp_id: usize,
+    lambda_id: usize,
     extra_includes: Vec<Include>,
 }
 
-impl IRGenerator {
+impl<'a> IRGenerator<'a> {
 
-    pub fn new(ast: Vec<Positioned<Node>>, scope: MutRef<Scope>) -> Self {
+    pub fn new(ast: Vec<Positioned<Node>>, table: &'a mut SymbolTable) -> Self {
         Self {
             ast,
-            scope,
-            trace: Trace::default(),
+            table,
+            scope: SymbolTable::ROOT,
             index: 0,
             temp_id: 0,
+            lambda_id: 0,
             extra_includes: Vec::new(),
         }
     } 
@@ -37,6 +39,36 @@ impl IRGenerator {
         self.extra_includes.push(include);
     }
 
+    fn add_temp_symbol(&mut self, node: Positioned<()>, name: String, var_type: VarType, data_type: Option<Positioned<DataType>>) -> SymbolId {
+        // Temporaries are visible in the whole function
+        self.table.add_symbol(self.scope, Scope::new(node.clone(), ScopeType::Variable { 
+            var_type: node.convert(var_type), 
+            name: node.convert(name),
+            data_type: data_type.map(|data_type| Scoped {
+                data: data_type,
+                scope: None
+            }),
+            initialized: true,
+            external: None
+        }, None))
+    }
+
+    /// Parameters of the function symbol being generated.
+    fn function_params(&mut self) -> &mut Vec<FunctionDefinitionParameter> {
+        let ScopeType::Function { params, .. } = &mut self.table.get_mut(self.scope).scope else {
+            unreachable!()
+        };
+        params
+    }
+
+    /// Return type of the function symbol being generated.
+    fn function_return_type(&mut self) -> &mut Option<Scoped<Positioned<DataType>>> {
+        let ScopeType::Function { return_type, .. } = &mut self.table.get_mut(self.scope).scope else {
+            unreachable!()
+        };
+        return_type
+    }
+
     /* Cursor Movement */
     fn advance(&mut self) {
         self.index += 1;
@@ -81,7 +113,7 @@ impl IRGenerator {
     }
 
     fn generate_function_call(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::FunctionCall { name, parameters } = node.data.clone() else {
+        let Node::FunctionCall { name, parameters, scope } = node.data.clone() else {
             unreachable!()
         };
 
@@ -96,28 +128,26 @@ impl IRGenerator {
             new_params.push(gen_param_last);
         }
 
-        Ok(vec![node.convert(Node::FunctionCall { 
+        pre.push(node.convert(Node::FunctionCall { 
             name: name.clone(), 
-            parameters: new_params
-        })])
+            parameters: new_params,
+            scope
+        }));
+
+        Ok(pre)
     }
 
     fn generate_function_definition(&mut self, node: Positioned<Node>, parent_type: Option<Scoped<Positioned<String>>>, root: bool) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::FunctionDefinition { name, external, constructor, mut parameters, mut return_type, mut body, access } = node.data.clone() else {
+        let Node::FunctionDefinition { name, external, constructor, mut parameters, mut return_type, mut body, access, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
         // Enter Scope
-        if let Some(function) = self.scope.get().enter_function(Trace::full(), name.data.clone(), true, true) {
-            function.get().parent = Some(self.scope.clone()); // FIXME: Somehow fix the problem
-            self.scope = function;
-        } else {
-            unreachable!("Symbol '{}' not found in {:#?}", name.data, self.scope.get().scope);
-        }
-
-        let ScopeType::Function { params: function_params, return_type: function_return_type, .. } = &mut self.scope.get().scope else {
-            unreachable!()
+        let Some(function) = scope else {
+            unreachable!("Function '{}' was not symbolized", name.data);
         };
+        self.table.get_mut(function).parent = Some(self.scope); // FIXME: Somehow fix the problem
+        self.scope = function.scope();
 
         if let Some(parent_type) = parent_type {
             if !constructor {
@@ -125,23 +155,24 @@ impl IRGenerator {
                 new_params.push(FunctionDefinitionParameter::new(name.convert("self".to_string()), parent_type.data.convert(DataType::Custom(parent_type.data.data.clone()))));
                 new_params.append(&mut parameters);
                 parameters = new_params;
-                *function_params = parameters.clone();
+                *self.function_params() = parameters.clone();
 
                 // Add Self as child Symbol
-                self.scope.get().add_child(Scope::new(node.convert(()), ScopeType::Variable { 
+                self.table.add_symbol(self.scope, Scope::new(node.convert(()), ScopeType::Variable { 
                     var_type: node.convert(VarType::Constant), 
                     name: node.convert("self".to_string()),
                     data_type: Some(Scoped {
                         data: parent_type.data.convert(DataType::Custom(parent_type.data.data.clone())), 
-                        scope: parent_type.scope.clone(),
+                        scope: parent_type.scope,
                     }),
-                    initialized: true 
-                }, Some(self.scope.clone()), Trace::new(0, self.trace.clone()), None));
+                    initialized: true,
+                    external: None
+                }, None));
             } else {
                 return_type = Some(parent_type.data.convert(DataType::Custom(parent_type.data.data.clone())));
-                *function_return_type = Some(Scoped {
+                *self.function_return_type() = Some(Scoped {
                     data: return_type.clone().unwrap(),
-                    scope: Some(self.scope.clone())
+                    scope: parent_type.scope
                 });
 
                 self.add_extra_include(Include { 
@@ -150,15 +181,16 @@ impl IRGenerator {
                 });
 
                 // Create Self Symbol
-                self.scope.get().add_child(Scope::new(node.convert(()), ScopeType::Variable { 
+                let symbol = self.table.add_symbol(self.scope, Scope::new(node.convert(()), ScopeType::Variable { 
                     var_type: node.convert(VarType::Constant), 
                     name: node.convert("self".to_string()),
                     data_type: Some(Scoped {
                         data: parent_type.data.convert(DataType::Custom(parent_type.data.data.clone())), 
-                        scope: parent_type.scope.clone(),
+                        scope: parent_type.scope,
                     }),
-                    initialized: true 
-                }, Some(self.scope.clone()), Trace::new(0, self.trace.clone()), None));
+                    initialized: true,
+                    external: None
+                }, None));
 
                 let mut new_body = Vec::new();
                 new_body.push(node.convert(Node::_Unchecked(Box::new(node.convert(Node::VariableDefinition { 
@@ -172,14 +204,19 @@ impl IRGenerator {
                                 name: node.convert("sizeof".to_string()), 
                                 parameters: vec![
                                     node.convert(Node::Value(ValueNode::Type(format!("_NOPTR_{}", parent_type.data.data.clone()))))
-                                ] 
+                                ],
+                                scope: None
                             })
-                        ] 
+                        ],
+                        scope: None
                     }))),
-                    access: None 
+                    access: None,
+                    external: None,
+                    module: None,
+                    scope: Some(symbol) 
                 })))));
                 new_body.append(&mut body);
-                new_body.push(node.convert(Node::Return(Some(Box::new(node.convert(Node::VariableCall("self".to_string())))))));
+                new_body.push(node.convert(Node::Return(Some(Box::new(node.convert(Node::VariableCall { name: "self".to_string(), scope: None }))))));
                 body = new_body;
             }
         } else if root && name.data == "main" {
@@ -187,7 +224,7 @@ impl IRGenerator {
                 match &data_type.data {
                     DataType::Custom(inner) if inner == "I32" => {
                         // Convert symbol's type
-                        *function_return_type = Some(Scoped {
+                        *self.function_return_type() = Some(Scoped {
                             data: data_type.convert(DataType::Custom("c_int".to_string())),
                             scope: None
                         });
@@ -197,7 +234,7 @@ impl IRGenerator {
                     _ => return Err(IRError::MainFunctionShouldReturnCInt(data_type.convert(()))),
                 }
             } else {
-                *function_return_type = Some(Scoped {
+                *self.function_return_type() = Some(Scoped {
                     data: name.convert(DataType::Custom("c_int".to_string())),
                     scope: None
                 });
@@ -207,14 +244,14 @@ impl IRGenerator {
         }
 
         let mut new_body = Vec::new();
-        self.trace = Trace::new(0, self.trace.clone());
         for child in body.iter() {
             if return_type.is_some() && std::ptr::eq(child, body.last().unwrap()) {
                 match child.data {
                     Node::Value(_) | 
                     Node::FunctionCall { .. } | 
-                    Node::VariableCall(_) |
-                    Node::BinaryOperation { .. } => {
+                    Node::VariableCall { .. } |
+                    Node::BinaryOperation { .. } |
+                    Node::Lambda { .. } => {
                         let mut child_checked = self.generate_function_definition_body(child.clone())?;
                         let child_last = child_checked.pop().unwrap();
                         new_body.append(&mut child_checked);
@@ -222,17 +259,15 @@ impl IRGenerator {
                         new_body.push(child.convert(Node::Return(Some(Box::new(child_last)))));
                     }
                     Node::Return(_) => new_body.append(&mut self.generate_function_definition_body(child.clone())?),
-                    _ => return Err(IRError::UnexpectedNode(node, Some("expression".to_string()))),
+                    _ => return Err(IRError::UnexpectedNode(Box::new(node), Some("expression".to_string()))),
                 }
             } else {
                 new_body.append(&mut self.generate_function_definition_body(child.clone())?);
             }
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
 
         // Exit Scope
-        if let Some(parent) = self.scope.get().parent.clone() {
+        if let Some(parent) = self.table.parent(self.scope) {
             self.scope = parent;
         } else {
             unreachable!("Not parent after entering function!");
@@ -245,7 +280,9 @@ impl IRGenerator {
             parameters: parameters.clone(), 
             return_type: return_type.clone(), 
             body: new_body,
-            access
+            access,
+            module,
+            scope
         })])
     }
 
@@ -260,7 +297,7 @@ impl IRGenerator {
                     self.generate_variable_definition(node)
                 }
             }
-            Node::VariableCall(_) => self.generate_variable_call(node),
+            Node::VariableCall { .. } => self.generate_variable_call(node),
             Node::BinaryOperation { .. } => self.generate_binary_operator(node, false),
             Node::UnaryOperation { .. } => self.generate_unary_operator(node),
             Node::Return(_) => self.generate_return(node),
@@ -270,14 +307,16 @@ impl IRGenerator {
             Node::Break(_) => self.generate_break(node),
             Node::Continue(_) => self.generate_continue(node),
             Node::Label { .. } => self.generate_label(node),
+            Node::Lambda { .. } => self.generate_lambda(node),
             Node::_Unchecked(_) => Ok(vec![node]),
             Node::_Generated(_) => Ok(vec![node]),
-            _ => Err(IRError::UnexpectedNode(node, None)),
+            Node::Error => Ok(vec![node]),
+            _ => Err(IRError::UnexpectedNode(Box::new(node), None)),
         }
     }
 
     fn generate_variable_definition(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::VariableDefinition { var_type, name, data_type, value, access } = node.data.clone() else {
+        let Node::VariableDefinition { var_type, name, data_type, value, access, external, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
@@ -296,29 +335,68 @@ impl IRGenerator {
             name, 
             data_type, 
             value: value_checked,
-            access
+            access,
+            external,
+            module,
+            scope
         }));
 
         Ok(pre)
     }
 
+    /// C requires globals to be initialized with constant expressions, bindings are folded by the
+    /// const evaluator.
+    fn is_constant_expr(node: &Positioned<Node>) -> bool {
+        match &node.data {
+            Node::Value(ValueNode::Type(_)) => false,
+            Node::Value(_) => true,
+            Node::VariableCall { .. } => true,
+            Node::UnaryOperation { value, .. } => Self::is_constant_expr(value),
+            Node::BinaryOperation { lhs, operator, rhs } => match operator.data {
+                Operator::Add | Operator::Subtract | Operator::Multiply | Operator::Divide | Operator::Modulo |
+                Operator::BooleanAnd | Operator::BooleanOr | Operator::BooleanXor |
+                Operator::Equal | Operator::NotEqual | Operator::Greater | Operator::GreaterOrEqual | Operator::Less | Operator::LessOrEqual |
+                Operator::BitwiseAnd | Operator::BitwiseOr | Operator::BitwiseXor | Operator::ShiftLeft | Operator::ShiftRight => {
+                    Self::is_constant_expr(lhs) && Self::is_constant_expr(rhs)
+                },
+                _ => false
+            },
+            _ => false
+        }
+    }
+
+    fn generate_global_variable_definition(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
+        let Node::VariableDefinition { value, .. } = &node.data else {
+            unreachable!()
+        };
+
+        if let Some(value) = value {
+            if !Self::is_constant_expr(value) {
+                return Err(IRError::NonConstantInitializer(value.convert(())));
+            }
+        }
+
+        Ok(vec![node])
+    }
+
     fn generate_expr(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
         match node.data {
             Node::Value(_) => self.generate_value(node),
             Node::FunctionCall { .. } => self.generate_function_call(node),
-            Node::VariableCall(_) => self.generate_variable_call(node),
+            Node::VariableCall { .. } => self.generate_variable_call(node),
             Node::BinaryOperation { .. } => self.generate_binary_operator(node, true),
             Node::UnaryOperation { .. } => self.generate_unary_operator(node),
-            _ => Err(IRError::UnexpectedNode(node, Some("Expression".to_string()))),
+            Node::Lambda { .. } => self.generate_lambda(node),
+            _ => Err(IRError::UnexpectedNode(Box::new(node), Some("Expression".to_string()))),
         }
     }
 
     fn generate_variable_call(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::VariableCall(name) = node.data.clone() else {
+        let Node::VariableCall { name, scope } = node.data.clone() else {
             unreachable!()
         };
 
-        Ok(vec![node.convert(Node::VariableCall(name))])
+        Ok(vec![node.convert(Node::VariableCall { name, scope })])
     }
 
     fn generate_binary_operator(&mut self, node: Positioned<Node>, used: bool) -> Result<Vec<Positioned<Node>>, IRError> {
@@ -334,26 +412,76 @@ impl IRGenerator {
         let lhs_last = lhs_gen.pop().unwrap();
         pre.append(&mut lhs_gen);
         let mut rhs_last = rhs_gen.pop().unwrap();
+        let rhs_pre_len = rhs_gen.len();
         pre.append(&mut rhs_gen);
 
         if operator.data == Operator::Assign && used {
-            let id = format!("_temp{}", self.temp_id);
-            pre.push(node.convert(Node::VariableDefinition { 
+            let id = mangle::temporary(self.temp_id);
+            let symbol = self.add_temp_symbol(node.convert(()), id.clone(), VarType::Constant, None);
+            pre.push(node.convert(Node::_Generated(Box::new(node.convert(Node::VariableDefinition { 
                 var_type: node.convert(VarType::Constant), 
                 name: node.convert(id.clone()), 
                 data_type: None, 
                 value: Some(Box::new(lhs_last.clone())),
-                access: None
-            }));
+                access: None,
+                external: None,
+                module: None,
+                scope: Some(symbol)
+            })))));
             self.temp_id += 1;
 
-            pre.push(node.convert(Node::BinaryOperation { 
+            pre.push(node.convert(Node::_Generated(Box::new(node.convert(Node::BinaryOperation { 
                 lhs: Box::new(lhs_last.clone()), 
                 operator, 
                 rhs: Box::new(rhs_last) 
-            }));
+            })))));
 
-            pre.push(node.convert(Node::VariableCall(id.clone())));
+            pre.push(node.convert(Node::VariableCall { name: id.clone(), scope: None }));
+        } else if (operator.data == Operator::BooleanAnd || operator.data == Operator::BooleanOr) && rhs_pre_len != 0 {
+            // Keep the hoisted rhs behind the lhs to preserve short-circuiting
+            // lhs and rhs => var _temp = lhs; if _temp then (rhs pre; _temp = rhs); _temp
+            let id = mangle::temporary(self.temp_id);
+            let symbol = self.add_temp_symbol(node.convert(()), id.clone(), VarType::Variable, Some(node.convert(DataType::Custom("Bool".to_string()))));
+            self.temp_id += 1;
+
+            // Split the hoisted nodes of the rhs from the ones of the lhs
+            let mut rhs_pre = pre.split_off(pre.len() - rhs_pre_len);
+
+            pre.push(node.convert(Node::_Generated(Box::new(node.convert(Node::VariableDefinition { 
+                var_type: node.convert(VarType::Variable), 
+                name: node.convert(id.clone()), 
+                data_type: Some(node.convert(DataType::Custom("Bool".to_string()))), 
+                value: Some(Box::new(lhs_last)),
+                access: None,
+                external: None,
+                module: None,
+                scope: Some(symbol)
+            })))));
+
+            let condition = if operator.data == Operator::BooleanAnd {
+                node.convert(Node::VariableCall { name: id.clone(), scope: None })
+            } else {
+                node.convert(Node::UnaryOperation { 
+                    operator: operator.convert(Operator::BooleanNot), 
+                    value: Box::new(node.convert(Node::VariableCall { name: id.clone(), scope: None })) 
+                })
+            };
+
+            rhs_pre.push(node.convert(Node::_Generated(Box::new(node.convert(Node::BinaryOperation { 
+                lhs: Box::new(node.convert(Node::VariableCall { name: id.clone(), scope: None })), 
+                operator: operator.convert(Operator::Assign), 
+                rhs: Box::new(rhs_last) 
+            })))));
+
+            pre.push(node.convert(Node::_Generated(Box::new(node.convert(Node::IfStatement { 
+                condition: Box::new(condition), 
+                body: rhs_pre, 
+                elif_branches: Vec::new(), 
+                else_body: Vec::new(),
+                scopes: Vec::new() 
+            })))));
+
+            pre.push(node.convert(Node::VariableCall { name: id.clone(), scope: None }));
         } else if operator.data == Operator::Access {
             if let Node::FunctionCall { parameters, .. } = &mut rhs_last.data {
                 let mut new_params = Vec::new();
@@ -373,24 +501,6 @@ impl IRGenerator {
                     rhs: Box::new(rhs_last) 
                 }));
             }
-        } else if operator.data == Operator::BooleanXor {
-            // lhs xor rhs => lhs || rhs && !(lhs && rhs)
-            pre.push(node.convert(Node::BinaryOperation { 
-                lhs: Box::new(node.convert(Node::BinaryOperation { 
-                    lhs: Box::new(lhs_last.clone()), 
-                    operator: operator.convert(Operator::BooleanOr), 
-                    rhs: Box::new(rhs_last.clone()) 
-                })), 
-                operator: operator.convert(Operator::BooleanAnd), 
-                rhs: Box::new(node.convert(Node::UnaryOperation { 
-                    operator: operator.convert(Operator::BooleanNot), 
-                    value: Box::new(node.convert(Node::BinaryOperation { 
-                        lhs: Box::new(lhs_last.clone()), 
-                        operator: operator.convert(Operator::BooleanAnd), 
-                        rhs: Box::new(rhs_last.clone()) 
-                    })) 
-                })) 
-            }))
         } else {
             pre.push(node.convert(Node::BinaryOperation { 
                 lhs: Box::new(lhs_last), 
@@ -417,6 +527,44 @@ impl IRGenerator {
         Ok(value_gen)
     }
 
+    fn generate_lambda(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
+        let Node::Lambda { parameters, return_type, body } = node.data.clone() else {
+            unreachable!()
+        };
+
+        let function = node.convert(Node::FunctionDefinition { 
+            name: node.convert(mangle::lambda(self.lambda_id)), 
+            external: None, 
+            constructor: false, 
+            parameters, 
+            return_type, 
+            body, 
+            access: None,
+            module: None,
+            scope: None 
+        });
+        self.lambda_id += 1;
+
+        self.add_extra_include(Include { 
+            include_type: IncludeType::StdExternal, 
+            path: node.convert("stdlib.h".to_string()) 
+        });
+
+        // Lambdas are symbolized as functions of the current scope
+        let mut symbolizer = Symbolizer::new(vec![function], self.table);
+        let function = match symbolizer.symbolize(self.scope) {
+            Ok(mut ast) => ast.pop().unwrap(),
+            Err(err) => return Err(IRError::SymbolizerError(err))
+        };
+
+        let mut gen_function = self.generate_function_definition(function, None, false)?;
+
+        Ok(vec![node.convert(Node::_Lambda { 
+            function: Box::new(gen_function.pop().unwrap()), 
+            captures: Vec::new() 
+        })])
+    }
+
     fn generate_return(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
         let Node::Return(expr) = node.data.clone() else {
             unreachable!()
@@ -439,17 +587,24 @@ impl IRGenerator {
     }
 
     fn generate_class_definition(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::ClassDefinition { name, mut body, access, extensions } = node.data.clone() else {
+        let Node::ClassDefinition { name, mut body, access, extensions, external, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
-        // Enter Scope
-        if let Some(class) = self.scope.get().enter_class(Trace::full(), name.data.clone()) {
-            self.scope = class;
-        } else {
-            unreachable!("Symbol '{}' not found in {:#?}", name.data, self.scope.get());
+        // Extern classes are defined in C, nothing to construct or destroy
+        if external.is_some() {
+            return Ok(vec![node]);
         }
 
+        // Enter Scope
+        let Some(class) = scope else {
+            unreachable!("Class '{}' was not symbolized", name.data);
+        };
+        self.scope = class.scope();
+        let ScopeType::Class { extensions: interfaces, .. } = self.table.get(class).scope.clone() else {
+            unreachable!()
+        };
+
         let mut new_body = Vec::new();
         let mut destructor = None;
         let mut has_constructor = false;
@@ -457,25 +612,21 @@ impl IRGenerator {
         let mut init_construction = Vec::new();
 
         // Generate fields for extensions
-        for extension in extensions.iter() {
+        for (extension, interface) in extensions.iter().zip(interfaces) {
             has_fields = true;
-            let field_name = extension.convert(format!("base_{}", extension.data.clone()));
-
-            // Find Interface TODO: replace Trace::full() by self.trace.clone()
-            let Some(interface) = self.scope.get().get_interface(Trace::full(), extension.data.clone()) else {
-                todo!("Error, interface '{}' not found!", extension.data);
-            };
+            let field_name = extension.convert(mangle::base_field(&extension.data));
 
             // Add Symbol
-            self.scope.get().add_child(Scope::new(extension.convert(()), ScopeType::Variable { 
+            let field = self.table.add_symbol(self.scope, Scope::new(extension.convert(()), ScopeType::Variable { 
                 var_type: extension.convert(VarType::Constant), 
                 name: field_name.clone(), 
                 data_type: Some(Scoped {
                     data: extension.convert(DataType::Custom(format!("_NOPTR_{}", extension.data.clone()))),
-                    scope: Some(interface.clone())
+                    scope: Some(interface)
                 }), 
-                initialized: true // True because initialized using unchecked node
-            }, Some(self.scope.clone()), Trace::new(0, self.trace.clone()), None));
+                initialized: true, // True because initialized using unchecked node
+                external: None
+            }, None));
 
             // Add Node
             new_body.push(extension.convert(Node::_Generated(Box::new(extension.convert(Node::VariableDefinition { 
@@ -483,15 +634,18 @@ impl IRGenerator {
                 name: field_name.clone(), 
                 data_type: Some(extension.convert(DataType::Custom(extension.data.clone()))), 
                 value: None, 
-                access: None 
+                access: None,
+                external: None,
+                module: None,
+                scope: Some(field) 
             })))));
 
             // Add Initialization to list (allocation & then field set) 
             // init_construction.push(extension.convert(Node::_Generated(Box::new(extension.convert(Node::_Unchecked(Box::new(extension.convert(Node::BinaryOperation { 
             //     lhs: Box::new(extension.convert(Node::BinaryOperation { 
-            //         lhs: Box::new(extension.convert(Node::VariableCall("self".to_string()))), 
+            //         lhs: Box::new(extension.convert(Node::VariableCall { name: "self".to_string(), scope: None })), 
             //         operator: extension.convert(Operator::Access), 
-            //         rhs: Box::new(extension.convert(Node::VariableCall(field_name.clone().data))) 
+            //         rhs: Box::new(extension.convert(Node::VariableCall { name: field_name.clone().data, scope: None })) 
             //     })), 
             //     operator: extension.convert(Operator::Assign), 
             //     rhs: Box::new(extension.convert(Node::FunctionCall { 
@@ -508,30 +662,31 @@ impl IRGenerator {
             // }))))))));
 
             // TODO: Functions ptr initialization
-            let ScopeType::Interface { name: intf_name, children, .. } = &interface.get().scope else {
+            let interface_name = self.table.process_name(interface);
+            let class_name = self.table.process_name(self.scope);
+            let ScopeType::Interface { children, .. } = &self.table.get(interface).scope else {
                 unreachable!()
             };
 
             for scope in children.iter() {
-                if let ScopeType::Function { name: fun_name, .. } = &scope.scope {
+                if let ScopeType::Function { name: fun_name, .. } = &self.table.get(*scope).scope {
                     init_construction.push(extension.convert(Node::_Generated(Box::new(extension.convert(Node::_Unchecked(Box::new(extension.convert(Node::BinaryOperation { 
                         lhs: Box::new(extension.convert(Node::BinaryOperation { 
                             lhs: Box::new(extension.convert(Node::BinaryOperation { 
-                                lhs: Box::new(extension.convert(Node::VariableCall("self".to_string()))), 
+                                lhs: Box::new(extension.convert(Node::VariableCall { name: "self".to_string(), scope: None })), 
                                 operator: extension.convert(Operator::Access),
-                                rhs: Box::new(extension.convert(Node::VariableCall(field_name.clone().data))) 
+                                rhs: Box::new(extension.convert(Node::VariableCall { name: field_name.clone().data, scope: None })) 
                             })), 
                             operator: extension.convert(Operator::DotAccess), 
-                            rhs: Box::new(extension.convert(Node::VariableCall(format!("{}_{}", intf_name.data, fun_name.data.clone())))) 
+                            rhs: Box::new(extension.convert(Node::VariableCall { name: mangle::member(&interface_name, &fun_name.data), scope: None })) 
                         })), 
                         operator: extension.convert(Operator::Assign), 
-                        rhs: Box::new(extension.convert(Node::VariableCall(format!("&{}_{}_impl", name.data, fun_name.data))))  // TODO: Change the & to a reference node
+                        rhs: Box::new(extension.convert(Node::VariableCall { name: format!("&{}", mangle::implementation(&mangle::member(&class_name, &fun_name.data))), scope: None }))  // TODO: Change the & to a reference node
                     }))))))));
                 }
             }
         }
 
-        self.trace = Trace::new(0, self.trace.clone());
         for node in body.iter_mut() {
             let node_pos = node.convert(());
             if let Node::FunctionDefinition { name: function_name, return_type, parameters, constructor, body, .. } = &mut node.data {
@@ -544,7 +699,7 @@ impl IRGenerator {
                 }
                 if function_name.data == "destroy" {
                     if let Some(destructor) = destructor {
-                        return Err(IRError::DestructorAlreadyDefined(node.convert(()), destructor));
+                        return Err(IRError::DestructorAlreadyDefined(node.convert(()), Box::new(destructor)));
                     } 
                     destructor = Some(node_pos.clone());
 
@@ -566,15 +721,12 @@ impl IRGenerator {
                 has_fields = true;
             }
             new_body.append(&mut self.generate_class_definition_body(node.clone(), name.clone())?);
-            self.trace.index += 1;
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
 
         // Generate destructor
         if destructor.is_none() {
             // First Generate the symbol
-            self.scope.get().add_child(Scope::new(name.convert(()), ScopeType::Function { 
+            let symbol = self.table.add_symbol(self.scope, Scope::new(name.convert(()), ScopeType::Function { 
                 name: name.convert("destroy".to_string()), 
                 params: vec![FunctionDefinitionParameter {
                     name: name.convert("self".to_string()), 
@@ -582,14 +734,14 @@ impl IRGenerator {
                 }], 
                 children: vec![], 
                 return_type: None, 
-                external: false, 
+                external: None, 
                 constructor: false, 
                 implementation: false 
-            }, Some(self.scope.clone()), self.trace.clone(), Some(name.convert(AccessModifier::Public))));
+            }, Some(name.convert(AccessModifier::Public))));
 
             for node in self.generate_class_definition_body(name.convert(Node::FunctionDefinition { 
                 name: name.convert("destroy".to_string()), 
-                external: false, 
+                external: None, 
                 constructor: false, 
                 parameters: vec![], 
                 return_type: None, 
@@ -597,11 +749,14 @@ impl IRGenerator {
                     name.convert(Node::_Unchecked(Box::new(name.convert(Node::FunctionCall { 
                         name: name.convert("free".to_string()), 
                         parameters: vec![
-                            name.convert(Node::VariableCall("self".to_string()))
-                        ] 
+                            name.convert(Node::VariableCall { name: "self".to_string(), scope: None })
+                        ],
+                        scope: None
                     }))))
                 ],
-                access: Some(node.convert(AccessModifier::Public)) 
+                access: Some(node.convert(AccessModifier::Public)),
+                module: None,
+                scope: Some(symbol) 
             }), name.clone())? {
                 new_body.push(node.clone().convert(Node::_Generated(Box::new(node))));
             }
@@ -609,21 +764,33 @@ impl IRGenerator {
 
         // Generate default constructor
         if !has_constructor && !has_fields {
+            let symbol = self.table.add_symbol(self.scope, Scope::new(name.convert(()), ScopeType::Function { 
+                name: name.convert("create".to_string()), 
+                params: vec![], 
+                children: vec![], 
+                return_type: None, 
+                external: None, 
+                constructor: true, 
+                implementation: false 
+            }, Some(name.convert(AccessModifier::Public))));
+
             for node in self.generate_class_definition_body(name.convert(Node::FunctionDefinition { 
                 name: name.convert("create".to_string()), 
-                external: false, 
+                external: None, 
                 constructor: true, 
                 parameters: vec![], 
                 return_type: None, 
                 body: vec![], 
-                access: Some(node.convert(AccessModifier::Public)) 
+                access: Some(node.convert(AccessModifier::Public)),
+                module: None,
+                scope: Some(symbol) 
             }), name.clone())? {
                 new_body.push(node.clone().convert(Node::_Generated(Box::new(node))));
             }
         }
 
         // Exit Scope
-        if let Some(parent) = self.scope.get().parent.clone() {
+        if let Some(parent) = self.table.parent(self.scope) {
             self.scope = parent;
         } else {
             unreachable!("Not parent after entering function!");
@@ -633,7 +800,10 @@ impl IRGenerator {
             name, 
             body: new_body,
             access,
-            extensions
+            extensions,
+            external,
+            module,
+            scope
         })])
     }
 
@@ -641,33 +811,45 @@ impl IRGenerator {
         match node.data {
             Node::FunctionDefinition { .. } => self.generate_function_definition(node, Some(Scoped {
                 data: parent_type,
-                scope: Some(self.scope.clone())
+                scope: self.table.symbol(self.scope)
             }), false),
             Node::VariableDefinition { .. } => self.generate_variable_definition(node),
             Node::_Unchecked(_) => Ok(vec![node]),
             Node::_Generated(_) => Ok(vec![node]),
-            _ => Err(IRError::UnexpectedNode(node, None)),
+            Node::Error => Ok(vec![node]),
+            _ => Err(IRError::UnexpectedNode(Box::new(node), None)),
         }
     }
 
     fn generate_space_definition(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::SpaceDefinition { name, body, access } = node.data.clone() else {
+        let Node::SpaceDefinition { name, body, access, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
+        // Enter Scope
+        let Some(space) = scope else {
+            unreachable!("Space '{}' was not symbolized", name.data);
+        };
+        self.scope = space.scope();
+
         let mut new_body = Vec::new();
-        self.trace = Trace::new(0, self.trace.clone());
         for node in body.iter() {
             new_body.append(&mut self.generate_space_definition_body(node.clone())?);
-            self.trace.index += 1;
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
+
+        // Exit Scope
+        if let Some(parent) = self.table.parent(self.scope) {
+            self.scope = parent;
+        } else {
+            unreachable!("Not parent after entering function!");
+        }
 
         Ok(vec![node.convert(Node::SpaceDefinition { 
             name, 
             body: new_body,
-            access
+            access,
+            module,
+            scope
         })])
     }
 
@@ -677,39 +859,84 @@ impl IRGenerator {
             Node::ClassDefinition { .. } => self.generate_class_definition(node),
             Node::SpaceDefinition { .. } => self.generate_space_definition(node),
             Node::InterfaceDefinition { .. } => self.generate_interface_definition(node),
+            Node::VariableDefinition { .. } => self.generate_global_variable_definition(node),
             Node::_Unchecked(_) => Ok(vec![node]),
             Node::_Generated(_) => Ok(vec![node]),
-            _ => Err(IRError::UnexpectedNode(node, None)),
+            Node::Error => Ok(vec![node]),
+            _ => Err(IRError::UnexpectedNode(Box::new(node), None)),
         }
     }
 
     fn generate_if_statement(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::IfStatement { condition, body, elif_branches, else_body } = node.data.clone() else {
+        let Node::IfStatement { condition, body, elif_branches, else_body, scopes } = node.data.clone() else {
             unreachable!()
         };
 
-        let mut pre = Vec::new();
-
-        let mut gen_condition = self.generate_expr(*condition)?;
-        let gen_condition_last = gen_condition.pop().unwrap();
-        pre.append(&mut gen_condition);
+        let mut pre = self.generate_expr(*condition)?;
+        let gen_condition_last = pre.pop().unwrap();
 
         let mut gen_body = Vec::new();
         for node in body {
             gen_body.append(&mut self.generate_function_definition_body(node)?);
         }
 
+        self.generate_if_branches(node.convert(()), pre, gen_condition_last, gen_body, elif_branches, else_body, scopes)
+    }
+
+    /// Generates the branches following an already generated `if`. An `elif` whose condition needs 
+    /// statements may only run them when the branches before it were not taken, so it and the 
+    /// rest of the chain become `else { <pre>; if cond ... }`.
+    #[allow(clippy::too_many_arguments)]
+    fn generate_if_branches(
+        &mut self, 
+        position: Positioned<()>, 
+        mut pre: Vec<Positioned<Node>>, 
+        condition: Positioned<Node>, 
+        body: Vec<Positioned<Node>>, 
+        elif_branches: Vec<ElifBranch>, 
+        else_body: Vec<Positioned<Node>>, 
+        mut scopes: Vec<ScopeId>
+    ) -> Result<Vec<Positioned<Node>>, IRError> {
         let mut elif_branch_gen = Vec::new();
-        for elif_branch in elif_branches {
+        let mut elif_branches = elif_branches.into_iter();
+        while let Some(elif_branch) = elif_branches.next() {
             let mut gen_condition = self.generate_expr(elif_branch.condition)?;
             let gen_condition_last = gen_condition.pop().unwrap();
-            pre.append(&mut gen_condition);
 
             let mut gen_body = Vec::new();
             for node in elif_branch.body {
                 gen_body.append(&mut self.generate_function_definition_body(node)?);
             }
 
+            if !gen_condition.is_empty() {
+                let index = elif_branch_gen.len() + 1;
+                let Some(parent) = self.table.parent(scopes[0]) else {
+                    unreachable!("If was not symbolized");
+                };
+                let else_scope = self.table.add_child(parent, Scope::new(gen_condition_last.convert(()), ScopeType::Branch { 
+                    label: None, 
+                    debug_name: "Else".to_string(), 
+                    children: Vec::new() 
+                }, Some(gen_condition_last.convert(AccessModifier::Public))));
+
+                let rest = scopes.split_off(index);
+                for scope in &rest {
+                    self.table.move_child(*scope, else_scope);
+                }
+                scopes.push(else_scope);
+
+                let elif_position = gen_condition_last.convert(());
+                let gen_else_body = self.generate_if_branches(elif_position, gen_condition, gen_condition_last, gen_body, elif_branches.collect(), else_body, rest)?;
+                pre.push(position.convert(Node::IfStatement { 
+                    condition: Box::new(condition), 
+                    body, 
+                    elif_branches: elif_branch_gen, 
+                    else_body: gen_else_body,
+                    scopes 
+                }));
+                return Ok(pre);
+            }
+
             elif_branch_gen.push(ElifBranch {
                 condition: gen_condition_last,
                 body: gen_body
@@ -721,42 +948,66 @@ impl IRGenerator {
             gen_else_body.append(&mut self.generate_function_definition_body(node)?);
         }
 
-        pre.push(node.convert(Node::IfStatement { 
-            condition: Box::new(gen_condition_last), 
-            body: gen_body, 
+        pre.push(position.convert(Node::IfStatement { 
+            condition: Box::new(condition), 
+            body, 
             elif_branches: elif_branch_gen, 
-            else_body: gen_else_body 
+            else_body: gen_else_body,
+            scopes 
         }));
 
         Ok(pre)
     }
 
     fn generate_while_loop(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::WhileLoop { condition, body } = node.data.clone() else {
+        let Node::WhileLoop { condition, body, scope } = node.data.clone() else {
             unreachable!()
         };
 
-        let mut pre = Vec::new();
-
         let mut gen_condition = self.generate_expr(*condition)?;
-        let gen_condition_last = gen_condition.pop().unwrap();
-        pre.append(&mut gen_condition);
+        let mut gen_condition_last = gen_condition.pop().unwrap();
 
         let mut gen_body = Vec::new();
+        if !gen_condition.is_empty() {
+            // The condition needs statements (like and/or temporaries) which have to run on every 
+            // iteration, so loop forever and break at the top of the body: `continue` still 
+            // re-evaluates the condition
+            let Some(while_scope) = scope else {
+                unreachable!("Loop was not symbolized");
+            };
+            let if_scope = self.table.add_child(while_scope, Scope::new(gen_condition_last.convert(()), ScopeType::Branch { 
+                label: None, 
+                debug_name: "If".to_string(), 
+                children: Vec::new() 
+            }, Some(gen_condition_last.convert(AccessModifier::Public))));
+
+            gen_body.append(&mut gen_condition);
+            gen_body.push(gen_condition_last.convert(Node::IfStatement { 
+                condition: Box::new(gen_condition_last.convert(Node::UnaryOperation { 
+                    operator: gen_condition_last.convert(Operator::BooleanNot), 
+                    value: Box::new(gen_condition_last.clone()) 
+                })), 
+                body: vec![gen_condition_last.convert(Node::Break(None))], 
+                elif_branches: Vec::new(), 
+                else_body: Vec::new(), 
+                scopes: vec![if_scope] 
+            }));
+            gen_condition_last = gen_condition_last.convert(Node::Value(ValueNode::Bool(true)));
+        }
+
         for node in body {
             gen_body.append(&mut self.generate_function_definition_body(node)?);
         }
 
-        pre.push(node.convert(Node::WhileLoop { 
+        Ok(vec![node.convert(Node::WhileLoop { 
             condition: Box::new(gen_condition_last), 
-            body: gen_body 
-        }));
-
-        Ok(pre)
+            body: gen_body,
+            scope 
+        })])
     }
 
     fn generate_match_statement(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::MatchStatement { expr, branches, else_body } = node.data.clone() else {
+        let Node::MatchStatement { expr, branches, else_body, scopes } = node.data.clone() else {
             unreachable!()
         };
 
@@ -782,7 +1033,7 @@ impl IRGenerator {
                 if let Some(final_condition) = &mut final_condition {
                     *final_condition = gen_condition_last.clone().convert(Node::BinaryOperation { 
                         lhs: Box::new(final_condition.clone()), 
-                        operator: node.convert(Operator::BooleanAnd), 
+                        operator: node.convert(Operator::BooleanOr), 
                         rhs: Box::new(gen_condition_last.clone().convert(Node::BinaryOperation { 
                             lhs: Box::new(gen_expr_last.clone()),
                             operator: gen_condition_last.convert(Operator::Equal), 
@@ -823,7 +1074,8 @@ impl IRGenerator {
             condition: Box::new(if_condition.unwrap()), 
             body: if_body, 
             elif_branches: gen_branches, 
-            else_body: else_body_gen 
+            else_body: else_body_gen,
+            scopes 
         }));
 
         Ok(pre)
@@ -860,28 +1112,23 @@ impl IRGenerator {
     }
 
     fn generate_interface_definition(&mut self, node: Positioned<Node>) -> Result<Vec<Positioned<Node>>, IRError> {
-        let Node::InterfaceDefinition { name, body, access } = node.data.clone() else {
+        let Node::InterfaceDefinition { name, body, access, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
         // Enter Scope
-        if let Some(interface) = self.scope.get().enter_interface(Trace::full(), name.data.clone()) {
-            self.scope = interface;
-        } else {
-            unreachable!("Symbol '{}' not found", name.data);
-        }
+        let Some(interface) = scope else {
+            unreachable!("Interface '{}' was not symbolized", name.data);
+        };
+        self.scope = interface.scope();
 
         let mut new_body = Vec::new();
-        self.trace = Trace::new(0, self.trace.clone());
         for node in body.iter() {
             new_body.append(&mut self.generate_interface_definition_body(node.clone())?);
-            self.trace.index += 1;
         }
-        let parent_trace = *self.trace.clone().parent.unwrap();
-        self.trace = parent_trace;
 
         // Exit Scope
-        if let Some(parent) = self.scope.get().parent.clone() {
+        if let Some(parent) = self.table.parent(self.scope) {
             self.scope = parent;
         } else {
             unreachable!("Not parent after entering function!");
@@ -890,7 +1137,9 @@ impl IRGenerator {
         Ok(vec![node.convert(Node::InterfaceDefinition { 
             name, 
             body: new_body,
-            access
+            access,
+            module,
+            scope
         })])
     }
 
@@ -899,7 +1148,8 @@ impl IRGenerator {
             Node::FunctionDefinition { constructor, .. } if !constructor => self.generate_function_definition(node, None, false),
             Node::_Unchecked(_) => Ok(vec![node]),
             Node::_Generated(_) => Ok(vec![node]),
-            _ => Err(IRError::UnexpectedNode(node, None)),
+            Node::Error => Ok(vec![node]),
+            _ => Err(IRError::UnexpectedNode(Box::new(node), None)),
         }
     }
 
@@ -913,25 +1163,33 @@ impl IRGenerator {
             match current.data {
                 Node::FunctionDefinition { constructor, .. } if !constructor => output.ast.append(&mut self.generate_function_definition(current, None, true)?),
                 Node::ClassDefinition { .. } => output.ast.append(&mut self.generate_class_definition(current)?),
+                Node::VariableDefinition { external: Some(_), .. } => output.ast.push(current),
+                Node::VariableDefinition { .. } => output.ast.append(&mut self.generate_global_variable_definition(current)?),
                 Node::SpaceDefinition { .. } => output.ast.append(&mut self.generate_space_definition(current)?),
                 Node::InterfaceDefinition { .. } => output.ast.append(&mut self.generate_interface_definition(current)?),
                 Node::_Unchecked(_) => output.ast.push(current),
                 Node::_Generated(_) => output.ast.push(current),
+                Node::Error => output.ast.push(current),
                 Node::Use(path) => {
                     for include in output.includes.iter() {
                         if include.full_path() == format!("{}.h", path.data) {
-                            return Err(IRError::FileAlreadyIncluded(path, include.path.convert(())));
+                            return Err(IRError::FileAlreadyIncluded(Box::new(path), Box::new(include.path.convert(()))));
                         }
                     }
 
                     output.includes.push(self.generate_include(path)?);
                 }
-                _ => return Err(IRError::UnexpectedNode(current, None)),
+                _ => return Err(IRError::UnexpectedNode(Box::new(current), None)),
             }
             self.advance();
-            self.trace.index += 1;
         } 
 
+        // Bool is a builtin type
+        self.add_extra_include(Include { 
+            include_type: IncludeType::StdExternal, 
+            path: Positioned::new("stdbool.h".to_string(), Default::default(), Default::default()) 
+        });
+
         // Add Extra includes
         'A: for include in self.extra_includes.iter() {
             for already in output.includes.iter() {
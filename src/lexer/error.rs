@@ -5,12 +5,37 @@ use crate::util::{position::Positioned, error::{ErrorFormat, ErrorType}, source_
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
 
 pub enum LexerError {
-    UnexpectedChar(Positioned<char>, Option<String>),
-    UnexpectedEOF(Option<String>)
+    UnexpectedChar(Positioned<char>, Option<&'static str>),
+    UnexpectedEOF(Option<String>),
+    MixedIndentation(Positioned<()>),
+    UnalignedIndentation(Positioned<()>, usize),
+    TooDeepIndentation(Positioned<()>, usize)
 }
 
 impl LexerError {
 
+    /// Name of the variant, used by the diagnostic tests.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LexerError::UnexpectedChar(..) => "UnexpectedChar",
+            LexerError::UnexpectedEOF(..) => "UnexpectedEOF",
+            LexerError::MixedIndentation(..) => "MixedIndentation",
+            LexerError::UnalignedIndentation(..) => "UnalignedIndentation",
+            LexerError::TooDeepIndentation(..) => "TooDeepIndentation",
+        }
+    }
+
+    /// Span of the main message of the error.
+    pub fn span(&self) -> Option<Positioned<()>> {
+        match self {
+            LexerError::UnexpectedChar(chr, _) => Some(chr.convert(())),
+            LexerError::UnexpectedEOF(_) => None,
+            LexerError::MixedIndentation(pos) |
+            LexerError::UnalignedIndentation(pos, _) |
+            LexerError::TooDeepIndentation(pos, _) => Some(pos.clone()),
+        }
+    }
+
     pub fn print_error(&self, src: &SourceFile) {
         match self {
             LexerError::UnexpectedChar(found, expected) => {
@@ -31,6 +56,23 @@ impl LexerError {
                 }
                 ErrorFormat::new(ErrorType::Error).add_message(buf, None).set_step("Lexer".to_string()).print(src);
             },
+            LexerError::MixedIndentation(pos) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message("Indentation mixes tabs and spaces!".to_string(), Some(pos.clone()))
+                    .add_hint("indent with 4 spaces or one tab per level".to_string())
+                    .set_step("Lexer".to_string()).print(src);
+            },
+            LexerError::UnalignedIndentation(pos, spaces) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message(format!("Indentation of {} spaces is not a multiple of 4!", spaces), Some(pos.clone()))
+                    .add_hint("indent with 4 spaces or one tab per level".to_string())
+                    .set_step("Lexer".to_string()).print(src);
+            },
+            LexerError::TooDeepIndentation(pos, levels) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message(format!("Indentation is more than one level deeper than the previous line ({} levels)!", levels), Some(pos.clone()))
+                    .set_step("Lexer".to_string()).print(src);
+            },
         }
     }
 
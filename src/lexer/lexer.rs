@@ -1,3 +1,5 @@
+use unicode_xid::UnicodeXID;
+
 use crate::{util::position::{Position, Positioned}, lexer::{tokens::{Token, Keyword}, error::LexerError}};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -31,6 +33,12 @@ impl Lexer {
         self.pos.advance(self.current())
     }
 
+    fn advance_x(&mut self, x: usize) {
+        for _ in 0..x {
+            self.advance();
+        }
+    }
+
     fn make_single<T>(&mut self, data: T) -> Positioned<T> {
         let start = self.pos.clone();
         let mut end = self.pos.clone();
@@ -38,12 +46,40 @@ impl Lexer {
         Positioned::new(data, start, end)
     }
 
+    fn make_with_equal(&mut self, single: Token, with_equal: Token) -> Positioned<Token> {
+        if self.peek(1) == '=' {
+            let start = self.pos.clone();
+            self.advance();
+            let mut end = self.pos.clone();
+            end.advance('=');
+            Positioned::new(with_equal, start, end)
+        } else {
+            self.make_single(single)
+        }
+    }
+
+    fn make_double(&mut self, double: Token, double_equal: Token) -> Positioned<Token> {
+        let start = self.pos.clone();
+        self.advance();
+        if self.peek(1) == '=' {
+            self.advance();
+            let mut end = self.pos.clone();
+            end.advance('=');
+            Positioned::new(double_equal, start, end)
+        } else {
+            let mut end = self.pos.clone();
+            end.advance(self.current());
+            Positioned::new(double, start, end)
+        }
+    }
+
+    /// Identifiers follow the Unicode XID rules, `_` being a continue character.
     pub fn make_identifier(&mut self) -> Result<Positioned<Token>, LexerError> {
         let start = self.pos.clone();
         let mut buf = String::new();
 
         let mut current = self.current();
-        while current.is_alphanumeric() || current == '_' {
+        while current.is_xid_continue() {
             buf.push(current);
             self.advance();
             current = self.current();
@@ -113,32 +149,95 @@ impl Lexer {
         }
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<Positioned<Token>>, LexerError> {
-        let mut tokens = Vec::new();
-        let mut space_count = 0;
-        let mut space_start_pos = self.pos.clone();
+    /// Reads the indentation of the line, emits an `Indent` when it is one level deeper and a `Dedent`
+    /// per level closed. Blank and comment-only lines keep the current level.
+    fn make_indentation(&mut self, tokens: &mut Vec<Positioned<Token>>, levels: &mut usize) -> Result<(), LexerError> {
+        let start = self.pos.clone();
+        let mut spaces = 0;
+        let mut tabs = 0;
+        loop {
+            match self.current() {
+                ' ' => spaces += 1,
+                '\t' => tabs += 1,
+                _ => break
+            }
+            self.advance();
+        }
 
+        if matches!(self.current(), '\n' | '\r' | '#' | '\0') {
+            return Ok(());
+        }
+
+        let indentation = Positioned::new((), start, self.pos.clone());
+        if spaces > 0 && tabs > 0 {
+            return Err(LexerError::MixedIndentation(indentation));
+        }
+        if spaces % 4 != 0 {
+            return Err(LexerError::UnalignedIndentation(indentation, spaces));
+        }
+
+        let level = tabs + spaces / 4;
+        if level > *levels + 1 {
+            return Err(LexerError::TooDeepIndentation(indentation, *levels));
+        }
+        if level > *levels {
+            tokens.push(indentation.convert(Token::Indent));
+        }
+        for _ in level..*levels {
+            tokens.push(self.make_single(Token::Dedent));
+        }
+        *levels = level;
+        Ok(())
+    }
+
+    /// Called on the `\n` of a line inside brackets. The next line continues the statement, however
+    /// shallow, unless it starts with a statement keyword or dedents out of the statement's block,
+    /// then the brackets were left open and the statement ends here. A line closing the bracket
+    /// continues.
+    fn ends_brackets(&self, levels: usize) -> bool {
+        let mut offset = 1;
+        let mut width = 0;
         loop {
-            let mut current = self.current();
+            match self.peek(offset) {
+                ' ' => width += 1,
+                '\t' => width += 4,
+                _ => break
+            }
+            offset += 1;
+        }
 
-            if current == ' ' {
-                space_count += 1;
-                if space_count == 4 {
-                    let mut end = self.pos.clone();
-                    end.advance(' ');
-                    tokens.push(Positioned::new(Token::Tab, space_start_pos.clone(), end));
-                    space_count = 0;
-                } else if space_count == 1 {
-                    space_start_pos = self.pos.clone();
-                }
-                self.advance();
-                continue;
-            } else {
-                space_count = 0;
+        let first = self.peek(offset);
+        if matches!(first, '\n' | '\r' | '#' | '\0' | ')') {
+            return false;
+        }
+        if levels > 0 && width <= (levels - 1) * 4 {
+            return true;
+        }
+
+        let mut word = String::new();
+        while self.peek(offset).is_xid_continue() {
+            word.push(self.peek(offset));
+            offset += 1;
+        }
+        Keyword::from_string(&word).is_some_and(|keyword| keyword.starts_statement())
+    }
+
+    pub fn tokenize(&mut self) -> Result<Vec<Positioned<Token>>, LexerError> {
+        let mut tokens: Vec<Positioned<Token>> = Vec::new();
+        let mut levels = 0;
+        // Open brackets, only `(` for now
+        let mut brackets = 0usize;
+        let mut line_start = true;
+
+        loop {
+            if line_start {
+                self.make_indentation(&mut tokens, &mut levels)?;
             }
-            
+            line_start = false;
+
+            let mut current = self.current();
             match current {
-                'a'..='z' | 'A'..='Z' => {
+                _ if current.is_xid_start() => {
                     tokens.push(self.make_identifier()?);
                     continue;
                 }
@@ -147,14 +246,20 @@ impl Lexer {
                     continue;
                 }
                 '"' => tokens.push(self.make_string()?),
-                '(' => tokens.push(self.make_single(Token::LeftParenthesis)),
-                ')' => tokens.push(self.make_single(Token::RightParenthesis)),
+                '(' => {
+                    brackets += 1;
+                    tokens.push(self.make_single(Token::LeftParenthesis));
+                }
+                ')' => {
+                    brackets = brackets.saturating_sub(1);
+                    tokens.push(self.make_single(Token::RightParenthesis));
+                }
                 ',' => tokens.push(self.make_single(Token::Comma)),
                 ':' => tokens.push(self.make_single(Token::Colon)),
                 '$' => {
                     let start = self.pos.clone();
                     self.advance();
-                    if self.current().is_alphabetic() {
+                    if self.current().is_xid_start() {
                         let expr = self.make_identifier()?;
                         let Token::Identifier(id) = expr.data else {
                             unreachable!()
@@ -162,7 +267,7 @@ impl Lexer {
                         tokens.push(Positioned::new(Token::Label(id), start, expr.end));
                         continue;
                     } else {
-                        return Err(LexerError::UnexpectedChar(self.make_single(self.current()), Some("letter".to_string())));
+                        return Err(LexerError::UnexpectedChar(self.make_single(self.current()), Some("letter")));
                     }
                 }
                 '=' => {
@@ -195,6 +300,7 @@ impl Lexer {
                             end.advance('=');
                             tokens.push(Positioned::new(Token::LeftAngleEqual, start, end));
                         }
+                        '<' => tokens.push(self.make_double(Token::DoubleLeftAngle, Token::DoubleLeftAngleEqual)),
                         _ => tokens.push(self.make_single(Token::LeftAngle))
                     }
                 }
@@ -208,6 +314,7 @@ impl Lexer {
                             end.advance('=');
                             tokens.push(Positioned::new(Token::RightAngleEqual, start, end));
                         }
+                        '>' => tokens.push(self.make_double(Token::DoubleRightAngle, Token::DoubleRightAngleEqual)),
                         _ => tokens.push(self.make_single(Token::RightAngle))
                     }
                 }
@@ -221,34 +328,57 @@ impl Lexer {
                             end.advance('=');
                             tokens.push(Positioned::new(Token::ExclamationMarkEqual, start, end));
                         }
-                        _ => return Err(LexerError::UnexpectedChar(self.make_single(current), Some("=".to_string())))
+                        _ => return Err(LexerError::UnexpectedChar(self.make_single(current), Some("=")))
+                    }
+                }
+                '+' => tokens.push(self.make_with_equal(Token::Plus, Token::PlusEqual)),
+                '-' => tokens.push(self.make_with_equal(Token::Dash, Token::DashEqual)),
+                '*' => tokens.push(self.make_with_equal(Token::Star, Token::StarEqual)),
+                '/' => tokens.push(self.make_with_equal(Token::Slash, Token::SlashEqual)),
+                '%' => tokens.push(self.make_with_equal(Token::Percent, Token::PercentEqual)),
+                '&' => tokens.push(self.make_with_equal(Token::Ampersand, Token::AmpersandEqual)),
+                '|' => tokens.push(self.make_with_equal(Token::Pipe, Token::PipeEqual)),
+                '^' => tokens.push(self.make_with_equal(Token::Caret, Token::CaretEqual)),
+                '~' => tokens.push(self.make_single(Token::Tilde)),
+                '.' => {
+                    if self.peek(1) == '.' && self.peek(2) == '.' {
+                        let start = self.pos.clone();
+                        self.advance_x(2);
+                        let mut end = self.pos.clone();
+                        end.advance('.');
+                        tokens.push(Positioned::new(Token::Ellipsis, start, end));
+                    } else {
+                        tokens.push(self.make_single(Token::Dot));
                     }
                 }
-                '+' => tokens.push(self.make_single(Token::Plus)),
-                '-' => tokens.push(self.make_single(Token::Dash)),
-                '*' => tokens.push(self.make_single(Token::Star)),
-                '/' => tokens.push(self.make_single(Token::Slash)),
-                '.' => tokens.push(self.make_single(Token::Dot)),
                 '\n' => {
-                    let start = self.pos.clone();
-                    let mut end = self.pos.clone();
-                    end.advance(' ');
-                    tokens.push(Positioned::new(Token::NewLine, start, end));
+                    let ended = brackets > 0 && self.ends_brackets(levels);
+                    if ended {
+                        brackets = 0;
+                    }
+                    // Lines inside brackets or after a binary operator continue the statement, their
+                    // indentation is ignored
+                    let continued = !ended && (brackets > 0 || tokens.last().is_some_and(|token| token.data.continues_line()));
+                    if !continued {
+                        // Blank lines don't end a statement
+                        if tokens.last().is_some_and(|token| token.data != Token::NewLine) {
+                            let start = self.pos.clone();
+                            let mut end = self.pos.clone();
+                            end.advance(' ');
+                            tokens.push(Positioned::new(Token::NewLine, start, end));
+                        }
+                        line_start = true;
+                    }
                 },
-                '\t' => tokens.push(self.make_single(Token::Tab)),
                 '#' => {
                     while current != '\n' && current != '\0' {
                         self.advance();
                         current = self.current();
                     }
-                    let start = self.pos.clone();
-                    let mut end = self.pos.clone();
-                    end.advance(' ');
-                    tokens.push(Positioned::new(Token::NewLine, start, end));
                     continue;
                 }
                 '\0' => break,
-                ' ' | '\r' => {
+                ' ' | '\t' | '\r' => {
                     // Ignore
                 }
                 _ => return Err(LexerError::UnexpectedChar(self.make_single(current), None))
@@ -257,7 +387,118 @@ impl Lexer {
             self.advance();
         }
 
+        if brackets == 0 {
+            if tokens.last().is_some_and(|token| token.data != Token::NewLine) {
+                tokens.push(self.make_single(Token::NewLine));
+            }
+            for _ in 0..levels {
+                tokens.push(self.make_single(Token::Dedent));
+            }
+        }
+
         Ok(tokens)
     }
 
-}
\ No newline at end of file
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Tests                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(src: &str) -> Vec<Token> {
+        Lexer::new(src).tokenize().unwrap_or_else(|err| panic!("Failed to tokenize, {}", err.kind())).into_iter().map(|token| token.data).collect()
+    }
+
+    fn error(src: &str) -> &'static str {
+        match Lexer::new(src).tokenize() {
+            Ok(_) => panic!("Tokenized but should fail"),
+            Err(err) => err.kind()
+        }
+    }
+
+    fn id(name: &str) -> Token {
+        Token::Identifier(name.to_string())
+    }
+
+    #[test]
+    fn indent_and_dedent_at_line_starts() {
+        assert_eq!(tokens("a\n    b\n\tc\n\n    # comment\nd"), vec![
+            id("a"), Token::NewLine,
+            Token::Indent, id("b"), Token::NewLine,
+            id("c"), Token::NewLine,
+            Token::Dedent, id("d"), Token::NewLine
+        ]);
+        assert_eq!(tokens("a\n    b\n        c\n"), vec![
+            id("a"), Token::NewLine,
+            Token::Indent, id("b"), Token::NewLine,
+            Token::Indent, id("c"), Token::NewLine,
+            Token::Dedent, Token::Dedent
+        ]);
+    }
+
+    #[test]
+    fn continuation_lines_inside_parentheses() {
+        assert_eq!(tokens("f(a,\n  b\n)\nc"), vec![
+            id("f"), Token::LeftParenthesis, id("a"), Token::Comma, id("b"), Token::RightParenthesis, Token::NewLine,
+            id("c"), Token::NewLine
+        ]);
+    }
+
+    #[test]
+    fn unclosed_parentheses_end_at_a_statement_keyword_or_dedent() {
+        assert_eq!(tokens("f(a,\nvar g\n"), vec![
+            id("f"), Token::LeftParenthesis, id("a"), Token::Comma, Token::NewLine,
+            Token::Keyword(Keyword::Var), id("g"), Token::NewLine
+        ]);
+        assert_eq!(tokens("a\n    f(b\nc\n"), vec![
+            id("a"), Token::NewLine,
+            Token::Indent, id("f"), Token::LeftParenthesis, id("b"), Token::NewLine,
+            Token::Dedent, id("c"), Token::NewLine
+        ]);
+        assert_eq!(tokens("a\n    f(b\n        return c\n"), vec![
+            id("a"), Token::NewLine,
+            Token::Indent, id("f"), Token::LeftParenthesis, id("b"), Token::NewLine,
+            Token::Indent, Token::Keyword(Keyword::Return), id("c"), Token::NewLine,
+            Token::Dedent, Token::Dedent
+        ]);
+    }
+
+    #[test]
+    fn shallow_continuation_lines_inside_parentheses() {
+        assert_eq!(tokens("a\n    f(b,\n  c)\n    d\n"), vec![
+            id("a"), Token::NewLine,
+            Token::Indent, id("f"), Token::LeftParenthesis, id("b"), Token::Comma, id("c"), Token::RightParenthesis, Token::NewLine,
+            id("d"), Token::NewLine,
+            Token::Dedent
+        ]);
+        assert_eq!(tokens("a\n    f(\n    b, c)\n"), vec![
+            id("a"), Token::NewLine,
+            Token::Indent, id("f"), Token::LeftParenthesis, id("b"), Token::Comma, id("c"), Token::RightParenthesis, Token::NewLine,
+            Token::Dedent
+        ]);
+        assert_eq!(tokens("f(a,\nb)\n"), vec![
+            id("f"), Token::LeftParenthesis, id("a"), Token::Comma, id("b"), Token::RightParenthesis, Token::NewLine
+        ]);
+    }
+
+    #[test]
+    fn continuation_lines_after_binary_operators() {
+        assert_eq!(tokens("a = b +\n        c\n    d"), vec![
+            id("a"), Token::Equal, id("b"), Token::Plus, id("c"), Token::NewLine,
+            Token::Indent, id("d"), Token::NewLine,
+            Token::Dedent
+        ]);
+    }
+
+    #[test]
+    fn inconsistent_indentation() {
+        assert_eq!(error("a\n  b\n"), "UnalignedIndentation");
+        assert_eq!(error("a\n\t    b\n"), "MixedIndentation");
+        assert_eq!(error("a\n        b\n"), "TooDeepIndentation");
+    }
+
+}
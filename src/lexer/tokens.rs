@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                              Token                                             //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
@@ -25,15 +27,108 @@ pub enum Token {
     Star,
     Slash,
     Dot,
+    Ellipsis,
     LeftAngle,
     LeftAngleEqual,
     RightAngle,
     RightAngleEqual,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    DoubleLeftAngle,
+    DoubleRightAngle,
+    PlusEqual,
+    DashEqual,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    AmpersandEqual,
+    PipeEqual,
+    CaretEqual,
+    DoubleLeftAngleEqual,
+    DoubleRightAngleEqual,
     // Formatting
-    Tab,
+    Indent,
+    Dedent,
     NewLine
 }
 
+impl Token {
+
+    /// Binary operators and commas can't end a statement, the expression continues on the next line.
+    pub fn continues_line(&self) -> bool {
+        matches!(self,
+            Token::Comma |
+            Token::Dot |
+            Token::Plus | Token::Dash | Token::Star | Token::Slash | Token::Percent |
+            Token::Ampersand | Token::Pipe | Token::Caret | Token::DoubleLeftAngle | Token::DoubleRightAngle |
+            Token::DoubleEqual | Token::ExclamationMarkEqual |
+            Token::LeftAngle | Token::LeftAngleEqual | Token::RightAngle | Token::RightAngleEqual |
+            Token::Keyword(Keyword::And) | Token::Keyword(Keyword::Or) | Token::Keyword(Keyword::Xor) |
+            Token::Equal | Token::PlusEqual | Token::DashEqual | Token::StarEqual | Token::SlashEqual | Token::PercentEqual |
+            Token::AmpersandEqual | Token::PipeEqual | Token::CaretEqual | Token::DoubleLeftAngleEqual | Token::DoubleRightAngleEqual
+        )
+    }
+
+}
+
+/// Renders the token as written in the source.
+impl Display for Token {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Keyword(keyword) => write!(f, "{}", keyword),
+            Token::Identifier(id) => write!(f, "{}", id),
+            Token::String(str) => write!(f, "\"{}\"", str),
+            Token::Integer(num) |
+            Token::Decimal(num) => write!(f, "{}", num),
+            Token::Bool(b) => write!(f, "{}", b),
+            Token::Label(label) => write!(f, "${}", label),
+            Token::LeftParenthesis => write!(f, "("),
+            Token::RightParenthesis => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+            Token::Colon => write!(f, ":"),
+            Token::RightDoubleArrow => write!(f, "=>"),
+            Token::Equal => write!(f, "="),
+            Token::DoubleEqual => write!(f, "=="),
+            Token::ExclamationMarkEqual => write!(f, "!="),
+            Token::Plus => write!(f, "+"),
+            Token::Dash => write!(f, "-"),
+            Token::Star => write!(f, "*"),
+            Token::Slash => write!(f, "/"),
+            Token::Dot => write!(f, "."),
+            Token::Ellipsis => write!(f, "..."),
+            Token::LeftAngle => write!(f, "<"),
+            Token::LeftAngleEqual => write!(f, "<="),
+            Token::RightAngle => write!(f, ">"),
+            Token::RightAngleEqual => write!(f, ">="),
+            Token::Percent => write!(f, "%"),
+            Token::Ampersand => write!(f, "&"),
+            Token::Pipe => write!(f, "|"),
+            Token::Caret => write!(f, "^"),
+            Token::Tilde => write!(f, "~"),
+            Token::DoubleLeftAngle => write!(f, "<<"),
+            Token::DoubleRightAngle => write!(f, ">>"),
+            Token::PlusEqual => write!(f, "+="),
+            Token::DashEqual => write!(f, "-="),
+            Token::StarEqual => write!(f, "*="),
+            Token::SlashEqual => write!(f, "/="),
+            Token::PercentEqual => write!(f, "%="),
+            Token::AmpersandEqual => write!(f, "&="),
+            Token::PipeEqual => write!(f, "|="),
+            Token::CaretEqual => write!(f, "^="),
+            Token::DoubleLeftAngleEqual => write!(f, "<<="),
+            Token::DoubleRightAngleEqual => write!(f, ">>="),
+            Token::Indent => write!(f, "indent"),
+            Token::Dedent => write!(f, "dedent"),
+            Token::NewLine => write!(f, "new line"),
+        }
+    }
+
+}
+
 
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -69,7 +164,8 @@ pub enum Keyword {
     Match,
     Break,
     Continue,
-    Intf
+    Intf,
+    Struct
 }
 
 impl Keyword {
@@ -104,8 +200,56 @@ impl Keyword {
             "break" => Some(Keyword::Break),
             "continue" => Some(Keyword::Continue),
             "intf" => Some(Keyword::Intf),
+            "struct" => Some(Keyword::Struct),
             _ => None
         }
     }
 
+    /// Keywords that can only start a statement or a definition, never continue an expression.
+    pub fn starts_statement(&self) -> bool {
+        !matches!(self, Keyword::New | Keyword::And | Keyword::Or | Keyword::Xor | Keyword::Not | Keyword::Then | Keyword::Do)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Keyword::Use => "use",
+            Keyword::Fn => "fn",
+            Keyword::Extern => "extern",
+            Keyword::Var => "var",
+            Keyword::Const => "const",
+            Keyword::Return => "return",
+            Keyword::Class => "class",
+            Keyword::Space => "space",
+            Keyword::New => "new",
+            Keyword::Pub => "pub",
+            Keyword::Prot => "prot",
+            Keyword::Lock => "lock",
+            Keyword::Guard => "guard",
+            Keyword::And => "and",
+            Keyword::Or => "or",
+            Keyword::Xor => "xor",
+            Keyword::Not => "not",
+            Keyword::If => "if",
+            Keyword::Elif => "elif",
+            Keyword::Else => "else",
+            Keyword::Then => "then",
+            Keyword::End => "end",
+            Keyword::While => "while",
+            Keyword::Do => "do",
+            Keyword::Match => "match",
+            Keyword::Break => "break",
+            Keyword::Continue => "continue",
+            Keyword::Intf => "intf",
+            Keyword::Struct => "struct",
+        }
+    }
+
+}
+
+impl Display for Keyword {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+
 }
\ No newline at end of file
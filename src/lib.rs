@@ -0,0 +1,11 @@
+pub mod util;
+pub mod lexer;
+pub mod parser;
+pub mod ir;
+pub mod symbolizer;
+pub mod checker;
+pub mod post_processor;
+pub mod generator;
+pub mod bindgen;
+pub mod evaluator;
+pub mod session;
@@ -1,17 +1,8 @@
-use std::process::exit;
+use std::process::{exit, Command};
 
 use colored::Colorize;
 
-use crate::{util::{source_file::SourceFile, position::Positioned, reference::MutRef}, lexer::{tokens::Token, lexer::Lexer}, parser::{parser::Parser, node::Node}, ir::{output::IROutput, ir::IRGenerator}, symbolizer::{symbolizer::Symbolizer, scope::{Scope}}, checker::checker::Checker, generator::{generator::Generator, project::Project}, post_processor::post_processor::PostProcessor};
-
-pub mod util;
-pub mod lexer;
-pub mod parser;
-pub mod ir;
-pub mod symbolizer;
-pub mod checker;
-pub mod post_processor;
-pub mod generator;
+use taly_lang::{util::source_file::SourceFile, generator::{project::Project, library::{Library, LibraryKind}}, bindgen::{c_lexer::CLexer, bindgen::Bindgen}, session::{session::Session, error::CompileError}};
 
 fn read_file(path: &str) -> SourceFile {
     match std::fs::read_to_string(path) {
@@ -23,85 +14,140 @@ fn read_file(path: &str) -> SourceFile {
     }
 }
 
-fn tokenize(src: &SourceFile) -> Vec<Positioned<Token>> {
-    let mut lexer = Lexer::new(&src.src);
-    match lexer.tokenize() {
-        Ok(tokens) => tokens,
-        Err(err) => {
-            err.print_error(src);
-            exit(2);
-        },
-    }
+/// Prints the error and exits with the code of the failing stage.
+fn fail(session: &Session, err: CompileError) -> ! {
+    err.print_error(session.source());
+    exit(match err {
+        CompileError::Lexer(_) => 2,
+        CompileError::Parser(_) | CompileError::ModuleNotFound(_) | CompileError::Module(..) => 3,
+        _ => 4
+    });
 }
 
-fn parse(src: &SourceFile, tokens: Vec<Positioned<Token>>) -> Vec<Positioned<Node>> {
-    let mut parser = Parser::new(tokens);
-    match parser.parse() {
-        Ok(ast) => ast,
-        Err(err) => {
-            err.print_error(src);
-            exit(3);
-        },
+fn write_files(project: &Project, dir: &str) {
+    std::fs::create_dir_all(dir).unwrap();
+    for file in project.files.iter() {
+        std::fs::write(format!("{}/{}.h", dir, file.name), file.header.clone()).unwrap();
+        std::fs::write(format!("{}/{}.c", dir, file.name), file.src.clone()).unwrap();
+        if let Some(source_map) = &file.source_map {
+            std::fs::write(format!("{}/{}.c.map", dir, file.name), source_map.to_json()).unwrap();
+        }
     }
 }
 
-fn ir_generate(src: &SourceFile, ast: Vec<Positioned<Node>>, root: MutRef<Scope>) -> IROutput {
-    let mut ir = IRGenerator::new(ast, root);
-    match ir.generate() {
-        Ok(output) => output,
-        Err(err) => {
-            err.print_error(src);
-            exit(4);
-        },
-    }
+fn build_project(project: Project) {
+    write_files(&project, "./out/project");
 }
 
-fn symbolize(src: &SourceFile, ast: Vec<Positioned<Node>>, root: MutRef<Scope>) {
-    let mut symbolizer = Symbolizer::new(ast);
-    match symbolizer.symbolize(root) {
-        Err(err) => {
-            err.print_error(src);
-            exit(4);
+fn run(command: &mut Command) {
+    match command.status() {
+        Ok(status) if status.success() => {},
+        Ok(status) => {
+            println!("Command {:?} failed with {}", command, status);
+            exit(5);
         },
-        _ => {}
+        Err(err) => {
+            println!("Failed to run {:?}, {}", command, err);
+            exit(5);
+        }
     }
 }
 
-fn check(src: &SourceFile, ir_output: IROutput, scope: MutRef<Scope>) -> IROutput {
-    let mut checker = Checker::new(ir_output, scope);
-    match checker.check() {
-        Ok(output) => output,
-        Err(err) => {
-            err.print_error(src);
-            exit(4);
-        },
+fn build_library(project: Project, library: &Library) {
+    let dir = format!("./out/{}", library.name);
+    write_files(&project, &dir);
+
+    let cc = std::env::var("CC").unwrap_or("cc".to_string());
+    let object = format!("{}/{}.o", dir, library.name);
+    let output = format!("{}/{}", dir, library.file_name());
+    run(Command::new(&cc).args(["-c", "-fPIC", "-o", &object, &format!("{}/{}.c", dir, library.name)]));
+    match library.kind {
+        LibraryKind::Static => run(Command::new("ar").args(["rcs", &output, &object])),
+        LibraryKind::Shared => run(Command::new(&cc).args(["-shared", "-o", &output, &object])),
     }
+    println!("Library written to '{}'", output);
 }
 
-fn post_process(ir_output: IROutput) -> IROutput {
-    let mut post_processor = PostProcessor::new(ir_output);
-    post_processor.process()
+fn library_usage() -> ! {
+    println!("Usage: taly-lang lib <name> [--prefix <prefix>] [--shared]");
+    exit(1);
 }
 
-fn generate(ir_output: IROutput) -> Project {
-    let mut generator = Generator::new(ir_output);
-    generator.generate()
+/// `taly-lang lib <name> [--prefix <prefix>] [--shared]`
+fn parse_library(args: &[String]) -> Library {
+    let Some(name) = args.first() else {
+        library_usage()
+    };
+
+    let mut prefix = None;
+    let mut kind = LibraryKind::Static;
+    let mut args = args[1..].iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--prefix" => match args.next() {
+                Some(value) => prefix = Some(value.clone()),
+                None => library_usage()
+            },
+            "--shared" => kind = LibraryKind::Shared,
+            _ => library_usage()
+        }
+    }
+
+    Library::new(name.clone(), prefix, kind)
 }
 
-fn build_project(project: Project) {
-    std::fs::create_dir_all("./out/project").unwrap();
-    for file in project.files.iter() {
-        std::fs::write(format!("./out/project/{}.h", file.name), file.header.clone()).unwrap();
-        std::fs::write(format!("./out/project/{}.c", file.name), file.src.clone()).unwrap();
+/// `taly-lang bindgen <header.h> [output.taly]`
+fn bindgen(args: &[String]) {
+    let Some(path) = args.first() else {
+        println!("Usage: taly-lang bindgen <header.h> [output.taly]");
+        exit(1);
+    };
+    let src = read_file(path);
+
+    let mut lexer = CLexer::new(&src.src);
+    let tokens = match lexer.tokenize() {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            err.print_error(&src);
+            exit(2);
+        },
+    };
+
+    let mut bindgen = Bindgen::new(tokens);
+    for err in bindgen.parse() {
+        err.print_warning(&src);
     }
+
+    // `use "c-name"` includes "name.h"
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let include = file_name.strip_suffix(".h").unwrap_or(file_name);
+    let output_path = args.get(1).cloned().unwrap_or(format!("res/{}.taly", include));
+    std::fs::write(&output_path, bindgen.write(include)).unwrap();
+    println!("Bindings written to '{}'", output_path);
 }
 
 fn main() {
-    let src = read_file("res/main.taly");
+    let args: Vec<String> = std::env::args().collect();
+    // `--line-directives` maps the generated C back to the Taly source for debuggers
+    let line_directives = args.iter().any(|arg| arg == "--line-directives");
+    let args: Vec<String> = args.into_iter().filter(|arg| arg != "--line-directives").collect();
+    if args.get(1).is_some_and(|x| x == "bindgen") {
+        bindgen(&args[2..]);
+        return;
+    }
+    let library = if args.get(1).is_some_and(|x| x == "lib") {
+        Some(parse_library(&args[2..]))
+    } else {
+        None
+    };
+
+    let mut session = Session::new(read_file("res/main.taly"));
+    session.set_library(library.clone());
+    session.set_line_directives(line_directives);
     
     // Lexer
     println!("{}", "\n/> Lexer".truecolor(81, 255, 255));
-    let tokens = tokenize(&src);
+    let tokens = session.tokenize().unwrap_or_else(|err| fail(&session, err));
 
     for token in tokens.iter() {
         println!("{:?}", token);
@@ -110,7 +156,7 @@ fn main() {
 
     // Parser
     println!("{}", "\n/> Parser".truecolor(81, 255, 255));
-    let ast = parse(&src, tokens);
+    let ast = session.parse(tokens).unwrap_or_else(|err| fail(&session, err));
 
     for node in ast.iter() {
         println!("{:#?}", node);
@@ -119,14 +165,13 @@ fn main() {
 
     // Symbolizer
     println!("{}", "\n/> Symbolizer".truecolor(81, 255, 255));
-    let mut root_scope = Scope::root();
-    symbolize(&src, ast.clone(), MutRef::new(&mut root_scope));
-    std::fs::write("./out/scope_out.json", format!("{:#?}", root_scope)).unwrap();
-    println!("{:#?}\n", root_scope);
+    let ast = session.symbolize(ast).unwrap_or_else(|err| fail(&session, err));
+    std::fs::write("./out/scope_out.json", format!("{:#?}", session.scope())).unwrap();
+    println!("{:#?}\n", session.scope());
 
     // IR Generator
     println!("{}", "\n/> IR Generator".truecolor(81, 255, 255));
-    let ir_output = ir_generate(&src, ast, MutRef::new(&mut root_scope));
+    let ir_output = session.ir_generate(ast).unwrap_or_else(|err| fail(&session, err));
 
     for include in ir_output.includes.iter() {
         println!("{:?}", include);
@@ -139,7 +184,7 @@ fn main() {
     
     // Checker
     println!("{}", "\n/> Checker".truecolor(81, 255, 255));
-    let checker_output = check(&src, ir_output, MutRef::new(&mut root_scope));
+    let checker_output = session.check(ir_output).unwrap_or_else(|err| fail(&session, err));
 
     for include in checker_output.includes.iter() {
         println!("{:?}", include);
@@ -150,9 +195,18 @@ fn main() {
     }
     println!("\n");
 
+    // Const Evaluator
+    println!("{}", "\n/> Const Evaluator".truecolor(81, 255, 255));
+    let evaluator_output = session.evaluate(checker_output).unwrap_or_else(|err| fail(&session, err));
+
+    for node in evaluator_output.ast.iter() {
+        println!("{:#?}", node);
+    }
+    println!("\n");
+
     // Post processor
     println!("{}", "\n/> Post Processor".truecolor(81, 255, 255));
-    let post_processor_output = post_process(checker_output);
+    let post_processor_output = session.post_process(evaluator_output);
 
     for include in post_processor_output.includes.iter() {
         println!("{:?}", include);
@@ -165,7 +219,7 @@ fn main() {
 
     // Generator
     println!("{}", "\n/> Generator".truecolor(81, 255, 255));
-    let project = generate(post_processor_output);
+    let project = session.generate(post_processor_output).unwrap_or_else(|err| fail(&session, err));
 
     for file in project.files.iter() {
         println!("{}.h", file.name);
@@ -174,5 +228,8 @@ fn main() {
         println!("{}\n", file.src);
     }
 
-    build_project(project);
+    match library {
+        Some(library) => build_library(project, &library),
+        None => build_project(project)
+    }
 }
@@ -1,54 +1,89 @@
-use crate::{util::{position::Positioned, source_file::SourceFile, error::{ErrorFormat, ErrorType}}, lexer::tokens::Token, parser::node::Node};
+use crate::{util::{position::Positioned, source_file::SourceFile, error::{ErrorFormat, ErrorType}, suggestion}, lexer::tokens::Token, parser::node::Node};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                           Parser Error                                         //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
 
 pub enum ParserError {
-    UnexpectedToken(Positioned<Token>, Option<String>),
-    UnexpectedEOF(Option<String>),
+    UnexpectedToken(Box<Positioned<Token>>, Option<String>),
+    /// Points at the end of the file
+    UnexpectedEOF(Positioned<()>, Option<String>),
     UninitializedConstant(Positioned<String>),
-    UnexpectedNode(Positioned<Node>, Option<String>),
+    UnexpectedNode(Box<Positioned<Node>>, Option<String>),
+    InitializedExternal(Positioned<String>),
+    UntypedExternal(Positioned<String>),
+    /// Token found, token missing and where it is missing: "after the function signature"
+    MissingToken(Box<Positioned<Token>>, Token, String),
 }
 
 impl ParserError {
 
+    /// Name of the variant, used by the diagnostic tests.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ParserError::UnexpectedToken(..) => "UnexpectedToken",
+            ParserError::UnexpectedEOF(..) => "UnexpectedEOF",
+            ParserError::UninitializedConstant(..) => "UninitializedConstant",
+            ParserError::UnexpectedNode(..) => "UnexpectedNode",
+            ParserError::InitializedExternal(..) => "InitializedExternal",
+            ParserError::UntypedExternal(..) => "UntypedExternal",
+            ParserError::MissingToken(..) => "MissingToken",
+        }
+    }
+
+    /// Span of the main message of the error.
+    pub fn span(&self) -> Option<Positioned<()>> {
+        match self {
+            ParserError::UnexpectedToken(found, _) => Some(found.convert(())),
+            ParserError::UnexpectedEOF(end, _) => Some(end.clone()),
+            ParserError::UninitializedConstant(var) => Some(var.convert(())),
+            ParserError::UnexpectedNode(found, _) => Some(found.convert(())),
+            ParserError::InitializedExternal(var) => Some(var.convert(())),
+            ParserError::UntypedExternal(var) => Some(var.convert(())),
+            ParserError::MissingToken(found, _, _) => Some(found.convert(())),
+        }
+    }
+
+    fn unexpected(found: String, expected: &Option<String>) -> String {
+        match expected {
+            Some(expected) => format!("Unexpected {}, should be '{}'!", found, expected),
+            None => format!("Unexpected {}!", found)
+        }
+    }
+
     pub fn print_error(&self, src: &SourceFile) {
+        let mut format = ErrorFormat::new(ErrorType::Error).set_step("Parser".to_string());
         match self {
             ParserError::UnexpectedToken(found, expected) => {
-                let mut buf = format!("Unexpected token '{:?}'", found.data);
-                if let Some(expected) = expected {
-                    buf.push_str(format!(", should be '{}'!", expected).as_str());
-                } else {
-                    buf.push('!');
-                }
-                ErrorFormat::new(ErrorType::Error).add_message(buf, Some(found.convert(()))).set_step("Parser".to_string()).print(src);
+                format = format.add_message(Self::unexpected(format!("token '{}'", found.data), expected), Some(found.convert(())));
             },
-            ParserError::UnexpectedEOF(expected) => {
-                let mut buf = "Unexpected EOF".to_string();
-                if let Some(expected) = expected {
-                    buf.push_str(format!(", should be '{}'!", expected).as_str());
-                } else {
-                    buf.push('!');
-                }
-                ErrorFormat::new(ErrorType::Error).add_message(buf, None).set_step("parser".to_string()).print(src);
+            ParserError::UnexpectedEOF(end, expected) => {
+                format = format.add_message(Self::unexpected("end of file".to_string(), expected), Some(end.clone()));
             },
             ParserError::UninitializedConstant(var) => {
-                ErrorFormat::new(ErrorType::Error)
-                    .add_message(format!("Constant '{}' is not initialized", var.data), Some(var.convert(())))
-                    .set_step("Parser".to_string())
-                    .print(src)
+                format = format.add_message(format!("Constant '{}' is not initialized", var.data), Some(var.convert(())));
             }
             ParserError::UnexpectedNode(found, expected) => {
-                let mut buf = format!("Unexpected node '{}'", found.data.short_name());
-                if let Some(expected) = expected {
-                    buf.push_str(format!(", should be '{}'!", expected).as_str());
-                } else {
-                    buf.push('!'); 
+                format = format.add_message(Self::unexpected(format!("node '{}'", found.data.short_name()), expected), Some(found.convert(())));
+            },
+            ParserError::InitializedExternal(var) => {
+                format = format.add_message(format!("External '{}' cannot be initialized, its value is defined in C", var.data), Some(var.convert(())));
+            },
+            ParserError::UntypedExternal(var) => {
+                format = format.add_message(format!("External '{}' needs an explicit type", var.data), Some(var.convert(())));
+            },
+            ParserError::MissingToken(found, missing, context) => {
+                format = format.add_message(Self::unexpected(format!("token '{}'", found.data), &Some(missing.to_string())), Some(found.convert(())))
+                    .add_hint(format!("missing '{}' {}", missing, context));
+                // `the` for `then`
+                if let (Token::Identifier(id), Token::Keyword(keyword)) = (&found.data, missing) {
+                    if suggestion::closest(id, [keyword.as_str()]).is_some() {
+                        format = format.add_hint(format!("did you mean '{}'?", keyword));
+                    }
                 }
-                ErrorFormat::new(ErrorType::Error).add_message(buf, Some(found.convert(()))).set_step("Parser".to_string()).print(src);
             },
         }
+        format.print(src);
     }
 
 }
\ No newline at end of file
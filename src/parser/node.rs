@@ -1,4 +1,4 @@
-use crate::util::position::Positioned;
+use crate::util::{position::Positioned, id::{ScopeId, SymbolId}};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                              Node                                              //
@@ -9,16 +9,22 @@ pub enum Node {
     Value(ValueNode),
     FunctionDefinition {
         name: Positioned<String>,
-        external: bool,
+        external: Option<External>,
         constructor: bool,
         parameters: Vec<FunctionDefinitionParameter>,
         return_type: Option<Positioned<DataType>>,
         body: Vec<Positioned<Node>>,
-        access: Option<Positioned<AccessModifier>>
+        access: Option<Positioned<AccessModifier>>,
+        /// Module imported with `use` the definition comes from, none for the compiled source
+        module: Option<String>,
+        /// Symbol of the definition, set by the symbolizer
+        scope: Option<SymbolId>
     },
     FunctionCall {
         name: Positioned<String>,
-        parameters: Vec<Positioned<Node>>
+        parameters: Vec<Positioned<Node>>,
+        /// Symbol of the function called, set by the checker
+        scope: Option<SymbolId>
     },
     Use(Positioned<String>),
     VariableDefinition {
@@ -26,9 +32,18 @@ pub enum Node {
         name: Positioned<String>,
         data_type: Option<Positioned<DataType>>,
         value: Option<Box<Positioned<Node>>>,
-        access: Option<Positioned<AccessModifier>>
+        access: Option<Positioned<AccessModifier>>,
+        external: Option<External>,
+        /// Module imported with `use` the definition comes from, none for the compiled source
+        module: Option<String>,
+        scope: Option<SymbolId>
+    },
+    VariableCall {
+        name: String,
+        /// Symbol of the variable, class or space named, set by the checker. On the right of an
+        /// access it is the field selected
+        scope: Option<SymbolId>
     },
-    VariableCall(String),
     BinaryOperation {
         lhs: Box<Positioned<Node>>,
         operator: Positioned<Operator>,
@@ -43,27 +58,38 @@ pub enum Node {
         name: Positioned<String>,
         body: Vec<Positioned<Node>>,
         access: Option<Positioned<AccessModifier>>,
-        extensions: Vec<Positioned<String>>
+        extensions: Vec<Positioned<String>>,
+        external: Option<External>,
+        /// Module imported with `use` the definition comes from, none for the compiled source
+        module: Option<String>,
+        scope: Option<SymbolId>
     },
     SpaceDefinition {
         name: Positioned<String>,
         body: Vec<Positioned<Node>>,
-        access: Option<Positioned<AccessModifier>>
+        access: Option<Positioned<AccessModifier>>,
+        /// Module imported with `use` the definition comes from, none for the compiled source
+        module: Option<String>,
+        scope: Option<SymbolId>
     },
     IfStatement {
         condition: Box<Positioned<Node>>,
         body: Vec<Positioned<Node>>,
         elif_branches: Vec<ElifBranch>,
-        else_body: Vec<Positioned<Node>>
+        else_body: Vec<Positioned<Node>>,
+        /// One branch scope per body, the else included when not empty
+        scopes: Vec<ScopeId>
     },
     WhileLoop {
         condition: Box<Positioned<Node>>,
-        body: Vec<Positioned<Node>>
+        body: Vec<Positioned<Node>>,
+        scope: Option<ScopeId>
     },
     MatchStatement {
         expr: Box<Positioned<Node>>,
         branches: Vec<MatchBranch>,
-        else_body: Vec<Positioned<Node>>
+        else_body: Vec<Positioned<Node>>,
+        scopes: Vec<ScopeId>
     },
     Break(Option<Positioned<String>>),
     Continue(Option<Positioned<String>>),
@@ -74,8 +100,18 @@ pub enum Node {
     InterfaceDefinition {
         name: Positioned<String>,
         body: Vec<Positioned<Node>>,
-        access: Option<Positioned<AccessModifier>>
+        access: Option<Positioned<AccessModifier>>,
+        /// Module imported with `use` the definition comes from, none for the compiled source
+        module: Option<String>,
+        scope: Option<SymbolId>
+    },
+    Lambda {
+        parameters: Vec<FunctionDefinitionParameter>,
+        return_type: Option<Positioned<DataType>>,
+        body: Vec<Positioned<Node>>
     },
+    /// Statement that failed to parse, its error is reported by the parser
+    Error,
     // Compiler Specific Annotation
     _Unchecked(Box<Positioned<Node>>),
     _Optional(Box<Positioned<Node>>),
@@ -85,6 +121,15 @@ pub enum Node {
     },
     _Implementation(Box<Positioned<Node>>),
     _Generated(Box<Positioned<Node>>),
+    _Lambda {
+        function: Box<Positioned<Node>>,
+        captures: Vec<LambdaCapture>
+    },
+    _ClosureCall {
+        closure: Box<Positioned<Node>>,
+        data_type: Positioned<DataType>,
+        parameters: Vec<Positioned<Node>>
+    },
 }
 
 impl Node {
@@ -106,56 +151,70 @@ impl Node {
             Node::FunctionCall { name, .. } => format!("FunctionCall({})", name.data),
             Node::Use(path) => format!("Use({})", path.data),
             Node::VariableDefinition { name, .. } => format!("Variable({})", name.data),
-            Node::VariableCall(name) => format!("VariableCall({})", name),
+            Node::VariableCall { name, .. } => format!("VariableCall({})", name),
             Node::BinaryOperation { operator, .. } => match operator.data {
-                Operator::Add => format!("BinaryOP(Addition)"),
-                Operator::Subtract => format!("BinaryOP(Subtraction)"),
-                Operator::Multiply => format!("BinaryOP(Multiplication)"),
-                Operator::Divide => format!("BinaryOP(Division)"),
-                Operator::Assign => format!("BinaryOP(Assignment)"),
-                Operator::Access => format!("BinaryOP(Access)"),
-                Operator::BooleanAnd => format!("BinaryOP(BooleanAnd)"),
-                Operator::BooleanOr => format!("BinaryOP(BooleanOr)"),
-                Operator::BooleanXor => format!("BinaryOP(BooleanXor)"),
-                Operator::Equal => format!("BinaryOP(Equal)"),
-                Operator::NotEqual => format!("BinaryOP(NotEqual)"),
-                Operator::Greater => format!("BinaryOP(Greater)"),
-                Operator::GreaterOrEqual => format!("BinaryOP(GreaterOREqual)"),
-                Operator::Less => format!("BinaryOP(Less)"),
-                Operator::LessOrEqual => format!("BinaryOP(LessOREqual)"),
+                Operator::Add => "BinaryOP(Addition)".to_string(),
+                Operator::Subtract => "BinaryOP(Subtraction)".to_string(),
+                Operator::Multiply => "BinaryOP(Multiplication)".to_string(),
+                Operator::Divide => "BinaryOP(Division)".to_string(),
+                Operator::Assign => "BinaryOP(Assignment)".to_string(),
+                Operator::Access => "BinaryOP(Access)".to_string(),
+                Operator::BooleanAnd => "BinaryOP(BooleanAnd)".to_string(),
+                Operator::BooleanOr => "BinaryOP(BooleanOr)".to_string(),
+                Operator::BooleanXor => "BinaryOP(BooleanXor)".to_string(),
+                Operator::Equal => "BinaryOP(Equal)".to_string(),
+                Operator::NotEqual => "BinaryOP(NotEqual)".to_string(),
+                Operator::Greater => "BinaryOP(Greater)".to_string(),
+                Operator::GreaterOrEqual => "BinaryOP(GreaterOREqual)".to_string(),
+                Operator::Less => "BinaryOP(Less)".to_string(),
+                Operator::LessOrEqual => "BinaryOP(LessOREqual)".to_string(),
+                Operator::Modulo => "BinaryOP(Modulo)".to_string(),
+                Operator::BitwiseAnd => "BinaryOP(BitwiseAnd)".to_string(),
+                Operator::BitwiseOr => "BinaryOP(BitwiseOr)".to_string(),
+                Operator::BitwiseXor => "BinaryOP(BitwiseXor)".to_string(),
+                Operator::ShiftLeft => "BinaryOP(ShiftLeft)".to_string(),
+                Operator::ShiftRight => "BinaryOP(ShiftRight)".to_string(),
+                Operator::AddAssign => "BinaryOP(AdditionAssignment)".to_string(),
+                Operator::SubtractAssign => "BinaryOP(SubtractionAssignment)".to_string(),
+                Operator::MultiplyAssign => "BinaryOP(MultiplicationAssignment)".to_string(),
+                Operator::DivideAssign => "BinaryOP(DivisionAssignment)".to_string(),
+                Operator::ModuloAssign => "BinaryOP(ModuloAssignment)".to_string(),
+                Operator::BitwiseAndAssign => "BinaryOP(BitwiseAndAssignment)".to_string(),
+                Operator::BitwiseOrAssign => "BinaryOP(BitwiseOrAssignment)".to_string(),
+                Operator::BitwiseXorAssign => "BinaryOP(BitwiseXorAssignment)".to_string(),
+                Operator::ShiftLeftAssign => "BinaryOP(ShiftLeftAssignment)".to_string(),
+                Operator::ShiftRightAssign => "BinaryOP(ShiftRightAssignment)".to_string(),
                 _ => unreachable!()
             }
             Node::UnaryOperation { operator, .. } => match operator.data {
-                Operator::Add => format!("UnaryOP(Positive)"),
-                Operator::Subtract => format!("UnaryOP(Negative)"),
-                Operator::BooleanNot => format!("UnaryOP(Boolean Negative)"),
+                Operator::Add => "UnaryOP(Positive)".to_string(),
+                Operator::Subtract => "UnaryOP(Negative)".to_string(),
+                Operator::BooleanNot => "UnaryOP(Boolean Negative)".to_string(),
+                Operator::BitwiseNot => "UnaryOP(Bitwise Negative)".to_string(),
                 _ => unreachable!()
             }
-            Node::Return(_) => format!("Return"),
+            Node::Return(_) => "Return".to_string(),
             Node::ClassDefinition { name, .. } => format!("Class({})", name.data),
             Node::SpaceDefinition { name, .. } => format!("Space({})", name.data),
-            Node::IfStatement { .. } => format!("If"),
-            Node::WhileLoop { .. } => format!("While"),
-            Node::MatchStatement { .. } => format!("Match"),
-            Node::Break(_) => format!("break"),
-            Node::Continue(_) => format!("continue"),
+            Node::IfStatement { .. } => "If".to_string(),
+            Node::WhileLoop { .. } => "While".to_string(),
+            Node::MatchStatement { .. } => "Match".to_string(),
+            Node::Break(_) => "break".to_string(),
+            Node::Continue(_) => "continue".to_string(),
             Node::Label { name, .. } => format!("Label({})", name.data),
             Node::InterfaceDefinition { name, .. } => format!("Interface({})", name.data),
+            Node::Lambda { .. } => "Lambda".to_string(),
+            Node::Error => "Error".to_string(),
             Node::_Unchecked(inner) => format!("!{}", inner.data.short_name()),
             Node::_Optional(inner) => format!("?{}", inner.data.short_name()),
             Node::_Renamed { node, .. } => format!("*{}", node.data.short_name()),
             Node::_Implementation(inner) => format!("@override {}", inner.data.short_name()),
             Node::_Generated(inner) => format!("@generated {}", inner.data.short_name()),
+            Node::_Lambda { function, .. } => format!("@lambda {}", function.data.short_name()),
+            Node::_ClosureCall { closure, .. } => format!("ClosureCall({})", closure.data.short_name()),
         }
     }
 
-    pub fn is_generated(&self) -> bool {
-        match self {
-            Node::_Generated(_) => true,
-            _ => false
-        }
-    } 
-
 }
 
 
@@ -185,7 +244,9 @@ pub enum DataType {
     Function {
         return_type: Option<Box<Positioned<DataType>>>,
         params: Vec<Positioned<DataType>>
-    }
+    },
+    Pointer(Box<Positioned<DataType>>),
+    Const(Box<Positioned<DataType>>)
 }
 
 impl ToString for DataType {
@@ -196,7 +257,7 @@ impl ToString for DataType {
             DataType::Function { return_type, params } => {
                 let mut buf = String::new();
                 buf.push_str("fn(");
-                let mut first = false;
+                let mut first = true;
                 for param in params {
                     if !first {
                         buf.push_str(", ");
@@ -211,34 +272,34 @@ impl ToString for DataType {
                 }
                 buf
             },
+            DataType::Pointer(inner) => format!("*{}", inner.data.to_string()),
+            DataType::Const(inner) => format!("const {}", inner.data.to_string()),
         }
     }
 
 }
 
+/// Strict structural equality: same names, same parameters in the same order, same return type.
+/// Positions are ignored.
 impl PartialEq for DataType {
     
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Self::Custom(l0), Self::Custom(r0)) => l0 == r0,
-            (Self::Function { return_type: l_return_type, params: l_params }, Self::Function { return_type: r_return_type, params: r_params }) => {
-                match (l_return_type, r_return_type) {
-                    (None, None) => {}
-                    (Some(l), Some(r)) if l.data == r.data => {},
-                    (_, _) => return false
-                }
-                
-                'A: for l_param in l_params.iter() {
-                    for r_param in r_params.iter() {
-                        if l_param.data == r_param.data {
-                            continue 'A;
-                        }
-                    }
-                    return false;
-                }
-                true
-            }
-            _ => false,
+            (DataType::Custom(lhs), DataType::Custom(rhs)) => lhs == rhs,
+            (DataType::Pointer(lhs), DataType::Pointer(rhs)) => lhs.data == rhs.data,
+            (DataType::Const(lhs), DataType::Const(rhs)) => lhs.data == rhs.data,
+            (DataType::Function { return_type: lhs_return_type, params: lhs_params }, DataType::Function { return_type: rhs_return_type, params: rhs_params }) => {
+                let same_return_type = match (lhs_return_type, rhs_return_type) {
+                    (None, None) => true,
+                    (Some(lhs), Some(rhs)) => lhs.data == rhs.data,
+                    (_, _) => false
+                };
+
+                same_return_type &&
+                    lhs_params.len() == rhs_params.len() &&
+                    lhs_params.iter().zip(rhs_params.iter()).all(|(lhs, rhs)| lhs.data == rhs.data)
+            },
+            (_, _) => false
         }
     }
 
@@ -277,6 +338,29 @@ impl FunctionDefinitionParameter {
 
 
 
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            External                                            //
+//////////////////////////////////////////////////////////////////////////////////////////////////// 
+
+#[derive(Clone, Debug)]
+pub struct External {
+    pub symbol: Option<Positioned<String>>,
+    pub variadic: bool
+}
+
+impl External {
+
+    pub fn new(symbol: Option<Positioned<String>>, variadic: bool) -> Self {
+        Self {
+            symbol,
+            variadic
+        }
+    }
+
+}
+
+
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                          Variable Type                                         //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
@@ -311,7 +395,24 @@ pub enum Operator {
     Greater,
     GreaterOrEqual,
     Less,
-    LessOrEqual
+    LessOrEqual,
+    Modulo,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseNot,
+    ShiftLeft,
+    ShiftRight,
+    AddAssign,
+    SubtractAssign,
+    MultiplyAssign,
+    DivideAssign,
+    ModuloAssign,
+    BitwiseAndAssign,
+    BitwiseOrAssign,
+    BitwiseXorAssign,
+    ShiftLeftAssign,
+    ShiftRightAssign
 }
 
 
@@ -351,4 +452,17 @@ pub struct ElifBranch {
 pub struct MatchBranch {
     pub conditions: Vec<Positioned<Node>>,
     pub body: Vec<Positioned<Node>>
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                         Lambda Capture                                         //
+//////////////////////////////////////////////////////////////////////////////////////////////////// 
+
+#[derive(Clone, Debug)]
+pub struct LambdaCapture {
+    pub name: Positioned<String>,
+    pub data_type: Positioned<DataType>,
+    pub value: Positioned<Node>
 }
\ No newline at end of file
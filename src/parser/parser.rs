@@ -1,4 +1,4 @@
-use crate::{lexer::tokens::{Token, Keyword}, util::position::{Positioned, Position}, parser::{error::ParserError, node::{Node, ValueNode, FunctionDefinitionParameter, VarType, Operator, AccessModifier, ElifBranch, MatchBranch, DataType}}};
+use crate::{lexer::tokens::{Token, Keyword}, util::position::{Positioned, Position}, parser::{error::ParserError, node::{Node, ValueNode, FunctionDefinitionParameter, VarType, Operator, AccessModifier, ElifBranch, MatchBranch, DataType, External}}};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                             Parser                                             //
@@ -7,7 +7,8 @@ use crate::{lexer::tokens::{Token, Keyword}, util::position::{Positioned, Positi
 pub struct Parser {
     tokens: Vec<Positioned<Token>>,
     index: usize,
-    tabs: usize
+    /// Errors recovered from, each replaced by a [`Node::Error`]
+    errors: Vec<ParserError>
 }
 
 impl Parser {
@@ -16,7 +17,7 @@ impl Parser {
         Self {
             tokens,
             index: 0,
-            tabs: 0
+            errors: Vec::new()
         }
     }
 
@@ -38,11 +39,19 @@ impl Parser {
     } 
 
     /* Expect */
+    /// Span of the last token, where an unexpected end of file is reported.
+    fn eof(&self) -> Positioned<()> {
+        match self.tokens.last() {
+            Some(last) => last.convert(()),
+            None => Positioned::new((), Position::default(), Position::default())
+        }
+    }
+
     fn expect_current(&self, token: Option<String>) -> Result<Positioned<Token>, ParserError> {
         if let Some(current) = self.current() {
             Ok(current)
         } else {
-            Err(ParserError::UnexpectedEOF(token))
+            Err(ParserError::UnexpectedEOF(self.eof(), token))
         }
     } 
 
@@ -51,7 +60,7 @@ impl Parser {
         if let Token::String(str) = current.data.clone() {
             Ok(current.convert(str))
         } else {
-            Err(ParserError::UnexpectedToken(current, Some("String".to_string())))
+            Err(ParserError::UnexpectedToken(Box::new(current), Some("String".to_string())))
         }
     }
 
@@ -60,63 +69,118 @@ impl Parser {
         if let Token::Identifier(str) = current.data.clone() {
             Ok(current.convert(str))
         } else {
-            Err(ParserError::UnexpectedToken(current, Some("Identifier".to_string())))
+            Err(ParserError::UnexpectedToken(Box::new(current), Some("Identifier".to_string())))
         }
     }
 
     fn expect_token(&self, token: Token) -> Result<Positioned<Token>, ParserError> {
-        let current = self.expect_current(Some("String".to_string()))?;
+        let current = self.expect_current(Some(token.to_string()))?;
+        if token == current.data.clone() {
+            Ok(current)
+        } else {
+            Err(ParserError::UnexpectedToken(Box::new(current), Some(token.to_string())))
+        }
+    }
+
+    /// Like [`Parser::expect_token`], with where the token is missing for the hint.
+    fn expect_token_after(&self, token: Token, context: &str) -> Result<Positioned<Token>, ParserError> {
+        let current = self.expect_current(Some(token.to_string()))?;
         if token == current.data.clone() {
             Ok(current)
         } else {
-            Err(ParserError::UnexpectedToken(current, Some(format!("{:?}", token))))
+            Err(ParserError::MissingToken(Box::new(current), token, context.to_string()))
         }
     }
 
     /* Parse */
+    /// Body following `=>`, `then`, `do`...: a statement on the same line or an indented block,
+    /// whose `Dedent` is consumed.
     fn parse_body(&mut self, body: &mut Vec<Positioned<Node>>) -> Result<(), ParserError> {
-        // Node?([nTab][Node][\n])* 
-        let mut first = true;
-        'A: while self.current().is_some() {
-            // Tab
-            let mut tab = 0;
-            let pre_index = self.index;
-            loop {
-                if let Some(current) = self.current() {
-                    if current.data == Token::Tab {
-                        tab += 1;
-                    } else if current.data == Token::NewLine {
-                        self.advance();
-                        tab = 0;
-                        first = false;
-                        continue;
-                    } else if current.data == Token::Keyword(Keyword::End) || current.data == Token::Keyword(Keyword::Else) || current.data == Token::Keyword(Keyword::Elif) {
-                        break 'A;
-                    } else {
-                        if tab < self.tabs && !first {
-                            self.index = pre_index;
-                            break 'A;
-                        } else {
-                            break;
-                        }
-                    }
-                    self.advance();
-                } else {
-                    self.index = pre_index;
-                    break 'A;
-                }
+        let Some(current) = self.current() else {
+            return Ok(());
+        };
+        if current.data != Token::NewLine {
+            if let Some(node) = self.parse_recovering() {
+                body.push(node);
             }
+            return Ok(());
+        }
+        if !self.peek(1).is_some_and(|token| token.data == Token::Indent) {
+            // Empty body, the new line ends the statement
+            return Ok(());
+        }
 
-            // Node
-            if let Some(node) = self.parse_current()? {
+        self.advance_x(2);
+        while let Some(current) = self.current() {
+            if current.data == Token::Dedent {
+                self.advance();
+                break;
+            }
+            if let Some(node) = self.parse_recovering() {
                 body.push(node);
-                first = false;
             }
         }
 
         Ok(())
     }
 
+    fn skip_new_lines(&mut self) {
+        while self.current().is_some_and(|token| token.data == Token::NewLine) {
+            self.advance();
+        }
+    }
+
+    /* Recovery */
+    /// Skips the broken statement starting at `start`: the rest of its line, its indented block
+    /// and the blocks it opened, up to their `end`. Returns the end of the skipped tokens.
+    fn synchronize(&mut self, start: usize) -> Position {
+        self.index = start;
+        let mut end = self.tokens[start].end.clone();
+        let mut nesting = 0usize;
+        let mut depth = 0usize;
+        let mut line_start = false;
+        while let Some(current) = self.current() {
+            if self.index > start {
+                match current.data {
+                    // Closes the block around the statement
+                    Token::Dedent if nesting == 0 => break,
+                    Token::Indent | Token::Dedent => {},
+                    _ if line_start && nesting == 0 && depth == 0 => break,
+                    _ => {}
+                }
+            }
+
+            match current.data {
+                Token::Indent => nesting += 1,
+                Token::Dedent => nesting = nesting.saturating_sub(1),
+                Token::Keyword(Keyword::If) | Token::Keyword(Keyword::While) | Token::Keyword(Keyword::Match) => depth += 1,
+                Token::Keyword(Keyword::End) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            line_start = matches!(current.data, Token::NewLine | Token::Dedent);
+            if !matches!(current.data, Token::NewLine | Token::Indent | Token::Dedent) {
+                end = current.end;
+            }
+            self.advance();
+        }
+        end
+    }
+
+    /// Parses the current statement, on error records it and replaces the statement by a
+    /// [`Node::Error`] spanning the skipped tokens.
+    fn parse_recovering(&mut self) -> Option<Positioned<Node>> {
+        let start = self.index;
+        match self.parse_current() {
+            Ok(node) => node,
+            Err(err) => {
+                self.errors.push(err);
+                let start_pos = self.tokens[start].start.clone();
+                let end = self.synchronize(start);
+                Some(Positioned::new(Node::Error, start_pos, end))
+            }
+        }
+    }
+
     fn parse_function_call(&mut self, name: Positioned<String>) -> Result<Positioned<Node>, ParserError> {
         self.advance_x(2);
 
@@ -135,13 +199,13 @@ impl Parser {
             current = self.expect_current(Some(")".to_string()))?;
         }
         if current.data != Token::RightParenthesis {
-            return Err(ParserError::UnexpectedToken(current, Some(")".to_string())));
+            return Err(ParserError::MissingToken(Box::new(current), Token::RightParenthesis, "to close the arguments".to_string()));
         }
 
         let start = name.start.clone();
         let end = current.end.clone();
 
-        Ok(Positioned::new(Node::FunctionCall { name, parameters }, start, end))
+        Ok(Positioned::new(Node::FunctionCall { name, parameters, scope: None }, start, end))
     }
 
     fn handle_id(&mut self, id: Positioned<String>) -> Result<Positioned<Node>, ParserError> {
@@ -149,11 +213,149 @@ impl Parser {
             if Token::LeftParenthesis == next.data {
                 self.parse_function_call(id)
             } else {
-                Ok(id.clone().convert(Node::VariableCall(id.data)))
+                Ok(id.clone().convert(Node::VariableCall { name: id.data, scope: None }))
             }
         } else {
-            Ok(id.clone().convert(Node::VariableCall(id.data)))
+            Ok(id.clone().convert(Node::VariableCall { name: id.data, scope: None }))
+        }
+    }
+
+    fn parse_data_type(&mut self) -> Result<Positioned<DataType>, ParserError> {
+        let current = self.expect_current(Some("Type".to_string()))?;
+
+        // *Type
+        if current.data == Token::Star {
+            self.advance();
+            let inner = self.parse_data_type()?;
+            return Ok(Positioned::new(DataType::Pointer(Box::new(inner.clone())), current.start, inner.end));
+        }
+
+        // const Type
+        if current.data == Token::Keyword(Keyword::Const) {
+            self.advance();
+            let inner = self.parse_data_type()?;
+            return Ok(Positioned::new(DataType::Const(Box::new(inner.clone())), current.start, inner.end));
+        }
+
+        if current.data != Token::Keyword(Keyword::Fn) {
+            let id = self.expect_id()?;
+            return Ok(id.clone().convert(DataType::Custom(id.data)));
+        }
+
+        // fn(Type, ...): Type
+        self.advance();
+        self.expect_token(Token::LeftParenthesis)?;
+        self.advance();
+        let mut params = Vec::new();
+        let mut current = self.expect_current(Some(")".to_string()))?;
+        while current.data != Token::RightParenthesis {
+            params.push(self.parse_data_type()?);
+            self.advance();
+
+            current = self.expect_current(Some(")".to_string()))?;
+            if current.data != Token::Comma {
+                break;
+            }
+            self.advance();
+            current = self.expect_current(Some(")".to_string()))?;
         }
+        let mut end = self.expect_token(Token::RightParenthesis)?.end;
+
+        let mut return_type = None;
+        if let Some(next) = self.peek(1) {
+            if next.data == Token::Colon {
+                self.advance_x(2);
+                let data_type = self.parse_data_type()?;
+                end = data_type.end.clone();
+                return_type = Some(Box::new(data_type));
+            }
+        }
+
+        Ok(Positioned::new(DataType::Function { 
+            return_type, 
+            params 
+        }, current.start, end))
+    }
+
+    fn parse_lambda(&mut self, start: Position) -> Result<Positioned<Node>, ParserError> {
+        self.advance();
+
+        let mut parameters = Vec::new();
+        self.expect_token(Token::LeftParenthesis)?;
+        self.advance();
+        let mut current = self.expect_current(Some(")".to_string()))?;
+        while current.data != Token::RightParenthesis {
+            // ID
+            let param_name = self.expect_id()?;
+            self.advance();
+            // :
+            self.expect_token(Token::Colon)?;
+            self.advance();
+            // Type
+            let data_type = self.parse_data_type()?;
+            self.advance();
+            // Push
+            parameters.push(FunctionDefinitionParameter::new(param_name, data_type));
+            // ,
+            current = self.expect_current(Some(")".to_string()))?;
+            if current.data != Token::Comma {
+                break;
+            } 
+            self.advance();
+            current = self.expect_current(Some(")".to_string()))?;
+        }
+        self.expect_token(Token::RightParenthesis)?;
+        self.advance();
+
+        let mut return_type = None;
+        if let Some(current) = self.current() {
+            if current.data == Token::Colon {
+                self.advance();
+                return_type = Some(self.parse_data_type()?);
+                self.advance();
+            }
+        }
+
+        // Body
+        self.expect_token(Token::RightDoubleArrow)?;
+        self.advance();
+        let mut body = Vec::new();
+        self.parse_body(&mut body)?;
+
+        // [Semantic]: Check the nodes inside the body
+        for node in body.iter() {
+            match &node.data {
+                Node::Value(_) |
+                Node::FunctionCall { .. } |
+                Node::VariableDefinition { .. } |
+                Node::VariableCall { .. } |
+                Node::BinaryOperation { .. } |
+                Node::UnaryOperation { .. } |
+                Node::Return(_) |
+                Node::Break(_) |
+                Node::Continue(_) |
+                Node::IfStatement { .. } |
+                Node::WhileLoop { .. } |
+                Node::MatchStatement { .. } |
+                Node::Label { .. }  => {}
+                Node::Error => {}
+                _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), None))
+            }
+        }
+
+        let Some(last) = body.last() else {
+            return Err(ParserError::UnexpectedToken(Box::new(self.expect_current(Some("Expression".to_string()))?), Some("Expression".to_string())));
+        };
+        let end = last.end.clone();
+
+        // Stay on the last token of the expression
+        self.index -= 1;
+
+        Ok(Positioned::new(Node::Lambda { 
+            parameters, 
+            return_type, 
+            body 
+        }, start, end))
     }
 
     fn parse_unary(&mut self, operator: Positioned<Operator>) -> Result<Positioned<Node>, ParserError> {
@@ -178,13 +380,15 @@ impl Parser {
             Token::Plus => self.parse_unary(current.convert(Operator::Add)),
             Token::Dash => self.parse_unary(current.convert(Operator::Subtract)),
             Token::Keyword(Keyword::Not) => self.parse_unary(current.convert(Operator::BooleanNot)),
+            Token::Tilde => self.parse_unary(current.convert(Operator::BitwiseNot)),
+            Token::Keyword(Keyword::Fn) => self.parse_lambda(current.start),
             Token::LeftParenthesis => {
                 self.advance();
                 let expr = self.parse_expr()?;
                 self.expect_token(Token::RightParenthesis)?;
                 Ok(expr)
             }
-            _ => Err(ParserError::UnexpectedToken(current, Some("Expression".to_string())))
+            _ => Err(ParserError::UnexpectedToken(Box::new(current), Some("Expression".to_string())))
         }
     }
 
@@ -221,6 +425,7 @@ impl Parser {
             let operator = match current.data {
                 Token::Star => current.convert(Operator::Multiply), 
                 Token::Slash => current.convert(Operator::Divide), 
+                Token::Percent => current.convert(Operator::Modulo), 
                 _ => break
             };
             self.advance();
@@ -267,6 +472,103 @@ impl Parser {
     fn parse_expr4(&mut self) -> Result<Positioned<Node>, ParserError> {
         let mut left = self.parse_expr3()?;
 
+        while let Some(current) = self.current() {
+            let operator = match current.data {
+                Token::DoubleLeftAngle => current.convert(Operator::ShiftLeft), 
+                Token::DoubleRightAngle => current.convert(Operator::ShiftRight), 
+                _ => break
+            };
+            self.advance();
+
+            let right = self.parse_expr3()?;
+            
+            let start = left.start.clone();
+            let end = right.end.clone();
+            left = Positioned::new(Node::BinaryOperation { 
+                lhs: Box::new(left), 
+                operator, 
+                rhs: Box::new(right) 
+            }, start, end);
+        }
+
+        Ok(left)
+    }
+
+    fn parse_expr5(&mut self) -> Result<Positioned<Node>, ParserError> {
+        let mut left = self.parse_expr4()?;
+
+        while let Some(current) = self.current() {
+            let operator = match current.data {
+                Token::Ampersand => current.convert(Operator::BitwiseAnd), 
+                _ => break
+            };
+            self.advance();
+
+            let right = self.parse_expr4()?;
+            
+            let start = left.start.clone();
+            let end = right.end.clone();
+            left = Positioned::new(Node::BinaryOperation { 
+                lhs: Box::new(left), 
+                operator, 
+                rhs: Box::new(right) 
+            }, start, end);
+        }
+
+        Ok(left)
+    }
+
+    fn parse_expr6(&mut self) -> Result<Positioned<Node>, ParserError> {
+        let mut left = self.parse_expr5()?;
+
+        while let Some(current) = self.current() {
+            let operator = match current.data {
+                Token::Caret => current.convert(Operator::BitwiseXor), 
+                _ => break
+            };
+            self.advance();
+
+            let right = self.parse_expr5()?;
+            
+            let start = left.start.clone();
+            let end = right.end.clone();
+            left = Positioned::new(Node::BinaryOperation { 
+                lhs: Box::new(left), 
+                operator, 
+                rhs: Box::new(right) 
+            }, start, end);
+        }
+
+        Ok(left)
+    }
+
+    fn parse_expr7(&mut self) -> Result<Positioned<Node>, ParserError> {
+        let mut left = self.parse_expr6()?;
+
+        while let Some(current) = self.current() {
+            let operator = match current.data {
+                Token::Pipe => current.convert(Operator::BitwiseOr), 
+                _ => break
+            };
+            self.advance();
+
+            let right = self.parse_expr6()?;
+            
+            let start = left.start.clone();
+            let end = right.end.clone();
+            left = Positioned::new(Node::BinaryOperation { 
+                lhs: Box::new(left), 
+                operator, 
+                rhs: Box::new(right) 
+            }, start, end);
+        }
+
+        Ok(left)
+    }
+
+    fn parse_expr8(&mut self) -> Result<Positioned<Node>, ParserError> {
+        let mut left = self.parse_expr7()?;
+
         while let Some(current) = self.current() {
             let operator = match current.data {
                 Token::DoubleEqual => current.convert(Operator::Equal), 
@@ -279,7 +581,7 @@ impl Parser {
             };
             self.advance();
 
-            let right = self.parse_expr3()?;
+            let right = self.parse_expr7()?;
             
             let start = left.start.clone();
             let end = right.end.clone();
@@ -293,8 +595,8 @@ impl Parser {
         Ok(left)
     }
 
-    fn parse_expr5(&mut self) -> Result<Positioned<Node>, ParserError> {
-        let mut left = self.parse_expr4()?;
+    fn parse_expr9(&mut self) -> Result<Positioned<Node>, ParserError> {
+        let mut left = self.parse_expr8()?;
 
         while let Some(current) = self.current() {
             let operator = match current.data {
@@ -305,7 +607,7 @@ impl Parser {
             };
             self.advance();
 
-            let right = self.parse_expr4()?;
+            let right = self.parse_expr8()?;
             
             let start = left.start.clone();
             let end = right.end.clone();
@@ -319,12 +621,22 @@ impl Parser {
         Ok(left)
     }
 
-    fn parse_expr6(&mut self) -> Result<Positioned<Node>, ParserError> {
-        let mut left = self.parse_expr5()?;
+    fn parse_expr10(&mut self) -> Result<Positioned<Node>, ParserError> {
+        let mut left = self.parse_expr9()?;
 
         while let Some(current) = self.current() {
             let operator = match current.data {
                 Token::Equal => current.convert(Operator::Assign), 
+                Token::PlusEqual => current.convert(Operator::AddAssign), 
+                Token::DashEqual => current.convert(Operator::SubtractAssign), 
+                Token::StarEqual => current.convert(Operator::MultiplyAssign), 
+                Token::SlashEqual => current.convert(Operator::DivideAssign), 
+                Token::PercentEqual => current.convert(Operator::ModuloAssign), 
+                Token::AmpersandEqual => current.convert(Operator::BitwiseAndAssign), 
+                Token::PipeEqual => current.convert(Operator::BitwiseOrAssign), 
+                Token::CaretEqual => current.convert(Operator::BitwiseXorAssign), 
+                Token::DoubleLeftAngleEqual => current.convert(Operator::ShiftLeftAssign), 
+                Token::DoubleRightAngleEqual => current.convert(Operator::ShiftRightAssign), 
                 _ => break
             };
             self.advance();
@@ -344,7 +656,7 @@ impl Parser {
     }
 
     fn parse_expr(&mut self) -> Result<Positioned<Node>, ParserError> {
-        self.parse_expr6()
+        self.parse_expr10()
     }
 
     fn parse_use(&mut self, start: Position) -> Result<Positioned<Node>, ParserError> {
@@ -355,7 +667,7 @@ impl Parser {
         Ok(Positioned::new(Node::Use(path), start, end))
     }
 
-    fn parse_function_definition(&mut self, start: Position, external: bool, constructor: bool, access: Option<Positioned<AccessModifier>>) -> Result<Positioned<Node>, ParserError> {
+    fn parse_function_definition(&mut self, start: Position, mut external: Option<External>, constructor: bool, access: Option<Positioned<AccessModifier>>) -> Result<Positioned<Node>, ParserError> {
         self.advance();
         let name = self.expect_id()?;
         self.advance();
@@ -365,6 +677,15 @@ impl Parser {
         self.advance();
         let mut current = self.expect_current(Some(")".to_string()))?;
         while current.data != Token::RightParenthesis {
+            // ... (only for external functions, always last)
+            if current.data == Token::Ellipsis {
+                let Some(external) = &mut external else {
+                    return Err(ParserError::UnexpectedToken(Box::new(current), Some("Identifier".to_string())));
+                };
+                external.variadic = true;
+                self.advance();
+                break;
+            }
             // ID
             let param_name = self.expect_id()?;
             self.advance();
@@ -372,8 +693,7 @@ impl Parser {
             self.expect_token(Token::Colon)?;
             self.advance();
             // Type
-            let id = self.expect_id()?;
-            let data_type = id.clone().convert(DataType::Custom(id.data));
+            let data_type = self.parse_data_type()?;
             self.advance();
             // Push
             parameters.push(FunctionDefinitionParameter::new(param_name, data_type));
@@ -385,15 +705,14 @@ impl Parser {
             self.advance();
             current = self.expect_current(Some(")".to_string()))?;
         }
-        let mut end = self.expect_token(Token::RightParenthesis)?.end;
+        let mut end = self.expect_token_after(Token::RightParenthesis, "to close the parameters")?.end;
         self.advance();
 
         let mut return_type = None;
         if let Some(current) = self.current() {
             if current.data == Token::Colon {
                 self.advance();
-                let id = self.expect_id()?; // TODO: Take expr (and allow BinOp.access)
-                return_type = Some(id.clone().convert(DataType::Custom(id.data)));
+                return_type = Some(self.parse_data_type()?); // TODO: Take expr (and allow BinOp.access)
                 end = return_type.as_ref().unwrap().end.clone();
                 self.advance();
             }
@@ -401,15 +720,17 @@ impl Parser {
 
         let mut body = Vec::new();
         if let Some(current) = self.current() {
+            // Declarations without a body end the line
+            if current.data != Token::RightDoubleArrow && current.data != Token::NewLine {
+                return Err(ParserError::MissingToken(Box::new(current), Token::RightDoubleArrow, "after the function signature".to_string()));
+            }
             if current.data == Token::RightDoubleArrow {
-                if external {
-                    return Err(ParserError::UnexpectedToken(current, None));
+                if external.is_some() {
+                    return Err(ParserError::UnexpectedToken(Box::new(current), None));
                 }
                 self.advance();
                 // Body
-                self.tabs += 1;
                 self.parse_body(&mut body)?;
-                self.tabs -= 1;
 
                 // [Semantic]: Check the nodes inside the body
                 for node in body.iter() {
@@ -417,15 +738,18 @@ impl Parser {
                         Node::Value(_) |
                         Node::FunctionCall { .. } |
                         Node::VariableDefinition { .. } |
-                        Node::VariableCall(_) |
+                        Node::VariableCall { .. } |
                         Node::BinaryOperation { .. } |
                         Node::UnaryOperation { .. } |
                         Node::Return(_) |
+                        Node::Break(_) |
+                        Node::Continue(_) |
                         Node::IfStatement { .. } |
                         Node::WhileLoop { .. } |
                         Node::MatchStatement { .. } |
                         Node::Label { .. }  => {}
-                        _ => return Err(ParserError::UnexpectedNode(node.clone(), None))
+                        Node::Error => {}
+                        _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), None))
                     }
                 }
             }
@@ -438,11 +762,13 @@ impl Parser {
             parameters, 
             return_type, 
             body,
-            access 
+            access,
+            module: None,
+            scope: None 
         }, start, end))
     } 
 
-    fn parse_variable_definition(&mut self, start: Position, var_type: Positioned<VarType>, access: Option<Positioned<AccessModifier>>) -> Result<Positioned<Node>, ParserError> {
+    fn parse_variable_definition(&mut self, start: Position, var_type: Positioned<VarType>, access: Option<Positioned<AccessModifier>>, external: Option<External>) -> Result<Positioned<Node>, ParserError> {
         self.advance();
 
         // Name
@@ -455,8 +781,7 @@ impl Parser {
         if let Some(current) = self.current() {
             if current.data == Token::Colon {
                 self.advance();
-                let id= self.expect_id()?;
-                data_type = Some(id.clone().convert(DataType::Custom(id.data)));
+                data_type = Some(self.parse_data_type()?);
                 end = self.current().unwrap().end.clone();
                 self.advance();
             }
@@ -473,7 +798,15 @@ impl Parser {
             }
         }
 
-        if var_type.data == VarType::Constant && value.is_none() {
+        // [Semantic]: Externs are defined on the C side
+        if external.is_some() {
+            if value.is_some() {
+                return Err(ParserError::InitializedExternal(name));
+            }
+            if data_type.is_none() {
+                return Err(ParserError::UntypedExternal(name));
+            }
+        } else if var_type.data == VarType::Constant && value.is_none() {
             return Err(ParserError::UninitializedConstant(name));
         }
 
@@ -483,7 +816,10 @@ impl Parser {
             name, 
             data_type, 
             value,
-            access
+            access,
+            external,
+            module: None,
+            scope: None
         }, start, end))
     }
 
@@ -502,7 +838,7 @@ impl Parser {
         }
     }
 
-    fn parse_class_definition(&mut self, start: Position, access: Option<Positioned<AccessModifier>>) -> Result<Positioned<Node>, ParserError> {
+    fn parse_class_definition(&mut self, start: Position, access: Option<Positioned<AccessModifier>>, external: Option<External>) -> Result<Positioned<Node>, ParserError> {
         self.advance();
         let name = self.expect_id()?;
         self.advance();
@@ -528,29 +864,41 @@ impl Parser {
             }
         }
 
-        self.tabs += 1;
         let mut body = Vec::new();
         self.parse_body(&mut body)?;
 
         // [Semantic]: Check the nodes inside the body
-        for node in body.iter() {
-            match &node.data {
+        for node in body.iter_mut() {
+            match &mut node.data {
+                Node::VariableDefinition { value: None, access: field_access, external: field_external, name, .. } if external.is_some() => {
+                    // Fields of extern classes are laid out by C and always public
+                    if field_access.is_none() {
+                        *field_access = Some(name.convert(AccessModifier::Public));
+                    }
+                    if field_external.is_none() {
+                        *field_external = Some(External::new(None, false));
+                    }
+                },
+                Node::Error => {}
+                _ if external.is_some() => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), Some("Field".to_string()))),
                 Node::FunctionDefinition { .. } |
                 Node::VariableDefinition { .. } => { },
-                _ => return Err(ParserError::UnexpectedNode(node.clone(), Some("Method, Constructor or Field".to_string())))
+                _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), Some("Method, Constructor or Field".to_string())))
             }
         }
 
         if let Some(last) = body.last() {
             end = last.end.clone();
         }
-        self.tabs -= 1;
 
         Ok(Positioned::new(Node::ClassDefinition { 
             name, 
             body,
             access,
-            extensions
+            extensions,
+            external,
+            module: None,
+            scope: None
         }, start, end))
     }
 
@@ -560,7 +908,6 @@ impl Parser {
         self.advance();
         let mut end = name.end.clone();
 
-        self.tabs += 1;
         let mut body = Vec::new();
         self.parse_body(&mut body)?;
 
@@ -570,30 +917,32 @@ impl Parser {
                 Node::FunctionDefinition { .. } |
                 Node::ClassDefinition { .. } |
                 Node::SpaceDefinition { .. } |
-                Node::InterfaceDefinition { .. } => { },
-                _ => return Err(ParserError::UnexpectedNode(node.clone(), Some("Function, Class, Interface or Space".to_string())))
+                Node::InterfaceDefinition { .. } |
+                Node::VariableDefinition { .. } => { },
+                Node::Error => {}
+                _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), Some("Function, Class, Interface, Space or Variable".to_string())))
             }
         }
 
         if let Some(last) = body.last() {
             end = last.end.clone();
         }
-        self.tabs -= 1;
 
         Ok(Positioned::new(Node::SpaceDefinition { 
             name, 
             body,
-            access
+            access,
+            module: None,
+            scope: None
         }, start, end))
     }
 
     fn parse_if_statement(&mut self, start: Position) -> Result<Positioned<Node>, ParserError> {
         self.advance();
         let if_condition = self.parse_expr()?;
-        self.expect_token(Token::Keyword(Keyword::Then))?;
+        self.expect_token_after(Token::Keyword(Keyword::Then), "after the condition")?;
         self.advance();
         
-        self.tabs += 1;
         let mut if_body = Vec::new();
         self.parse_body(&mut if_body)?;
 
@@ -603,15 +952,18 @@ impl Parser {
                 Node::Value(_) |
                 Node::FunctionCall { .. } |
                 Node::VariableDefinition { .. } |
-                Node::VariableCall(_) |
+                Node::VariableCall { .. } |
                 Node::BinaryOperation { .. } |
                 Node::UnaryOperation { .. } |
                 Node::Return(_) |
+                Node::Break(_) |
+                Node::Continue(_) |
                 Node::IfStatement { .. } |
                 Node::WhileLoop { .. } |
                 Node::MatchStatement { .. } |
                 Node::Label { .. }  => {}
-                _ => return Err(ParserError::UnexpectedNode(node.clone(), None))
+                Node::Error => {}
+                _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), None))
             }
         }
 
@@ -627,7 +979,7 @@ impl Parser {
                 Token::Keyword(Keyword::Elif) => {
                     self.advance();
                     let condition = self.parse_expr()?;
-                    self.expect_token(Token::Keyword(Keyword::Then))?;
+                    self.expect_token_after(Token::Keyword(Keyword::Then), "after the condition")?;
                     self.advance();
 
                     let mut body = Vec::new();
@@ -639,15 +991,18 @@ impl Parser {
                             Node::Value(_) |
                             Node::FunctionCall { .. } |
                             Node::VariableDefinition { .. } |
-                            Node::VariableCall(_) |
+                            Node::VariableCall { .. } |
                             Node::BinaryOperation { .. } |
                             Node::UnaryOperation { .. } |
                             Node::Return(_) |
+                            Node::Break(_) |
+                            Node::Continue(_) |
                             Node::IfStatement { .. } |
                             Node::WhileLoop { .. } |
                             Node::MatchStatement { .. } |
                             Node::Label { .. }  => {}
-                            _ => return Err(ParserError::UnexpectedNode(node.clone(), None))
+                            Node::Error => {}
+                            _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), None))
                         }
                     }
 
@@ -660,6 +1015,7 @@ impl Parser {
                     self.advance();
 
                     self.parse_body(&mut else_body)?;
+                    self.skip_new_lines();
 
                     // [Semantic]: Check the nodes inside the body
                     for node in else_body.iter() {
@@ -667,15 +1023,18 @@ impl Parser {
                             Node::Value(_) |
                             Node::FunctionCall { .. } |
                             Node::VariableDefinition { .. } |
-                            Node::VariableCall(_) |
+                            Node::VariableCall { .. } |
                             Node::BinaryOperation { .. } |
                             Node::UnaryOperation { .. } |
                             Node::Return(_) |
+                            Node::Break(_) |
+                            Node::Continue(_) |
                             Node::IfStatement { .. } |
                             Node::WhileLoop { .. } |
                             Node::MatchStatement { .. } |
                             Node::Label { .. }  => {}
-                            _ => return Err(ParserError::UnexpectedNode(node.clone(), None))
+                            Node::Error => {}
+                            _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), None))
                         }
                     }
 
@@ -684,11 +1043,12 @@ impl Parser {
                     break
                 }
                 Token::Keyword(Keyword::End) => {
-                    self.tabs -= 1;
                     end = current.end;
+                    self.advance();
                     break;
                 }
-                _ => return Err(ParserError::UnexpectedToken(current, Some("end".to_string())))
+                Token::NewLine => self.advance(),
+                _ => return Err(ParserError::UnexpectedToken(Box::new(current), Some("end".to_string())))
             }
         }
 
@@ -696,25 +1056,21 @@ impl Parser {
             condition: Box::new(if_condition), 
             body: if_body, 
             elif_branches: branches, 
-            else_body 
+            else_body,
+            scopes: Vec::new() 
         }, start, end))
     }
 
     fn parse_while_loop(&mut self, start: Position) -> Result<Positioned<Node>, ParserError> {
         self.advance();
         let expr = self.parse_expr()?;
-        self.expect_token(Token::Keyword(Keyword::Do))?;
+        self.expect_token_after(Token::Keyword(Keyword::Do), "after the condition")?;
         self.advance();
 
         let mut body = Vec::new();
-        let mut current = self.expect_current(Some("end".to_string()))?;
-        while current.data != Token::Keyword(Keyword::End) {
-            if let Some(node) = self.parse_current()? {
-                body.push(node);
-            }
-            current = self.expect_current(Some("end".to_string()))?;
-        }
-        let end = current.end.clone();
+        self.parse_body(&mut body)?;
+        self.skip_new_lines();
+        let end = self.expect_token(Token::Keyword(Keyword::End))?.end;
         self.advance();
 
         // [Semantic]: Check the nodes inside the body
@@ -723,22 +1079,26 @@ impl Parser {
                 Node::Value(_) |
                 Node::FunctionCall { .. } |
                 Node::VariableDefinition { .. } |
-                Node::VariableCall(_) |
+                Node::VariableCall { .. } |
                 Node::BinaryOperation { .. } |
                 Node::UnaryOperation { .. } |
                 Node::Return(_) |
+                Node::Break(_) |
+                Node::Continue(_) |
                 Node::IfStatement { .. } |
                 Node::WhileLoop { .. } |
                 Node::MatchStatement { .. } |
                 Node::Label { .. }  => {}
-                _ => return Err(ParserError::UnexpectedNode(node.clone(), None))
+                Node::Error => {}
+                _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), None))
             }
         }
 
         
         Ok(Positioned::new(Node::WhileLoop { 
             condition: Box::new(expr), 
-            body 
+            body,
+            scope: None 
         }, start, end))
     }
 
@@ -747,20 +1107,20 @@ impl Parser {
         let expr = self.parse_expr()?;
         self.expect_token(Token::NewLine)?;
         self.advance();
-        self.tabs += 1;
+        self.expect_token(Token::Indent)?;
+        self.advance();
 
         let mut branches = Vec::new();
         let mut else_body = Vec::new();
         let end;
 
-        let mut tabs = 0;
+        // The `else` branch is either the last branch or follows the branches, before `end`
+        let mut in_branches = true;
         let mut current = self.expect_current(Some("end".to_string()))?;
         loop {
             match current.data {
                 Token::Keyword(Keyword::Else) => {
                     self.advance();
-
-                    // No +1 on index because already +1 for the global match
                     self.parse_body(&mut else_body)?;
 
                     // [Semantic]: Check the nodes inside the body
@@ -769,45 +1129,40 @@ impl Parser {
                             Node::Value(_) |
                             Node::FunctionCall { .. } |
                             Node::VariableDefinition { .. } |
-                            Node::VariableCall(_) |
+                            Node::VariableCall { .. } |
                             Node::BinaryOperation { .. } |
                             Node::UnaryOperation { .. } |
                             Node::Return(_) |
+                            Node::Break(_) |
+                            Node::Continue(_) |
                             Node::IfStatement { .. } |
                             Node::WhileLoop { .. } |
                             Node::MatchStatement { .. } |
                             Node::Label { .. }  => {}
-                            _ => return Err(ParserError::UnexpectedNode(node.clone(), None))
+                            Node::Error => {}
+                            _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), None))
                         }
                     }
-                    
-                    end = self.expect_token(Token::Keyword(Keyword::End))?.end;
-                    self.advance();
-                    break;
                 }
-                Token::Keyword(Keyword::End) => {
+                Token::Keyword(Keyword::End) if !in_branches => {
                     end = current.end.clone();
                     self.advance();
                     break;
                 }
-                Token::NewLine => {
-                    tabs = 0;
-                    self.advance();
-                }
-                Token::Tab => {
-                    tabs += 1;
+                Token::NewLine => self.advance(),
+                Token::Dedent if in_branches => {
+                    in_branches = false;
                     self.advance();
                 }
                 _ => {
-                    if tabs >= self.tabs {
-                        tabs = 0;
+                    if in_branches {
                         let mut conditions = Vec::new();
                         let mut next_allowed = true;
                         // EXPR, [TAB|NL]?EXPR
                         loop {
                             let current = self.expect_current(Some("Expr or =>".to_string()))?;
                             match current.data {
-                                Token::Tab | Token::NewLine => self.advance(),
+                                Token::NewLine => self.advance(),
                                 Token::Comma if !next_allowed => {
                                     self.advance();
                                     next_allowed = true
@@ -824,10 +1179,8 @@ impl Parser {
                         self.expect_token(Token::RightDoubleArrow)?;
                         self.advance();
     
-                        self.tabs += 1;
                         let mut body = Vec::new();
                         self.parse_body(&mut body)?;
-                        self.tabs -= 1;
 
                         // [Semantic]: Check the nodes inside the body
                         for node in body.iter() {
@@ -835,15 +1188,18 @@ impl Parser {
                                 Node::Value(_) |
                                 Node::FunctionCall { .. } |
                                 Node::VariableDefinition { .. } |
-                                Node::VariableCall(_) |
+                                Node::VariableCall { .. } |
                                 Node::BinaryOperation { .. } |
                                 Node::UnaryOperation { .. } |
                                 Node::Return(_) |
+                                Node::Break(_) |
+                                Node::Continue(_) |
                                 Node::IfStatement { .. } |
                                 Node::WhileLoop { .. } |
                                 Node::MatchStatement { .. } |
                                 Node::Label { .. }  => {}
-                                _ => return Err(ParserError::UnexpectedNode(node.clone(), None))
+                                Node::Error => {}
+                                _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), None))
                             }
                         }
                         
@@ -852,18 +1208,18 @@ impl Parser {
                             body
                         });
                     } else {
-                        return Err(ParserError::UnexpectedToken(current, Some("tab".to_string())));
+                        return Err(ParserError::UnexpectedToken(Box::new(current), Some("end".to_string())));
                     }
                 }  
             }
             current = self.expect_current(Some("end".to_string()))?;
         }
-        self.tabs -= 1;
 
         Ok(Positioned::new(Node::MatchStatement { 
             expr: Box::new(expr), 
             branches, 
-            else_body 
+            else_body,
+            scopes: Vec::new() 
         }, start, end))
     } 
 
@@ -903,7 +1259,6 @@ impl Parser {
         self.advance();
         let mut end = name.end.clone();
 
-        self.tabs += 1;
         let mut body = Vec::new();
         self.parse_body(&mut body)?;
         if let Some(last) = body.last() {
@@ -914,16 +1269,18 @@ impl Parser {
         for node in body.iter() {
             match &node.data {
                 Node::FunctionDefinition { .. } => {}
-                _ => return Err(ParserError::UnexpectedNode(node.clone(), None))
+                Node::Error => {}
+                _ => return Err(ParserError::UnexpectedNode(Box::new(node.clone()), None))
             }
         }
 
-        self.tabs -= 1;
 
         Ok(Positioned::new(Node::InterfaceDefinition { 
             name, 
             body,
-            access
+            access,
+            module: None,
+            scope: None
         }, start, end))
     }
 
@@ -931,31 +1288,55 @@ impl Parser {
         self.advance();
         let current = self.expect_current(Some("Function, Class, Space, ..".to_string()))?;
         match current.data {
-            Token::Keyword(Keyword::Fn) => self.parse_function_definition(access.start.clone(), false, false, Some(access)),
-            Token::Keyword(Keyword::New) => self.parse_function_definition(access.start.clone(), false, true, Some(access)),
-            Token::Keyword(Keyword::Var) => self.parse_variable_definition(access.start.clone(), current.convert(VarType::Variable), Some(access)),
-            Token::Keyword(Keyword::Const) => self.parse_variable_definition(access.start.clone(), current.convert(VarType::Constant), Some(access)),
-            Token::Keyword(Keyword::Class) => self.parse_class_definition(access.start.clone(), Some(access)),
+            Token::Keyword(Keyword::Fn) => self.parse_function_definition(access.start.clone(), None, false, Some(access)),
+            Token::Keyword(Keyword::New) => self.parse_function_definition(access.start.clone(), None, true, Some(access)),
+            Token::Keyword(Keyword::Var) => self.parse_variable_definition(access.start.clone(), current.convert(VarType::Variable), Some(access), None),
+            Token::Keyword(Keyword::Const) => self.parse_variable_definition(access.start.clone(), current.convert(VarType::Constant), Some(access), None),
+            Token::Keyword(Keyword::Class) => self.parse_class_definition(access.start.clone(), Some(access), None),
             Token::Keyword(Keyword::Space) => self.parse_space_definition(access.start.clone(), Some(access)),
             Token::Keyword(Keyword::Intf) => self.parse_interface_definition(access.start.clone(), Some(access)),
-            _ => Err(ParserError::UnexpectedToken(current, Some("Function, Class, Space, ..".to_string())))
+            _ => Err(ParserError::UnexpectedToken(Box::new(current), Some("Function, Class, Space, ..".to_string())))
         }
     }
 
     fn handle_keyword(&mut self, keyword: Positioned<Keyword>) -> Result<Positioned<Node>, ParserError> {
         match keyword.data {
             Keyword::Use => self.parse_use(keyword.start),
-            Keyword::Fn => self.parse_function_definition(keyword.start, false, false, None),
-            Keyword::New => self.parse_function_definition(keyword.start, false, true, None),
+            Keyword::Fn => self.parse_function_definition(keyword.start, None, false, None),
+            Keyword::New => self.parse_function_definition(keyword.start, None, true, None),
             Keyword::Extern => {
                 self.advance();
-                _ = self.expect_token(Token::Keyword(Keyword::Fn))?;
-                self.parse_function_definition(keyword.start, true, false, None)
+                // extern "symbol" (fn | class | struct | var | const)
+                let mut symbol = None;
+                if let Some(current) = self.current() {
+                    if let Token::String(str) = current.data.clone() {
+                        symbol = Some(current.convert(str));
+                        self.advance();
+                    }
+                }
+                let current = self.expect_current(Some("Function, Class, Struct, Variable or Constant".to_string()))?;
+                match current.data {
+                    Token::Keyword(Keyword::Fn) => self.parse_function_definition(keyword.start, Some(External::new(symbol, false)), false, None),
+                    Token::Keyword(Keyword::Class) => self.parse_class_definition(keyword.start, None, Some(External::new(symbol, false))),
+                    Token::Keyword(Keyword::Struct) => {
+                        let mut node = self.parse_class_definition(keyword.start, None, Some(External::new(symbol, false)))?;
+                        // `struct name` unless bound to another symbol
+                        if let Node::ClassDefinition { name, external: Some(external), .. } = &mut node.data {
+                            if external.symbol.is_none() {
+                                external.symbol = Some(name.convert(format!("struct {}", name.data)));
+                            }
+                        }
+                        Ok(node)
+                    },
+                    Token::Keyword(Keyword::Var) => self.parse_variable_definition(keyword.start, current.convert(VarType::Variable), None, Some(External::new(symbol, false))),
+                    Token::Keyword(Keyword::Const) => self.parse_variable_definition(keyword.start, current.convert(VarType::Constant), None, Some(External::new(symbol, false))),
+                    _ => Err(ParserError::UnexpectedToken(Box::new(current), Some("Function, Class, Struct, Variable or Constant".to_string())))
+                }
             },
-            Keyword::Var => self.parse_variable_definition(keyword.start.clone(), keyword.convert(VarType::Variable), None),
-            Keyword::Const => self.parse_variable_definition(keyword.start.clone(), keyword.convert(VarType::Constant), None),
+            Keyword::Var => self.parse_variable_definition(keyword.start.clone(), keyword.convert(VarType::Variable), None, None),
+            Keyword::Const => self.parse_variable_definition(keyword.start.clone(), keyword.convert(VarType::Constant), None, None),
             Keyword::Return => self.parse_return(keyword.convert(())),
-            Keyword::Class => self.parse_class_definition(keyword.start, None),
+            Keyword::Class => self.parse_class_definition(keyword.start, None, None),
             Keyword::Space => self.parse_space_definition(keyword.start, None),
             Keyword::Pub => self.handle_access(keyword.convert(AccessModifier::Public)),
             Keyword::Prot => self.handle_access(keyword.convert(AccessModifier::Protected)),
@@ -967,7 +1348,7 @@ impl Parser {
             Keyword::Break => self.parse_break(keyword.convert(())),
             Keyword::Continue => self.parse_continue(keyword.convert(())),
             Keyword::Intf => self.parse_interface_definition(keyword.start, None),
-            _ => Err(ParserError::UnexpectedToken(self.current().unwrap(), None))
+            _ => Err(ParserError::UnexpectedToken(Box::new(self.current().unwrap()), None))
         }
     }
 
@@ -986,7 +1367,7 @@ impl Parser {
                     inner: Box::new(inner) 
                 }, start, end))
             },
-            _ => return Err(ParserError::UnexpectedToken(current, Some("while".to_string()))),
+            _ => return Err(ParserError::UnexpectedToken(Box::new(current), Some("while".to_string()))),
         }
     } 
 
@@ -1001,27 +1382,38 @@ impl Parser {
             Token::Plus |
             Token::Dash |
             Token::Keyword(Keyword::Not) |
+            Token::Tilde |
             Token::LeftParenthesis => self.parse_expr().map(|x| Some(x)),
             Token::Keyword(keyword) => self.handle_keyword(current.convert(keyword)).map(|x| Some(x)),
             Token::Label(label) => self.parse_label(current.convert(label)).map(|x| Some(x)),
-            Token::NewLine | Token::Tab => {
+            Token::NewLine => {
                 self.advance(); 
                 Ok(None)
             }
-            _ => Err(ParserError::UnexpectedToken(current, None))
+            _ => Err(ParserError::UnexpectedToken(Box::new(current), None))
         }
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Positioned<Node>>, ParserError> {
+    /// Parses the whole file, broken statements are replaced by [`Node::Error`]s.
+    pub fn parse_partial(&mut self) -> (Vec<Positioned<Node>>, Vec<ParserError>) {
         let mut ast = Vec::new();
 
         while self.current().is_some() {
-            if let Some(node) = self.parse_current()? {
+            if let Some(node) = self.parse_recovering() {
                 ast.push(node);
             }
         }   
 
-        Ok(ast)
+        (ast, std::mem::take(&mut self.errors))
+    }
+
+    pub fn parse(&mut self) -> Result<Vec<Positioned<Node>>, Vec<ParserError>> {
+        let (ast, errors) = self.parse_partial();
+        if errors.is_empty() {
+            Ok(ast)
+        } else {
+            Err(errors)
+        }
     }
  
 }
\ No newline at end of file
@@ -1,8 +1,11 @@
-use crate::{ir::output::IROutput, util::position::Positioned, parser::node::{Node, Operator, ElifBranch, VarType, DataType, FunctionDefinitionParameter}};
+use crate::{ir::output::{IROutput, Include, IncludeType}, util::{position::Positioned, mangle}, parser::node::{Node, Operator, ElifBranch, VarType, DataType, FunctionDefinitionParameter, ValueNode, External, AccessModifier}};
 
 pub struct PostProcessor {
     ir_output: IROutput,
     index: usize,
+    lambda_nodes: Vec<Positioned<Node>>,
+    closure_used: bool,
+    extern_types: Vec<(String, String)>,
 }
 
 impl PostProcessor {
@@ -10,7 +13,10 @@ impl PostProcessor {
     pub fn new(ir_output: IROutput) -> Self {
         Self {
             ir_output,
-            index: 0
+            index: 0,
+            lambda_nodes: Vec::new(),
+            closure_used: false,
+            extern_types: Vec::new()
         }
     } 
 
@@ -23,7 +29,7 @@ impl PostProcessor {
     }
 
     fn process_function_definition(&mut self, node: Positioned<Node>, new_name: Option<String>) -> Positioned<Node> {
-        let Node::FunctionDefinition { name, external, constructor, parameters, return_type, body, access  } = node.data.clone() else {
+        let Node::FunctionDefinition { name, external, constructor, parameters, return_type, body, access, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
@@ -36,15 +42,17 @@ impl PostProcessor {
             name: new_name.map_or(name.clone(), |x| name.convert(x)), 
             external, 
             constructor, 
-            parameters, 
-            return_type, 
+            parameters: self.process_parameters(parameters), 
+            return_type: return_type.map(|x| self.process_data_type(x)), 
             body: new_body, 
-            access 
+            access,
+            module,
+            scope 
         })
     }
 
     fn process_function_call(&mut self, node: Positioned<Node>, new_name: Option<String>) -> Positioned<Node> {
-        let Node::FunctionCall { name, parameters } = node.data.clone() else {
+        let Node::FunctionCall { name, parameters, scope } = node.data.clone() else {
             unreachable!()
         };
 
@@ -55,24 +63,55 @@ impl PostProcessor {
 
         node.convert(Node::FunctionCall { 
             name: new_name.map_or(name.clone(), |x| name.convert(x)), 
-            parameters: new_params
+            parameters: new_params,
+            scope
         })
     }
 
     fn process_variable_definition(&mut self, node: Positioned<Node>) -> Positioned<Node> {
-        let Node::VariableDefinition { var_type, name, data_type, value, access } = node.data.clone() else {
+        let Node::VariableDefinition { var_type, name, data_type, value, access, external, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
         node.convert(Node::VariableDefinition { 
             var_type, 
             name, 
-            data_type, 
+            data_type: data_type.map(|x| self.process_data_type(x)), 
             value: value.map(|x| Box::new(self.process_node(*x, None))), 
-            access 
+            access,
+            external,
+            module,
+            scope 
         })
     }
 
+    fn process_data_type(&mut self, data_type: Positioned<DataType>) -> Positioned<DataType> {
+        match data_type.data.clone() {
+            DataType::Function { .. } => {
+                // Functions values are passed around as closures
+                self.closure_used = true;
+                data_type.convert(DataType::Custom("_NOPTR__Closure".to_string()))
+            },
+            DataType::Custom(custom) => {
+                // Extern classes are referenced by their C name
+                if let Some((_, symbol)) = self.extern_types.iter().find(|(name, _)| *name == custom) {
+                    data_type.convert(DataType::Custom(symbol.clone()))
+                } else {
+                    data_type
+                }
+            },
+            DataType::Pointer(inner) => data_type.convert(DataType::Pointer(Box::new(self.process_data_type(*inner)))),
+            DataType::Const(inner) => data_type.convert(DataType::Const(Box::new(self.process_data_type(*inner)))),
+        }
+    }
+
+    fn process_parameters(&mut self, parameters: Vec<FunctionDefinitionParameter>) -> Vec<FunctionDefinitionParameter> {
+        parameters.into_iter().map(|param| FunctionDefinitionParameter {
+            name: param.name,
+            data_type: self.process_data_type(param.data_type)
+        }).collect()
+    }
+
     fn process_access(&mut self, node: Positioned<Node>) -> Positioned<Node> {
         let Node::BinaryOperation { lhs, operator, rhs } = node.data.clone() else {
             unreachable!()
@@ -131,7 +170,7 @@ impl PostProcessor {
     }
 
     fn process_class_definition(&mut self, node: Positioned<Node>) -> Positioned<Node> {
-        let Node::ClassDefinition { name, body, access, extensions } = node.data.clone() else {
+        let Node::ClassDefinition { name, body, access, extensions, external, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
@@ -144,12 +183,15 @@ impl PostProcessor {
             name, 
             body: new_body, 
             access,
-            extensions
+            extensions,
+            external,
+            module,
+            scope
         })
     }
 
     fn process_space_definition(&mut self, node: Positioned<Node>) -> Positioned<Node> {
-        let Node::SpaceDefinition { name, body, access } = node.data.clone() else {
+        let Node::SpaceDefinition { name, body, access, module, scope } = node.data.clone() else {
             unreachable!()
         };
 
@@ -161,12 +203,14 @@ impl PostProcessor {
         node.convert(Node::SpaceDefinition { 
             name, 
             body: new_body, 
-            access 
+            access,
+            module,
+            scope 
         })
     }
 
     fn process_if_statement(&mut self, node: Positioned<Node>) -> Positioned<Node> {
-        let Node::IfStatement { condition, body, elif_branches, else_body } = node.data.clone() else {
+        let Node::IfStatement { condition, body, elif_branches, else_body, scopes } = node.data.clone() else {
             unreachable!()
         };
 
@@ -201,12 +245,13 @@ impl PostProcessor {
             condition: Box::new(processed_condition), 
             body: processed_body, 
             elif_branches: processed_elif_branches, 
-            else_body: processed_else_body 
+            else_body: processed_else_body,
+            scopes 
         })
     }
 
     fn process_while_loop(&mut self, node: Positioned<Node>) -> Positioned<Node> {
-        let Node::WhileLoop { condition, body } = node.data.clone() else {
+        let Node::WhileLoop { condition, body, scope } = node.data.clone() else {
             unreachable!()
         };
 
@@ -220,6 +265,7 @@ impl PostProcessor {
         node.convert(Node::WhileLoop {
             condition: Box::new(processed_condition), 
             body: processed_body, 
+            scope
         })
     }
 
@@ -245,7 +291,7 @@ impl PostProcessor {
     }
 
     fn process_interface_definition(&mut self, node: Positioned<Node>) -> Positioned<Node> {
-        let Node::InterfaceDefinition { name: interface_name, body, access } = node.data.clone() else {
+        let Node::InterfaceDefinition { name: interface_name, body, access, module, .. } = node.data.clone() else {
             unreachable!()
         };
 
@@ -271,7 +317,10 @@ impl PostProcessor {
                     params: params.clone() 
                 })), 
                 value: None, 
-                access: None 
+                access: None,
+                external: None,
+                module: None,
+                scope: None 
             }));
 
             // Process parameters
@@ -281,31 +330,37 @@ impl PostProcessor {
                 name: interface_name.convert("self".to_string()),
                 data_type: interface_name.convert(DataType::Custom(interface_name.data.clone())),
             });
-            parameters_call.push(processed_node.convert(Node::VariableCall("self".to_string())));
+            parameters_call.push(processed_node.convert(Node::VariableCall { name: "self".to_string(), scope: None }));
             for param in parameters.iter() {
-                parameters_call.push(processed_node.convert(Node::VariableCall(param.name.data.clone())));
+                parameters_call.push(processed_node.convert(Node::VariableCall { name: param.name.data.clone(), scope: None }));
             }
             new_parameters.append(&mut parameters);
 
+            let mut call = processed_node.convert(Node::BinaryOperation { 
+                lhs: Box::new(processed_node.convert(Node::VariableCall { name: "self".to_string(), scope: None })), 
+                operator: processed_node.convert(Operator::Access), 
+                rhs: Box::new(processed_node.convert(Node::FunctionCall { 
+                    name: name.clone(), 
+                    parameters: parameters_call,
+                    scope: None
+                })) 
+            });
+            if return_type.is_some() {
+                call = processed_node.convert(Node::Return(Some(Box::new(call))));
+            }
+
             // Methods TODO: Removed useless method (check if symbol is still present. If it is, remove it)
             // TODO: Put back needed to call a function part of the interface with only the interface and not the class
             new_body.push(processed_node.convert(Node::FunctionDefinition { 
                 name: name.clone(), 
-                external: false, 
+                external: None, 
                 constructor: false, 
                 parameters: new_parameters,
                 return_type: return_type.clone(), 
-                body: vec![
-                    processed_node.convert(Node::BinaryOperation { 
-                        lhs: Box::new(processed_node.convert(Node::VariableCall("self".to_string()))), 
-                        operator: processed_node.convert(Operator::Access), 
-                        rhs: Box::new(processed_node.convert(Node::FunctionCall { 
-                            name: name.clone(), 
-                            parameters: parameters_call
-                        })) 
-                    })
-                ], 
-                access: None 
+                body: vec![call], 
+                access: None,
+                module: None,
+                scope: None 
             }))
         }
 
@@ -313,7 +368,212 @@ impl PostProcessor {
             name: interface_name, 
             body: new_body, 
             access,
-            extensions: Vec::new()
+            extensions: Vec::new(),
+            external: None,
+            module,
+            scope: None
+        })
+    }
+
+    fn process_lambda(&mut self, node: Positioned<Node>) -> Positioned<Node> {
+        let Node::_Lambda { function, captures } = node.data.clone() else {
+            unreachable!()
+        };
+
+        let Node::FunctionDefinition { name, parameters, return_type, body, .. } = self.process_function_definition(*function, None).data else {
+            unreachable!()
+        };
+        self.closure_used = true;
+
+        let env_name = mangle::environment(&name.data);
+        let env_type = DataType::Custom(env_name.clone());
+        let env_call = || node.convert(Node::VariableCall { name: "_env".to_string(), scope: None });
+
+        // Environment
+        if !captures.is_empty() {
+            let mut fields = Vec::new();
+            for capture in captures.iter() {
+                fields.push(capture.name.convert(Node::VariableDefinition { 
+                    var_type: capture.name.convert(VarType::Variable), 
+                    name: capture.name.clone(), 
+                    data_type: Some(self.process_data_type(capture.data_type.clone())), 
+                    value: None, 
+                    access: None,
+                    external: None,
+                    module: None,
+                    scope: None 
+                }));
+            }
+
+            self.lambda_nodes.push(node.convert(Node::ClassDefinition { 
+                name: name.convert(env_name.clone()), 
+                body: fields, 
+                access: None, 
+                extensions: Vec::new(),
+                external: None,
+                module: None,
+                scope: None 
+            }));
+        }
+
+        // Function (receives its environment as first parameter)
+        let mut new_parameters = vec![FunctionDefinitionParameter {
+            name: name.convert("_env_data".to_string()),
+            data_type: name.convert(DataType::Custom("_NOPTR_void*".to_string()))
+        }];
+        new_parameters.extend(parameters);
+
+        let mut new_body = Vec::new();
+        if !captures.is_empty() {
+            new_body.push(node.convert(Node::VariableDefinition { 
+                var_type: node.convert(VarType::Constant), 
+                name: node.convert("_env".to_string()), 
+                data_type: Some(node.convert(env_type.clone())), 
+                value: Some(Box::new(node.convert(Node::VariableCall { name: "_env_data".to_string(), scope: None }))), 
+                access: None,
+                external: None,
+                module: None,
+                scope: None 
+            }));
+        }
+        new_body.extend(body);
+
+        self.lambda_nodes.push(node.convert(Node::FunctionDefinition { 
+            name: name.clone(), 
+            external: None, 
+            constructor: false, 
+            parameters: new_parameters, 
+            return_type, 
+            body: new_body, 
+            access: None,
+            module: None,
+            scope: None 
+        }));
+
+        // Constructor (allocates the environment and builds the closure), the environment belongs to
+        // the closure and is freed by calling `destroy` on the function value
+        let mut create_parameters = Vec::new();
+        let mut create_body = Vec::new();
+        if !captures.is_empty() {
+            create_body.push(node.convert(Node::VariableDefinition { 
+                var_type: node.convert(VarType::Constant), 
+                name: node.convert("_env".to_string()), 
+                data_type: Some(node.convert(env_type.clone())), 
+                value: Some(Box::new(node.convert(Node::FunctionCall { 
+                    name: node.convert("malloc".to_string()), 
+                    parameters: vec![
+                        node.convert(Node::FunctionCall { 
+                            name: node.convert("sizeof".to_string()), 
+                            parameters: vec![
+                                node.convert(Node::Value(ValueNode::Type(format!("_NOPTR_{}", env_name))))
+                            ],
+                            scope: None
+                        })
+                    ],
+                    scope: None
+                }))), 
+                access: None,
+                external: None,
+                module: None,
+                scope: None 
+            }));
+        }
+        for capture in captures.iter() {
+            create_parameters.push(FunctionDefinitionParameter {
+                name: capture.name.clone(),
+                data_type: self.process_data_type(capture.data_type.clone())
+            });
+            create_body.push(capture.name.convert(Node::BinaryOperation { 
+                lhs: Box::new(capture.name.convert(Node::BinaryOperation { 
+                    lhs: Box::new(env_call()), 
+                    operator: capture.name.convert(Operator::Access), 
+                    rhs: Box::new(capture.name.convert(Node::VariableCall { name: capture.name.data.clone(), scope: None })) 
+                })), 
+                operator: capture.name.convert(Operator::Assign), 
+                rhs: Box::new(capture.name.convert(Node::VariableCall { name: capture.name.data.clone(), scope: None })) 
+            }));
+        }
+
+        let closure_field = |field: &str, value: Positioned<Node>| node.convert(Node::BinaryOperation { 
+            lhs: Box::new(node.convert(Node::BinaryOperation { 
+                lhs: Box::new(node.convert(Node::VariableCall { name: "_closure".to_string(), scope: None })), 
+                operator: node.convert(Operator::DotAccess), 
+                rhs: Box::new(node.convert(Node::VariableCall { name: field.to_string(), scope: None })) 
+            })), 
+            operator: node.convert(Operator::Assign), 
+            rhs: Box::new(value) 
+        });
+        create_body.push(node.convert(Node::VariableDefinition { 
+            var_type: node.convert(VarType::Variable), 
+            name: node.convert("_closure".to_string()), 
+            data_type: Some(node.convert(DataType::Custom("_NOPTR__Closure".to_string()))), 
+            value: None, 
+            access: None,
+            external: None,
+            module: None,
+            scope: None 
+        }));
+        create_body.push(closure_field("env", if captures.is_empty() {
+            node.convert(Node::Value(ValueNode::Integer("0".to_string())))
+        } else {
+            env_call()
+        }));
+        create_body.push(closure_field("fn", node.convert(Node::VariableCall { name: name.data.clone(), scope: None })));
+        create_body.push(node.convert(Node::Return(Some(Box::new(node.convert(Node::VariableCall { name: "_closure".to_string(), scope: None }))))));
+
+        let create_name = format!("{}_create", name.data);
+        self.lambda_nodes.push(node.convert(Node::FunctionDefinition { 
+            name: name.convert(create_name.clone()), 
+            external: None, 
+            constructor: false, 
+            parameters: create_parameters, 
+            return_type: Some(node.convert(DataType::Custom("_NOPTR__Closure".to_string()))), 
+            body: create_body, 
+            access: None,
+            module: None,
+            scope: None 
+        }));
+
+        // Replace the lambda by the creation of its closure
+        let mut values = Vec::new();
+        for capture in captures {
+            values.push(self.process_node(capture.value, None));
+        }
+
+        node.convert(Node::FunctionCall { 
+            name: node.convert(create_name), 
+            parameters: values,
+            scope: None
+        })
+    }
+
+    fn process_closure_call(&mut self, node: Positioned<Node>) -> Positioned<Node> {
+        let Node::_ClosureCall { closure, data_type, parameters } = node.data.clone() else {
+            unreachable!()
+        };
+
+        let DataType::Function { return_type, params } = data_type.data.clone() else {
+            unreachable!()
+        };
+
+        // The function behind the closure receives the environment first
+        let mut new_params = vec![data_type.convert(DataType::Custom("_NOPTR_void*".to_string()))];
+        for param in params {
+            new_params.push(self.process_data_type(param));
+        }
+
+        let mut new_parameters = Vec::new();
+        for param in parameters {
+            new_parameters.push(self.process_node(param, None));
+        }
+
+        node.convert(Node::_ClosureCall { 
+            closure: Box::new(self.process_node(*closure, None)), 
+            data_type: data_type.convert(DataType::Function { 
+                return_type: return_type.map(|x| Box::new(self.process_data_type(*x))), 
+                params: new_params 
+            }), 
+            parameters: new_parameters 
         })
     }
 
@@ -324,7 +584,7 @@ impl PostProcessor {
             Node::FunctionCall { .. } => self.process_function_call(node, new_name),
             Node::Use(_) => node,
             Node::VariableDefinition { .. } => self.process_variable_definition(node),
-            Node::VariableCall(_) => node,
+            Node::VariableCall { .. } => node,
             Node::BinaryOperation { operator, .. } if operator.data == Operator::Access || operator.data == Operator::DotAccess => self.process_access(node),
             Node::BinaryOperation { .. } => self.process_bin_op(node),
             Node::UnaryOperation { .. } => self.process_unary_op(node),
@@ -338,11 +598,15 @@ impl PostProcessor {
             Node::Continue(_) => self.process_continue(node),
             Node::Label { .. } => self.process_label(node),
             Node::InterfaceDefinition { .. } => self.process_interface_definition(node),
+            Node::Lambda { .. } => unreachable!("Should have been processed in the IR Generator!"),
+            Node::Error => node, // Only found in partial parses, which are never generated
             Node::_Unchecked(inner) => self.process_node(*inner, None),
             Node::_Optional(inner) => self.process_node(*inner, None),
             Node::_Renamed { name, node } => self.process_node(*node, Some(name)),
             Node::_Implementation(inner) => node.convert(Node::_Implementation(Box::new(self.process_node(*inner, new_name)))),
-            Node::_Generated(_) => unreachable!("Should have been processed in the checker!")
+            Node::_Generated(_) => unreachable!("Should have been processed in the checker!"),
+            Node::_Lambda { .. } => self.process_lambda(node),
+            Node::_ClosureCall { .. } => self.process_closure_call(node),
         }
     }
 
@@ -352,11 +616,54 @@ impl PostProcessor {
             ast: Vec::new(),
         };
 
+        // Extern classes bound to another C symbol
+        for node in self.ir_output.ast.iter() {
+            if let Node::ClassDefinition { name, external: Some(External { symbol: Some(symbol), .. }), .. } = &node.data {
+                self.extern_types.push((name.data.clone(), symbol.data.clone()));
+            }
+        }
+
         while let Some(current) = self.current() {
             output.ast.push(self.process_node(current, None));
+            output.ast.append(&mut self.lambda_nodes);
             self.advance();
         }
 
+        // Closure structure
+        if self.closure_used {
+            let pos = Positioned::new((), Default::default(), Default::default());
+
+            // Environments are allocated and freed with the C allocator
+            let stdlib = Include { 
+                include_type: IncludeType::StdExternal, 
+                path: pos.convert("stdlib.h".to_string()) 
+            };
+            if !output.includes.iter().any(|include| include.full_path() == stdlib.full_path()) {
+                output.includes.push(stdlib);
+            }
+
+            let field = |name: &str| pos.convert(Node::VariableDefinition { 
+                var_type: pos.convert(VarType::Variable), 
+                name: pos.convert(name.to_string()), 
+                data_type: Some(pos.convert(DataType::Custom("_NOPTR_void*".to_string()))), 
+                value: None, 
+                access: None,
+                external: None,
+                module: None,
+                scope: None 
+            });
+            output.ast.insert(0, pos.convert(Node::ClassDefinition { 
+                name: pos.convert("_Closure".to_string()), 
+                body: vec![field("env"), field("fn")], 
+                // Closures can cross the public API of a library
+                access: Some(pos.convert(AccessModifier::Public)), 
+                extensions: Vec::new(),
+                external: None,
+                module: None,
+                scope: None 
+            }));
+        }
+
         output
     }
 
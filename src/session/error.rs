@@ -0,0 +1,86 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Compile Error                                         //
+//////////////////////////////////////////////////////////////////////////////////////////////////// 
+
+use crate::{util::{position::Positioned, source_file::SourceFile, error::{ErrorFormat, ErrorType}}, lexer::error::LexerError, parser::error::ParserError, symbolizer::error::SymbolizerError, ir::error::IRError, checker::error::CheckerError, evaluator::error::EvaluatorError, generator::error::GeneratorError};
+
+pub enum CompileError {
+    Lexer(LexerError),
+    /// Every error the parser recovered from, in order
+    Parser(Vec<ParserError>),
+    /// `use "module.taly"` of a module neither added to the session nor found on disk
+    ModuleNotFound(Box<Positioned<String>>),
+    /// Error found in an imported module
+    Module(SourceFile, Box<CompileError>),
+    // Boxed, the stage errors carry several spans and would make every result of the session large
+    Symbolizer(Box<SymbolizerError>),
+    IR(Box<IRError>),
+    Checker(Box<CheckerError>),
+    Evaluator(EvaluatorError),
+    Generator(GeneratorError)
+}
+
+impl CompileError {
+
+    /// Kind and span of every error, several for the parser.
+    pub fn diagnostics(&self) -> Vec<(String, Option<Positioned<()>>)> {
+        match self {
+            CompileError::Parser(errors) => errors.iter().map(|err| (format!("Parser::{}", err.kind()), err.span())).collect(),
+            CompileError::Module(_, err) => err.diagnostics(),
+            _ => vec![(self.kind(), self.span())]
+        }
+    }
+
+    /// Stage and variant of the (first) error, `Checker::SymbolNotFound` for instance.
+    pub fn kind(&self) -> String {
+        match self {
+            CompileError::Lexer(err) => format!("Lexer::{}", err.kind()),
+            CompileError::Parser(errors) => format!("Parser::{}", errors[0].kind()),
+            CompileError::ModuleNotFound(_) => "ModuleNotFound".to_string(),
+            CompileError::Module(_, err) => err.kind(),
+            CompileError::Symbolizer(err) => format!("Symbolizer::{}", err.kind()),
+            CompileError::IR(err) => format!("IR::{}", err.kind()),
+            CompileError::Checker(err) => format!("Checker::{}", err.kind()),
+            CompileError::Evaluator(err) => format!("Evaluator::{}", err.kind()),
+            CompileError::Generator(err) => format!("Generator::{}", err.kind()),
+        }
+    }
+
+    /// Span of the main message of the error, in the imported module for [`CompileError::Module`].
+    pub fn span(&self) -> Option<Positioned<()>> {
+        match self {
+            CompileError::Lexer(err) => err.span(),
+            CompileError::Parser(errors) => errors[0].span(),
+            CompileError::ModuleNotFound(path) => Some(path.convert(())),
+            CompileError::Module(_, err) => err.span(),
+            CompileError::Symbolizer(err) => err.span(),
+            CompileError::IR(err) => err.span(),
+            CompileError::Checker(err) => err.span(),
+            CompileError::Evaluator(err) => err.span(),
+            CompileError::Generator(err) => err.span(),
+        }
+    }
+
+    pub fn print_error(&self, src: &SourceFile) {
+        match self {
+            CompileError::Lexer(err) => err.print_error(src),
+            CompileError::Parser(errors) => {
+                for err in errors.iter() {
+                    err.print_error(src);
+                }
+            },
+            CompileError::ModuleNotFound(path) => {
+                ErrorFormat::new(ErrorType::Error)
+                    .add_message(format!("Module '{}' not found!", path.data), Some(path.convert(())))
+                    .set_step("Parser".to_string()).print(src);
+            },
+            CompileError::Module(module, err) => err.print_error(module),
+            CompileError::Symbolizer(err) => err.print_error(src),
+            CompileError::IR(err) => err.print_error(src),
+            CompileError::Checker(err) => err.print_error(src),
+            CompileError::Evaluator(err) => err.print_error(src),
+            CompileError::Generator(err) => err.print_error(src),
+        }
+    }
+
+}
\ No newline at end of file
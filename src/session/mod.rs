@@ -0,0 +1,2 @@
+pub mod error;
+pub mod session;
\ No newline at end of file
@@ -0,0 +1,399 @@
+use crate::{util::{source_file::SourceFile, position::Positioned}, lexer::{tokens::Token, lexer::Lexer}, parser::{parser::Parser, node::Node, error::ParserError}, symbolizer::{symbolizer::Symbolizer, scope::SymbolTable}, ir::{ir::IRGenerator, output::IROutput}, checker::checker::Checker, evaluator::evaluator::ConstEvaluator, post_processor::post_processor::PostProcessor, generator::{generator::Generator, project::Project, library::Library}};
+
+use super::error::CompileError;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Stage                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stage {
+    Tokenize,
+    Parse,
+    Symbolize,
+    IRGenerate,
+    Check,
+    Evaluate,
+    PostProcess,
+    Generate
+}
+
+/// Result of the last stage run by [`Session::run`].
+#[derive(Debug)]
+pub enum Output {
+    Tokens(Vec<Positioned<Token>>),
+    Ast(Vec<Positioned<Node>>),
+    /// The symbols are in [`Session::scope`]
+    Symbols,
+    IR(IROutput),
+    Project(Project)
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Session                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Compiles one in-memory source file, each stage returning its output instead of printing it.
+pub struct Session {
+    source: SourceFile,
+    /// Modules imported with `use "module.taly"`, looked up before the file system
+    modules: Vec<SourceFile>,
+    /// Modules spliced in by the last parse, in the order of their position sources
+    imported: Vec<SourceFile>,
+    library: Option<Library>,
+    line_directives: bool,
+    table: SymbolTable
+}
+
+impl Session {
+
+    pub fn new(source: SourceFile) -> Self {
+        Self {
+            source,
+            modules: Vec::new(),
+            imported: Vec::new(),
+            library: None,
+            line_directives: false,
+            table: SymbolTable::default()
+        }
+    }
+
+    pub fn add_module(&mut self, module: SourceFile) {
+        self.modules.push(module);
+    }
+
+    pub fn set_library(&mut self, library: Option<Library>) {
+        self.library = library;
+    }
+
+    /// Maps the generated C back to the source for debuggers.
+    pub fn set_line_directives(&mut self, line_directives: bool) {
+        self.line_directives = line_directives;
+    }
+
+    pub fn source(&self) -> &SourceFile {
+        &self.source
+    }
+
+    pub fn scope(&self) -> &SymbolTable {
+        &self.table
+    }
+
+    fn tokenize_file(src: &SourceFile) -> Result<Vec<Positioned<Token>>, CompileError> {
+        let mut lexer = Lexer::new(&src.src);
+        lexer.tokenize().map_err(CompileError::Lexer)
+    }
+
+    fn parse_tokens(tokens: Vec<Positioned<Token>>) -> Result<Vec<Positioned<Node>>, CompileError> {
+        let mut parser = Parser::new(tokens);
+        parser.parse().map_err(CompileError::Parser)
+    }
+
+    /// `use "module.taly"` resolves to an added module whose path is `module.taly` or
+    /// `res/module.taly`, else to the file `res/module.taly`.
+    fn find_module(&self, path: &Positioned<String>) -> Result<SourceFile, CompileError> {
+        let file_path = format!("res/{}", path.data);
+        if let Some(module) = self.modules.iter().find(|module| module.path == path.data || module.path == file_path) {
+            return Ok(module.clone());
+        }
+
+        match std::fs::read_to_string(&file_path) {
+            Ok(src) => Ok(SourceFile::new(file_path, src)),
+            Err(_) => Err(CompileError::ModuleNotFound(Box::new(path.clone())))
+        }
+    }
+
+    /// Marks a top-level definition as coming from `module`, unless it comes from a module that
+    /// one imports.
+    fn set_module(node: &mut Positioned<Node>, path: &str) {
+        match &mut node.data {
+            Node::FunctionDefinition { module, .. } |
+            Node::VariableDefinition { module, .. } |
+            Node::ClassDefinition { module, .. } |
+            Node::SpaceDefinition { module, .. } |
+            Node::InterfaceDefinition { module, .. } if module.is_none() => *module = Some(path.to_string()),
+            _ => {}
+        }
+    }
+
+    /// Splices the modules imported with `use "module.taly"` in place of the use, each definition
+    /// keeping the module it comes from.
+    fn import_modules(&mut self, ast: Vec<Positioned<Node>>) -> Result<Vec<Positioned<Node>>, CompileError> {
+        let mut nodes = Vec::new();
+        for node in ast {
+            if let Node::Use(path) = &node.data {
+                if path.data.ends_with(".taly") {
+                    let module = self.find_module(path)?;
+                    if !self.imported.iter().any(|imported| imported.path == module.path) {
+                        self.imported.push(module.clone());
+                        let source = self.imported.len();
+                        let ast = Self::tokenize_file(&module)
+                            .map(|mut tokens| {
+                                for token in tokens.iter_mut() {
+                                    token.start.source = source;
+                                    token.end.source = source;
+                                }
+                                tokens
+                            })
+                            .and_then(Self::parse_tokens)
+                            .map_err(|err| CompileError::Module(module.clone(), Box::new(err)))?;
+                        for mut node in self.import_modules(ast)? {
+                            Self::set_module(&mut node, &path.data);
+                            nodes.push(node);
+                        }
+                    }
+                    continue;
+                }
+            }
+            nodes.push(node);
+        }
+        Ok(nodes)
+    }
+
+    /// Parses the tokens of the source alone, broken statements are replaced by error nodes. The AST
+    /// can go through the stages up to the post processor, only `parse` gives one to generate.
+    pub fn parse_partial(&self, tokens: Vec<Positioned<Token>>) -> (Vec<Positioned<Node>>, Vec<ParserError>) {
+        let mut parser = Parser::new(tokens);
+        parser.parse_partial()
+    }
+
+    pub fn tokenize(&self) -> Result<Vec<Positioned<Token>>, CompileError> {
+        Self::tokenize_file(&self.source)
+    }
+
+    /// Parses the tokens of the source, the imported modules included.
+    pub fn parse(&mut self, tokens: Vec<Positioned<Token>>) -> Result<Vec<Positioned<Node>>, CompileError> {
+        let ast = Self::parse_tokens(tokens)?;
+        self.imported = Vec::new();
+        self.import_modules(ast)
+    }
+
+    /// Errors of the stages after the parser point in the imported module of their span, if any.
+    fn locate(&self, err: CompileError) -> CompileError {
+        match err.span().map(|span| span.start.source) {
+            Some(source) if source > 0 => CompileError::Module(self.imported[source - 1].clone(), Box::new(err)),
+            _ => err
+        }
+    }
+
+    /// Fills the scope of the session, replacing the symbols of a previous run. Returns the AST with
+    /// the symbol of each definition, to give to the IR Generator.
+    pub fn symbolize(&mut self, ast: Vec<Positioned<Node>>) -> Result<Vec<Positioned<Node>>, CompileError> {
+        self.table = SymbolTable::default();
+        let mut symbolizer = Symbolizer::new(ast, &mut self.table);
+        symbolizer.symbolize(SymbolTable::ROOT).map_err(|err| self.locate(CompileError::Symbolizer(Box::new(err))))
+    }
+
+    pub fn ir_generate(&mut self, ast: Vec<Positioned<Node>>) -> Result<IROutput, CompileError> {
+        let mut ir = IRGenerator::new(ast, &mut self.table);
+        ir.generate().map_err(|err| self.locate(CompileError::IR(Box::new(err))))
+    }
+
+    pub fn check(&mut self, ir_output: IROutput) -> Result<IROutput, CompileError> {
+        let mut checker = Checker::new(ir_output, &mut self.table);
+        checker.check().map_err(|err| self.locate(CompileError::Checker(Box::new(err))))
+    }
+
+    pub fn evaluate(&self, ir_output: IROutput) -> Result<IROutput, CompileError> {
+        let mut evaluator = ConstEvaluator::new(ir_output);
+        evaluator.evaluate().map_err(|err| self.locate(CompileError::Evaluator(err)))
+    }
+
+    pub fn post_process(&self, ir_output: IROutput) -> IROutput {
+        let mut post_processor = PostProcessor::new(ir_output);
+        post_processor.process()
+    }
+
+    pub fn generate(&self, ir_output: IROutput) -> Result<Project, CompileError> {
+        let source = self.line_directives.then(|| self.source.path.clone());
+        let mut generator = Generator::new(ir_output, self.library.clone(), source);
+        generator.generate().map_err(|err| self.locate(CompileError::Generator(err)))
+    }
+
+    /// Runs the pipeline up to `stage` included.
+    pub fn run(&mut self, stage: Stage) -> Result<Output, CompileError> {
+        let tokens = self.tokenize()?;
+        if stage == Stage::Tokenize {
+            return Ok(Output::Tokens(tokens));
+        }
+
+        let ast = self.parse(tokens)?;
+        if stage == Stage::Parse {
+            return Ok(Output::Ast(ast));
+        }
+
+        let ast = self.symbolize(ast)?;
+        if stage == Stage::Symbolize {
+            return Ok(Output::Symbols);
+        }
+
+        let mut ir_output = self.ir_generate(ast)?;
+        for (current, next) in [(Stage::IRGenerate, Stage::Check), (Stage::Check, Stage::Evaluate), (Stage::Evaluate, Stage::PostProcess), (Stage::PostProcess, Stage::Generate)] {
+            if stage == current {
+                return Ok(Output::IR(ir_output));
+            }
+            ir_output = match next {
+                Stage::Check => self.check(ir_output)?,
+                Stage::Evaluate => self.evaluate(ir_output)?,
+                Stage::PostProcess => self.post_process(ir_output),
+                _ => return self.generate(ir_output).map(Output::Project)
+            };
+        }
+
+        unreachable!()
+    }
+
+    /// Runs the whole pipeline.
+    pub fn compile(&mut self) -> Result<Project, CompileError> {
+        match self.run(Stage::Generate)? {
+            Output::Project(project) => Ok(project),
+            _ => unreachable!()
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{util::source_file::SourceFile, session::error::CompileError, parser::node::Node, checker::error::CheckerError, symbolizer::scope::ScopeType, generator::library::{Library, LibraryKind}};
+
+    use super::{Session, Stage, Output};
+
+    const MAIN: &str = "extern fn printf(msg: c_string)\n\nfn main() =>\n    printf(\"Hello\")\n";
+
+    #[test]
+    fn run_stops_at_stage() {
+        let mut session = Session::new(SourceFile::new("main.taly".to_string(), MAIN.to_string()));
+        assert!(matches!(session.run(Stage::Tokenize), Ok(Output::Tokens(_))));
+        assert!(matches!(session.run(Stage::Parse), Ok(Output::Ast(_))));
+        assert!(matches!(session.run(Stage::Check), Ok(Output::IR(_))));
+    }
+
+    #[test]
+    fn compile_in_memory_module() {
+        let mut session = Session::new(SourceFile::new("main.taly".to_string(), "use \"print.taly\"\n\nfn main() =>\n    printf(\"Hello\")\n".to_string()));
+        session.add_module(SourceFile::new("print.taly".to_string(), "extern fn printf(msg: c_string)\n".to_string()));
+        let project = session.compile().unwrap_or_else(|_| panic!("Failed to compile"));
+        assert!(project.files.iter().any(|file| file.src.contains("printf(\"Hello\")")));
+    }
+
+    #[test]
+    fn modules_get_their_own_file() {
+        let mut session = Session::new(SourceFile::new("main.taly".to_string(), "use \"util/strings.taly\"\n\nfn main() =>\n    shout(\"Hello\")\n".to_string()));
+        session.add_module(SourceFile::new("util/strings.taly".to_string(), "extern fn puts(msg: c_string)\n\nvar count: c_int = 0\n\nfn shout(msg: c_string) =>\n    count += 1\n    puts(msg)\n".to_string()));
+        let project = session.compile().unwrap_or_else(|_| panic!("Failed to compile"));
+        let module = project.files.iter().find(|file| file.name == "util_strings").unwrap_or_else(|| panic!("No file for the module"));
+        assert!(module.src.contains("void shout(const char* msg)"));
+        assert!(module.src.contains("int count = 0;"));
+        let main = project.files.iter().find(|file| file.name == "main").unwrap_or_else(|| panic!("No main file"));
+        assert!(main.src.contains("#include \"util_strings.h\""));
+        assert!(!main.src.contains("void shout"));
+    }
+
+    #[test]
+    fn externs_have_no_file() {
+        let mut session = Session::new(SourceFile::new("main.taly".to_string(), "extern class FILE\nextern struct point\n    var x: c_int\n\nfn main(): c_int =>\n    return 0\n".to_string()));
+        let project = session.compile().unwrap_or_else(|_| panic!("Failed to compile"));
+        assert_eq!(project.files.iter().map(|file| file.name.as_str()).collect::<Vec<_>>(), vec!["main"]);
+    }
+
+    #[test]
+    fn parse_partial_keeps_valid_statements() {
+        let session = Session::new(SourceFile::new("main.taly".to_string(), "fn broken(a) =>\n    return 0\n\nfn main() =>\n    printf(\"a\" 1)\n    printf(\"b\")\n".to_string()));
+        let (ast, errors) = session.parse_partial(session.tokenize().unwrap_or_else(|_| panic!("Failed to tokenize")));
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(ast[0].data, Node::Error));
+        let Node::FunctionDefinition { body, .. } = &ast[1].data else {
+            panic!("Expected a function");
+        };
+        assert!(matches!(body[0].data, Node::Error));
+        assert!(matches!(body[1].data, Node::FunctionCall { .. }));
+    }
+
+    #[test]
+    fn partial_parse_goes_through_post_processing() {
+        let mut session = Session::new(SourceFile::new("main.taly".to_string(), MAIN.replace("printf(\"Hello\")", "printf(\"a\" 1)\n    printf(\"b\")")));
+        let (ast, errors) = session.parse_partial(session.tokenize().unwrap_or_else(|_| panic!("Failed to tokenize")));
+        assert_eq!(errors.len(), 1);
+        let ast = session.symbolize(ast).unwrap_or_else(|_| panic!("Failed to symbolize"));
+        let ir_output = session.ir_generate(ast).unwrap_or_else(|_| panic!("Failed to generate the IR"));
+        let ir_output = session.check(ir_output).unwrap_or_else(|_| panic!("Failed to check"));
+        let ir_output = session.post_process(ir_output);
+        assert!(ir_output.ast.iter().any(|node| matches!(&node.data, Node::FunctionDefinition { body, .. } if body.iter().any(|node| matches!(node.data, Node::Error)))));
+    }
+
+    #[test]
+    fn use_nodes_get_their_symbol() {
+        let mut session = Session::new(SourceFile::new("main.taly".to_string(), "extern fn printf(msg: c_string)\n\nclass Text\n\n    pub var c_str: c_string\n\n    pub new create(c_str: c_string) =>\n        self.c_str = c_str\n\nfn main() =>\n    const text: Text = Text.create(\"Hello\")\n    printf(text.c_str)\n".to_string()));
+        let Ok(Output::IR(ir_output)) = session.run(Stage::Check) else {
+            panic!("Failed to check");
+        };
+        let Some(Node::_Renamed { node: main, .. }) = ir_output.ast.last().map(|node| &node.data) else {
+            panic!("Expected the main function");
+        };
+        let Node::FunctionDefinition { body, .. } = &main.data else {
+            panic!("Expected a function");
+        };
+        let Node::_Renamed { node: call, .. } = &body[1].data else {
+            panic!("Expected a call");
+        };
+        let Node::FunctionCall { parameters, scope: Some(function), .. } = &call.data else {
+            panic!("Expected a resolved call");
+        };
+        let Node::BinaryOperation { rhs, .. } = &parameters[0].data else {
+            panic!("Expected an access");
+        };
+        let Node::VariableCall { scope: Some(field), .. } = &rhs.data else {
+            panic!("Expected a resolved field");
+        };
+        assert!(matches!(&session.table.get(*function).scope, ScopeType::Function { name, .. } if name.data == "printf"));
+        assert!(session.table.is_field(*field));
+    }
+
+    #[test]
+    fn symbol_not_found_suggestion() {
+        let mut session = Session::new(SourceFile::new("main.taly".to_string(), MAIN.replace("printf(\"", "prinft(\"")));
+        let Err(CompileError::Checker(err)) = session.compile() else {
+            panic!("Expected a checker error");
+        };
+        let CheckerError::SymbolNotFound(_, suggestion) = *err else {
+            panic!("Expected SymbolNotFound");
+        };
+        assert_eq!(suggestion, Some("printf".to_string()));
+    }
+
+    #[test]
+    fn library_symbols_named_like_a_member() {
+        let mut session = Session::new(SourceFile::new("shapes.taly".to_string(), "pub class Square\n\n    pub var side: c_int\n\n    pub new create(side: c_int) =>\n        self.side = side\n\npub fn Square_create(): c_int => 0\n".to_string()));
+        session.set_library(Some(Library::new("shapes".to_string(), Some("mylib".to_string()), LibraryKind::Static)));
+        let Err(err) = session.compile() else {
+            panic!("Expected a name conflict");
+        };
+        assert_eq!(err.kind(), "Generator::NameConflict");
+        assert_eq!(err.span().map(|span| span.start.line), Some(8));
+    }
+
+    #[test]
+    fn missing_module() {
+        let mut session = Session::new(SourceFile::new("main.taly".to_string(), "use \"missing.taly\"\n".to_string()));
+        assert!(matches!(session.run(Stage::Parse), Err(CompileError::ModuleNotFound(_))));
+    }
+
+    #[test]
+    fn module_errors_point_in_the_module() {
+        for (module, kind, line) in [("fn twice() =>\n    return\n\nfn twice() =>\n    return\n", "Symbolizer::SymbolAlreadyDefined", 4), ("fn broken(): c_int =>\n    return true\n", "Checker::UnexpectedType", 2)] {
+            let mut session = Session::new(SourceFile::new("main.taly".to_string(), "use \"module.taly\"\n\nfn main() =>\n    return\n".to_string()));
+            session.add_module(SourceFile::new("module.taly".to_string(), module.to_string()));
+            let Err(err) = session.compile() else {
+                panic!("Expected an error in the module");
+            };
+            assert_eq!(err.kind(), kind);
+            assert!(matches!(&err, CompileError::Module(source, _) if source.path == "module.taly"));
+            assert_eq!(err.span().map(|span| span.start.line), Some(line));
+        }
+    }
+
+}
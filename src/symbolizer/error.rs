@@ -5,12 +5,28 @@
 use crate::util::{position::Positioned, source_file::SourceFile, error::{ErrorFormat, ErrorType}};
 
 pub enum SymbolizerError {
-    SymbolAlreadyDefined(Positioned<String>, Positioned<()>),
+    SymbolAlreadyDefined(Box<Positioned<String>>, Box<Positioned<()>>),
     SymbolNotFound(Positioned<String>)
 }
 
 impl SymbolizerError {
 
+    /// Name of the variant, used by the diagnostic tests.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SymbolizerError::SymbolAlreadyDefined(..) => "SymbolAlreadyDefined",
+            SymbolizerError::SymbolNotFound(..) => "SymbolNotFound",
+        }
+    }
+
+    /// Span of the main message of the error.
+    pub fn span(&self) -> Option<Positioned<()>> {
+        match self {
+            SymbolizerError::SymbolAlreadyDefined(symbol, _) => Some(symbol.convert(())),
+            SymbolizerError::SymbolNotFound(symbol) => Some(symbol.convert(())),
+        }
+    }
+
     pub fn print_error(&self, src: &SourceFile) {
         match self {
             SymbolizerError::SymbolAlreadyDefined(symbol, here) => {
@@ -1,4 +1,3 @@
 pub mod scope;
 pub mod symbolizer;
 pub mod error;
-pub mod trace;
\ No newline at end of file
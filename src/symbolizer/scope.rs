@@ -1,4 +1,6 @@
-use crate::{util::{reference::MutRef, position::{Positioned, Position}}, parser::node::{FunctionDefinitionParameter, VarType, AccessModifier, DataType}, symbolizer::trace::Trace};
+use std::fmt::Debug;
+
+use crate::{util::{position::{Positioned, Position}, mangle, id::{ScopeId, SymbolId}}, parser::node::{FunctionDefinitionParameter, VarType, AccessModifier, DataType, External}};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                             Scoped                                             //
@@ -7,7 +9,20 @@ use crate::{util::{reference::MutRef, position::{Positioned, Position}}, parser:
 #[derive(Clone, Debug)]
 pub struct Scoped<T> {
     pub data: T,
-    pub scope: Option<MutRef<Scope>>
+    pub scope: Option<SymbolId>
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Visibility                                           //
+//////////////////////////////////////////////////////////////////////////////////////////////////// 
+
+/// Symbols a lookup can find.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    /// Every symbol, wherever it is defined
+    All,
+    /// Symbols whose definition was reached, so they are used after being defined
+    Declared
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -17,14 +32,14 @@ pub struct Scoped<T> {
 #[derive(Clone, Debug)]
 pub enum ScopeType {
     Root {
-        children: Vec<Scope>,
+        children: Vec<ScopeId>,
     },
     Function {
         name: Positioned<String>,
         params: Vec<FunctionDefinitionParameter>,
-        children: Vec<Scope>,
+        children: Vec<ScopeId>,
         return_type: Option<Scoped<Positioned<DataType>>>,
-        external: bool,
+        external: Option<External>,
         constructor: bool,
         implementation: bool
     },
@@ -33,26 +48,28 @@ pub enum ScopeType {
         name: Positioned<String>,
         data_type: Option<Scoped<Positioned<DataType>>>,
         initialized: bool,
+        external: Option<External>
     },
     Class {
         name: Positioned<String>,
-        children: Vec<Scope>,
+        children: Vec<ScopeId>,
         linked_space: bool,
-        extensions: Vec<MutRef<Scope>>
+        extensions: Vec<SymbolId>,
+        external: Option<External>
     },
     Space {
         name: Positioned<String>,
-        children: Vec<Scope>,
+        children: Vec<ScopeId>,
         linked_class: bool
     },
     Interface {
         name: Positioned<String>,
-        children: Vec<Scope>,
+        children: Vec<ScopeId>,
     },
     Branch {
         label: Option<Positioned<String>>,
         debug_name: String,
-        children: Vec<Scope>
+        children: Vec<ScopeId>
     }
 }
 
@@ -60,31 +77,22 @@ pub enum ScopeType {
 pub struct Scope {
     pub pos: Positioned<()>,
     pub scope: ScopeType,
-    pub parent: Option<MutRef<Scope>>,
-    pub trace: Trace,
+    /// Set when the scope is added to the table
+    pub parent: Option<ScopeId>,
+    /// Symbols of the source are declared when the checker reaches their definition, generated ones
+    /// from the start
+    pub declared: bool,
     pub access: Option<Positioned<AccessModifier>>
 }
 
 impl Scope {
 
-    pub fn root() -> Self {
-        Self {
-            pos: Positioned::new((), Position::default(), Position::default()),
-            scope: ScopeType::Root { 
-                children: Vec::new()
-            },
-            parent: None,
-            trace: Trace::default(),
-            access: None
-        }
-    }
-
-    pub fn new(pos: Positioned<()>, scope: ScopeType, parent: Option<MutRef<Scope>>, trace: Trace, access: Option<Positioned<AccessModifier>>) -> Self {
+    pub fn new(pos: Positioned<()>, scope: ScopeType, access: Option<Positioned<AccessModifier>>) -> Self {
         Self {
             pos,
             scope,
-            parent,
-            trace,
+            parent: None,
+            declared: true,
             access
         }
     }
@@ -101,25 +109,16 @@ impl Scope {
         }
     }
 
-    pub fn process_name(&mut self) -> String {
-        let mut buf = String::new();
-        if let Some(parent) = &self.parent {
-            buf.push_str(&parent.get().process_name());
-            if !self.is_root() && !parent.get().is_root() && !self.is_branch() && !parent.get().is_branch() {
-                buf.push('_');
-            }
-        } 
+    pub fn children(&self) -> &[ScopeId] {
         match &self.scope {
-            ScopeType::Function { name, implementation, .. } if *implementation => buf.push_str(&format!("{}_impl", name.data)),
-            ScopeType::Function { name, .. } => buf.push_str(&name.data),
-            ScopeType::Variable { name, .. } => return name.data.clone(),
-            ScopeType::Class { name, .. } => buf.push_str(&name.data),
-            ScopeType::Space { name, .. } => buf.push_str(&name.data),
-            ScopeType::Interface { name, .. } => buf.push_str(&name.data),
-            _ => {}
+            ScopeType::Root { children } |
+            ScopeType::Function { children, .. } |
+            ScopeType::Class { children, .. } |
+            ScopeType::Space { children, .. } |
+            ScopeType::Branch { children, .. } |
+            ScopeType::Interface { children, .. } => children,
+            ScopeType::Variable { .. } => &[]
         }
-
-        buf
     }
 
     pub fn is_root(&self) -> bool {
@@ -143,282 +142,381 @@ impl Scope {
         }
     }
 
-    pub fn add_child(&mut self, scope: Scope) {
-        match &mut self.scope {
-            ScopeType::Root { children } |
-            ScopeType::Function { children, .. } |
-            ScopeType::Class { children, .. } |
-            ScopeType::Space { children, .. } |
-            ScopeType::Branch { children, .. } |
-            ScopeType::Interface { children, .. } => {
-                children.push(scope);
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                          Symbol Table                                          //
+//////////////////////////////////////////////////////////////////////////////////////////////////// 
+
+/// Every scope of the program, referring to each other by id. Scopes are never removed so the ids
+/// stay valid while children are added.
+#[derive(Clone)]
+pub struct SymbolTable {
+    scopes: Vec<Scope>
+}
+
+impl Default for SymbolTable {
+
+    fn default() -> Self {
+        Self {
+            scopes: vec![Scope::new(Positioned::new((), Position::default(), Position::default()), ScopeType::Root { 
+                children: Vec::new()
+            }, None)]
+        }
+    }
+
+}
+
+/// Scopes listed by id, children refer to their id.
+impl Debug for SymbolTable {
+
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.scopes.iter().enumerate().map(|(id, scope)| (ScopeId(id), scope))).finish()
+    }
+
+}
+
+impl SymbolTable {
+
+    pub const ROOT: ScopeId = ScopeId(0);
+
+    pub fn get(&self, id: impl Into<ScopeId>) -> &Scope {
+        &self.scopes[id.into().0]
+    }
+
+    pub fn get_mut(&mut self, id: impl Into<ScopeId>) -> &mut Scope {
+        &mut self.scopes[id.into().0]
+    }
+
+    pub fn parent(&self, id: impl Into<ScopeId>) -> Option<ScopeId> {
+        self.get(id).parent
+    }
+
+    /// The symbol becomes visible to [`Visibility::Declared`] lookups.
+    pub fn declare(&mut self, id: SymbolId) {
+        self.get_mut(id).declared = true;
+    }
+
+    /// Whether `id` is `ancestor` or one of its descendants.
+    pub fn is_inside(&self, id: ScopeId, ancestor: ScopeId) -> bool {
+        let mut current = Some(id);
+        while let Some(scope) = current {
+            if scope == ancestor {
+                return true;
             }
-            _ => {
-                panic!("cannot add child here!")
-            },
+            current = self.parent(scope);
         }
+        false
     }
 
-    pub fn get_last(&mut self) -> MutRef<Scope> {
-        match &mut self.scope {
+    /// Adds the scope to the children of `parent`, returns its id.
+    pub fn add_child(&mut self, parent: ScopeId, mut scope: Scope) -> ScopeId {
+        let id = ScopeId(self.scopes.len());
+        scope.parent = Some(parent);
+        match &mut self.get_mut(parent).scope {
             ScopeType::Root { children } |
             ScopeType::Function { children, .. } |
             ScopeType::Class { children, .. } |
             ScopeType::Space { children, .. } |
             ScopeType::Branch { children, .. } |
-            ScopeType::Interface { children, .. } => {
-                MutRef::new(children.last_mut().unwrap())
-            }
+            ScopeType::Interface { children, .. } => children.push(id),
             _ => {
-                panic!("cannot have children here!")
-            }
+                panic!("cannot add child here!")
+            },
         }
+        self.scopes.push(scope);
+        id
     }
 
-    pub fn get_child(&mut self, index: usize) -> MutRef<Scope> {
-        match &mut self.scope {
-            ScopeType::Root { children } |
-            ScopeType::Function { children, .. } |
-            ScopeType::Class { children, .. } |
-            ScopeType::Space { children, .. } |
-            ScopeType::Branch { children, .. } |
-            ScopeType::Interface { children, .. } => {
-                for child in children {
-                    if child.trace.index == index {
-                        return MutRef::new(child);
+    /// Moves an existing scope to the children of `parent`.
+    pub fn move_child(&mut self, id: ScopeId, parent: ScopeId) {
+        for owner in self.get(id).parent.into_iter().chain([parent]) {
+            match &mut self.get_mut(owner).scope {
+                ScopeType::Root { children } |
+                ScopeType::Function { children, .. } |
+                ScopeType::Class { children, .. } |
+                ScopeType::Space { children, .. } |
+                ScopeType::Branch { children, .. } |
+                ScopeType::Interface { children, .. } => {
+                    if owner == parent {
+                        children.push(id);
+                    } else {
+                        children.retain(|child| *child != id);
                     }
-                }
-                panic!("Could not find child '{}'", index);
-            }
-            _ => {
-                panic!("cannot have children here!")
+                },
+                _ => {
+                    panic!("cannot add child here!")
+                },
             }
         }
-    } 
+        self.get_mut(id).parent = Some(parent);
+    }
 
-    fn get_function_in_children(children: &mut Vec<Scope>, trace: Trace, name: String) -> Option<MutRef<Scope>> {
-        for child in children.iter_mut() {
-            if let ScopeType::Function { name: var_name, .. } = &child.scope {
-                if var_name.data == name && (trace.full || child.trace.index <= trace.index) {
-                    return Some(MutRef::new(child));
-                }
-            }
+    /// Adds the scope of a named symbol to the children of `parent`.
+    pub fn add_symbol(&mut self, parent: ScopeId, scope: Scope) -> SymbolId {
+        SymbolId(self.add_child(parent, scope).0)
+    }
+
+    /// Symbol of the scope, none for the root and the branches.
+    pub fn symbol(&self, id: ScopeId) -> Option<SymbolId> {
+        (!matches!(self.get(id).scope, ScopeType::Root { .. } | ScopeType::Branch { .. })).then_some(SymbolId(id.0))
+    }
+
+    pub fn process_name(&self, id: impl Into<ScopeId>) -> String {
+        let id = id.into();
+        let scope = self.get(id);
+        let parent = match scope.parent {
+            Some(parent) => self.process_name(parent),
+            None => String::new()
+        };
+        let mangled = |name: &str| if parent.is_empty() {
+            mangle::escape(name)
+        } else {
+            mangle::member(&parent, name)
+        };
+
+        match &scope.scope {
+            ScopeType::Function { external: Some(External { symbol: Some(symbol), .. }), .. } => symbol.data.clone(),
+            ScopeType::Function { external: Some(_), name, .. } => name.data.clone(),
+            ScopeType::Function { name, implementation, .. } if *implementation => mangle::implementation(&mangled(&name.data)),
+            ScopeType::Function { name, .. } => mangled(&name.data),
+            ScopeType::Variable { external: Some(External { symbol: Some(symbol), .. }), .. } => symbol.data.clone(),
+            ScopeType::Variable { external: Some(_), name, .. } => name.data.clone(),
+            // Globals are namespaced like functions
+            ScopeType::Variable { name, .. } if self.is_global(id) => mangled(&name.data),
+            // Fields are only accessed through their structure
+            ScopeType::Variable { name, .. } if self.is_field(id) => mangle::ascii(&name.data),
+            ScopeType::Variable { name, .. } => mangle::escape(&name.data),
+            ScopeType::Class { external: Some(External { symbol: Some(symbol), .. }), .. } => symbol.data.clone(),
+            ScopeType::Class { external: Some(_), name, .. } => name.data.clone(),
+            ScopeType::Class { name, .. } => mangled(&name.data),
+            ScopeType::Space { name, .. } => mangled(&name.data),
+            ScopeType::Interface { name, .. } => mangled(&name.data),
+            // Branches are transparent
+            ScopeType::Root { .. } |
+            ScopeType::Branch { .. } => parent
         }
-        None
     }
 
-    fn get_constructor_in_children(children: &mut Vec<Scope>, trace: Trace, name: String) -> Option<MutRef<Scope>> {
-        for child in children.iter_mut() {
-            if let ScopeType::Function { name: var_name, constructor, .. } = &child.scope {
-                if *constructor && var_name.data == name && (trace.full || child.trace.index <= trace.index) {
-                    return Some(MutRef::new(child));
-                }
+    /// Variables defined at module or space level.
+    pub fn is_global(&self, id: impl Into<ScopeId>) -> bool {
+        let id = id.into();
+        self.get(id).is_variable() && self.parent(id).is_some_and(|parent| matches!(self.get(parent).scope, ScopeType::Root { .. } | ScopeType::Space { .. }))
+    }
+
+    pub fn is_field(&self, id: impl Into<ScopeId>) -> bool {
+        let id = id.into();
+        self.get(id).is_variable() && self.parent(id).is_some_and(|parent| matches!(self.get(parent).scope, ScopeType::Class { .. }))
+    }
+
+    /// Names of the symbols reachable from this scope, the members of its class for a variable.
+    pub fn visible_names(&self, id: impl Into<ScopeId>) -> Vec<String> {
+        let scope = self.get(id);
+        if let ScopeType::Variable { data_type: Some(Scoped { scope: Some(class), .. }), .. } = &scope.scope {
+            return self.visible_names(*class);
+        }
+
+        let mut names = Vec::new();
+        for child in scope.children() {
+            match &self.get(*child).scope {
+                ScopeType::Function { name, .. } |
+                ScopeType::Variable { name, .. } |
+                ScopeType::Class { name, .. } |
+                ScopeType::Space { name, .. } |
+                ScopeType::Interface { name, .. } => names.push(name.data.clone()),
+                _ => {}
             }
         }
-        None
+        if let Some(parent) = scope.parent {
+            names.append(&mut self.visible_names(parent));
+        }
+        names
+    }
+
+    pub fn get_last(&self, id: ScopeId) -> ScopeId {
+        match self.get(id).children().last() {
+            Some(last) => *last,
+            None => panic!("cannot have children here!")
+        }
+    }
+
+    /// First visible child of `id` matching the filter.
+    fn find_child(&self, id: ScopeId, visibility: Visibility, filter: impl Fn(&ScopeType) -> bool) -> Option<SymbolId> {
+        self.get(id).children().iter().copied().find(|child| {
+            let child = self.get(*child);
+            filter(&child.scope) && (visibility == Visibility::All || child.declared)
+        }).map(|child| SymbolId(child.0))
+    }
+
+    fn get_function_in_children(&self, id: ScopeId, visibility: Visibility, name: &str) -> Option<SymbolId> {
+        self.find_child(id, visibility, |scope| matches!(scope, ScopeType::Function { name: fun_name, .. } if fun_name.data == name))
     }
 
-    pub fn enter_function(&mut self, trace: Trace, name: String, look_links: bool, allow_fields: bool) -> Option<MutRef<Scope>> {
-        let space_to_check;
+    fn get_constructor_in_children(&self, id: ScopeId, visibility: Visibility, name: &str) -> Option<SymbolId> {
+        self.find_child(id, visibility, |scope| matches!(scope, ScopeType::Function { name: fun_name, constructor: true, .. } if fun_name.data == name))
+    }
 
-        match &mut self.scope {
-            ScopeType::Root { children } => return Self::get_function_in_children(children, trace, name),
+    pub fn enter_function(&self, id: ScopeId, visibility: Visibility, name: String, look_links: bool, allow_fields: bool) -> Option<SymbolId> {
+        match &self.get(id).scope {
+            ScopeType::Root { .. } => self.get_function_in_children(id, visibility, &name),
             ScopeType::Variable { data_type, .. } => {
-                if let Some(data_type) = data_type.clone() {
-                    if let Some(class) = data_type.scope {
-                        return class.get().enter_function(trace, name, look_links, true);
-                    } else {
-                        return None;
-                    }
-                } else {
-                    return None;
-                }
+                let class = data_type.as_ref()?.scope?;
+                self.enter_function(class.scope(), visibility, name, look_links, true)
             },
-            ScopeType::Class { name: class_name, children, linked_space, .. } => {
+            ScopeType::Class { name: class_name, linked_space, .. } => {
                 if allow_fields {
-                    return Self::get_function_in_children(children, trace, name);
+                    return self.get_function_in_children(id, visibility, &name);
                 } 
-                if let Some(constructor) = Self::get_constructor_in_children(children, trace.clone(), name.clone()) {
+                if let Some(constructor) = self.get_constructor_in_children(id, visibility, &name) {
                     return Some(constructor);
                 }
-                if linked_space.clone() && look_links {
-                    space_to_check = Some(class_name.clone());                        
+                if *linked_space && look_links {
+                    let space = self.get_space(id, visibility, class_name.data.clone())?;
+                    self.enter_function(space.scope(), visibility, name, false, false)
                 } else {
-                    return None;
+                    None
                 }
             },
-            ScopeType::Space { children, .. } => return Self::get_function_in_children(children, trace, name),
-            ScopeType::Interface { children, .. } if allow_fields => return Self::get_function_in_children(children, trace, name),
-            _ => return None
-        }
-
-        if let Some(linked_space) = space_to_check {
-            if let Some(space) = self.get_space(trace.clone(), linked_space.data.clone()) {
-                space.get().enter_function(trace, name, false, false)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    }
-    
-    pub fn get_function(&mut self, trace: Trace, name: String, allow_fields: bool) -> Option<MutRef<Scope>> {
-        if let Some(fun) = self.enter_function(trace.clone(), name.clone(), true, allow_fields) {
-            return Some(fun);
-        }
-        if let Some(parent) = &self.parent {
-            if trace.full {
-                return parent.get().get_function(trace, name, allow_fields);
-            } else {
-                return parent.get().get_function(*trace.parent.unwrap(), name, allow_fields);
-            }
+            ScopeType::Space { .. } => self.get_function_in_children(id, visibility, &name),
+            // Lambdas are symbolized inside their enclosing function
+            ScopeType::Function { .. } => self.get_function_in_children(id, visibility, &name),
+            ScopeType::Interface { .. } if allow_fields => self.get_function_in_children(id, visibility, &name),
+            _ => None
         }
-        None
     }
 
-    fn get_variable_in_children(children: &mut Vec<Scope>, trace: Trace, name: String) -> Option<MutRef<Scope>> {
-        for child in children.iter_mut() {
-            if let ScopeType::Variable { name: var_name, .. } = &child.scope {
-                if var_name.data == name && (trace.full || child.trace.index <= trace.index) {
-                    return Some(MutRef::new(child));
-                }
-            }
-        }
-        None
+    pub fn get_function(&self, id: ScopeId, visibility: Visibility, name: String, allow_fields: bool) -> Option<SymbolId> {
+        self.enter_function(id, visibility, name.clone(), true, allow_fields)
+            .or_else(|| self.get_function(self.parent(id)?, visibility, name, allow_fields))
     }
 
-    pub fn enter_variable(&mut self, trace: Trace, name: String, look_links: bool, allow_fields: bool) -> Option<MutRef<Scope>> {
-        match &mut self.scope {
-            ScopeType::Function { children, .. } => Self::get_variable_in_children(children, trace, name),
+    pub fn enter_variable(&self, id: ScopeId, visibility: Visibility, name: String, look_links: bool, allow_fields: bool) -> Option<SymbolId> {
+        let is_variable = |scope: &ScopeType| matches!(scope, ScopeType::Variable { name: var_name, .. } if var_name.data == name);
+        match &self.get(id).scope {
+            ScopeType::Root { .. } |
+            ScopeType::Function { .. } |
+            ScopeType::Space { .. } |
+            ScopeType::Branch { .. } => self.find_child(id, visibility, is_variable),
+            ScopeType::Class { .. } if allow_fields => self.find_child(id, visibility, is_variable),
             ScopeType::Variable { data_type, .. } => {
-                if let Some(data_type) = data_type.clone() {
-                    if let Some(class) = data_type.scope {
-                        class.get().enter_variable(trace, name, look_links, true)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
+                let class = data_type.as_ref()?.scope?;
+                self.enter_variable(class.scope(), visibility, name, look_links, true)
             },
-            ScopeType::Class { children, .. } if allow_fields => Self::get_variable_in_children(children, trace, name),
-            ScopeType::Branch { children, ..} => Self::get_variable_in_children(children, trace, name),
             _ => None
         }
     }
 
-    pub fn get_variable(&mut self, trace: Trace, name: String, allow_fields: bool) -> Option<MutRef<Scope>> {
-        if let Some(fun) = self.enter_variable(trace.clone(), name.clone(), true, allow_fields) {
-            return Some(fun);
-        }
-        if let Some(parent) = &self.parent {
-            if trace.full {
-                return parent.get().get_variable(trace, name, allow_fields);
-            } else {
-                return parent.get().get_variable(*trace.parent.unwrap(), name, allow_fields);
-            }
-        }
-        None
+    pub fn get_variable(&self, id: ScopeId, visibility: Visibility, name: String, allow_fields: bool) -> Option<SymbolId> {
+        self.enter_variable(id, visibility, name.clone(), true, allow_fields)
+            .or_else(|| self.get_variable(self.parent(id)?, visibility, name, allow_fields))
     }
 
-    pub fn enter_class(&mut self, trace: Trace, name: String) -> Option<MutRef<Scope>> {
-        match &mut self.scope {
-            ScopeType::Root { children } |
-            ScopeType::Function { children, .. } |
-            ScopeType::Class { children, .. } |
-            ScopeType::Space { children, .. } => {
-                for child in children.iter_mut() {
-                    if let ScopeType::Class { name: c_name, .. } = &child.scope {
-                        if c_name.data == name && (trace.full || child.trace.index <= trace.index) {
-                            return Some(MutRef::new(child));
-                        }
-                    }
-                }
-                None
-            },
+    /// Classes, spaces and interfaces are only defined in the root, functions, classes and spaces.
+    fn enter_type(&self, id: ScopeId, visibility: Visibility, filter: impl Fn(&ScopeType) -> bool) -> Option<SymbolId> {
+        match &self.get(id).scope {
+            ScopeType::Root { .. } |
+            ScopeType::Function { .. } |
+            ScopeType::Class { .. } |
+            ScopeType::Space { .. } => self.find_child(id, visibility, filter),
             _ => None,
         }
     }
 
-    pub fn get_class(&mut self, trace: Trace, name: String) -> Option<MutRef<Scope>> {
-        if let Some(fun) = self.enter_class(trace.clone(), name.clone()) {
-            return Some(fun);
-        }
-        if let Some(parent) = &self.parent {
-            if trace.full {
-                return parent.get().get_class(trace, name);
-            } else {
-                return parent.get().get_class(*trace.parent.unwrap(), name);
-            }
-        }
-        None
+    pub fn enter_class(&self, id: ScopeId, visibility: Visibility, name: String) -> Option<SymbolId> {
+        self.enter_type(id, visibility, |scope| matches!(scope, ScopeType::Class { name: c_name, .. } if c_name.data == name))
     }
 
-    pub fn enter_space(&mut self, trace: Trace, name: String) -> Option<MutRef<Scope>> {
-        match &mut self.scope {
-            ScopeType::Root { children } |
-            ScopeType::Function { children, .. } |
-            ScopeType::Class { children, .. } |
-            ScopeType::Space { children, .. } => {
-                for child in children.iter_mut() {
-                    if let ScopeType::Space { name: c_name, .. } = &child.scope {
-                        if c_name.data == name && (trace.full || child.trace.index <= trace.index) {
-                            return Some(MutRef::new(child));
-                        }
-                    }
-                }
-                None
-            },
-            _ => None,
-        }
+    pub fn get_class(&self, id: ScopeId, visibility: Visibility, name: String) -> Option<SymbolId> {
+        self.enter_class(id, visibility, name.clone())
+            .or_else(|| self.get_class(self.parent(id)?, visibility, name))
     }
 
-    pub fn get_space(&mut self, trace: Trace, name: String) -> Option<MutRef<Scope>> {
-        if let Some(fun) = self.enter_space(trace.clone(), name.clone()) {
-            return Some(fun);
-        }
-        if let Some(parent) = &self.parent {
-            if trace.full {
-                return parent.get().get_space(trace, name);
-            } else {
-                return parent.get().get_space(*trace.parent.unwrap(), name);
-            }
-        }
-        None
+    pub fn enter_space(&self, id: ScopeId, visibility: Visibility, name: String) -> Option<SymbolId> {
+        self.enter_type(id, visibility, |scope| matches!(scope, ScopeType::Space { name: c_name, .. } if c_name.data == name))
     }
 
-    pub fn enter_interface(&mut self, trace: Trace, name: String) -> Option<MutRef<Scope>> {
-        match &mut self.scope {
-            ScopeType::Root { children } |
-            ScopeType::Function { children, .. } |
-            ScopeType::Class { children, .. } |
-            ScopeType::Space { children, .. } => {
-                for child in children.iter_mut() {
-                    if let ScopeType::Interface { name: c_name, .. } = &child.scope {
-                        if c_name.data == name && (trace.full || child.trace.index <= trace.index) {
-                            return Some(MutRef::new(child));
-                        }
-                    }
-                }
-                None
-            },
-            _ => None,
-        }
+    pub fn get_space(&self, id: ScopeId, visibility: Visibility, name: String) -> Option<SymbolId> {
+        self.enter_space(id, visibility, name.clone())
+            .or_else(|| self.get_space(self.parent(id)?, visibility, name))
     }
 
-    pub fn get_interface(&mut self, trace: Trace, name: String) -> Option<MutRef<Scope>> {
-        if let Some(fun) = self.enter_interface(trace.clone(), name.clone()) {
-            return Some(fun);
-        }
-        if let Some(parent) = &self.parent {
-            if trace.full {
-                return parent.get().get_interface(trace, name);
-            } else {
-                return parent.get().get_interface(*trace.parent.unwrap(), name);
-            }
+    pub fn enter_interface(&self, id: ScopeId, visibility: Visibility, name: String) -> Option<SymbolId> {
+        self.enter_type(id, visibility, |scope| matches!(scope, ScopeType::Interface { name: c_name, .. } if c_name.data == name))
+    }
+
+    pub fn get_interface(&self, id: ScopeId, visibility: Visibility, name: String) -> Option<SymbolId> {
+        self.enter_interface(id, visibility, name.clone())
+            .or_else(|| self.get_interface(self.parent(id)?, visibility, name))
+    }
+
+}
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Tests                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos() -> Positioned<()> {
+        Positioned::new((), Position::default(), Position::default())
+    }
+
+    fn variable(name: &str) -> Scope {
+        Scope::new(pos(), ScopeType::Variable { 
+            var_type: pos().convert(VarType::Variable), 
+            name: pos().convert(name.to_string()), 
+            data_type: None, 
+            initialized: false, 
+            external: None 
+        }, None)
+    }
+
+    #[test]
+    fn ids_survive_new_children() {
+        let mut table = SymbolTable::default();
+        let function = table.add_symbol(SymbolTable::ROOT, Scope::new(pos(), ScopeType::Function { 
+            name: pos().convert("main".to_string()), 
+            params: Vec::new(), 
+            children: Vec::new(), 
+            return_type: None, 
+            external: None, 
+            constructor: false, 
+            implementation: false 
+        }, None));
+        let first = table.add_symbol(function.scope(), variable("first"));
+        for index in 0..64 {
+            table.add_symbol(function.scope(), variable(&format!("v{}", index)));
         }
-        None
+
+        assert_eq!(table.parent(first), Some(function.scope()));
+        assert_eq!(table.get_last(function.scope()), table.get_variable(function.scope(), Visibility::All, "v63".to_string(), false).unwrap().scope());
+        assert_eq!(table.get_variable(first.scope(), Visibility::All, "first".to_string(), false), Some(first));
+        assert_eq!(table.get_function(first.scope(), Visibility::All, "main".to_string(), false), Some(function));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn declared_lookups_skip_later_definitions() {
+        let mut table = SymbolTable::default();
+        let outer = table.add_symbol(SymbolTable::ROOT, variable("x"));
+        let branch = table.add_child(SymbolTable::ROOT, Scope::new(pos(), ScopeType::Branch { 
+            label: None, 
+            debug_name: "If".to_string(), 
+            children: Vec::new() 
+        }, None));
+        let inner = table.add_symbol(branch, variable("x"));
+        table.get_mut(inner).declared = false;
+
+        assert_eq!(table.get_variable(branch, Visibility::Declared, "x".to_string(), false), Some(outer));
+        assert_eq!(table.get_variable(branch, Visibility::All, "x".to_string(), false), Some(inner));
+        table.declare(inner);
+        assert_eq!(table.get_variable(branch, Visibility::Declared, "x".to_string(), false), Some(inner));
+        assert!(table.is_inside(inner.scope(), SymbolTable::ROOT) && !table.is_inside(outer.scope(), branch));
+        assert_eq!(table.symbol(outer.scope()), Some(outer));
+        assert_eq!(table.symbol(branch), None);
+    }
+}
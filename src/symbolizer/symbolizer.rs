@@ -1,422 +1,458 @@
-use crate::{symbolizer::{scope::{Scope, ScopeType, Scoped}, error::SymbolizerError, trace::Trace}, util::{reference::MutRef, position::{Positioned}}, parser::node::{Node, VarType, AccessModifier, DataType}};
-
-////////////////////////////////////////////////////////////////////////////////////////////////////
+use crate::{symbolizer::{scope::{Scope, ScopeType, Scoped, SymbolTable, Visibility}, error::SymbolizerError}, util::{position::Positioned, id::{ScopeId, SymbolId}}, parser::node::{Node, VarType, AccessModifier, DataType, ElifBranch, MatchBranch}};
+        
+//////////////////////////////////////////////////////////////////////////////////////////////////// 
 //                                            Symbolizer                                          //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
-
-pub struct Symbolizer {
+        
+/// Fills the symbol table and returns the AST with the symbol of each definition, so the next
+/// passes do not have to find them again.
+pub struct Symbolizer<'a> {
     ast: Vec<Positioned<Node>>,
     index: usize,
-    trace: Trace,
+    table: &'a mut SymbolTable
 }
-
-impl Symbolizer {
-
-    pub fn new(ast: Vec<Positioned<Node>>) -> Self {
+        
+impl<'a> Symbolizer<'a> {
+        
+    pub fn new(ast: Vec<Positioned<Node>>, table: &'a mut SymbolTable) -> Self {
         Self {
             ast,
             index: 0,
-            trace: Trace::default()
+            table
         }
     }
-
+        
     /* Cursor Movement */
     fn current(&self) -> Option<Positioned<Node>> {
         self.ast.get(self.index).cloned()
     }
-
+        
     fn advance(&mut self) {
         self.index += 1;
     }
-
+        
+    /* Useful functions */
+        
+    /// Definitions of the source are only visible once the checker reached them.
+    fn add_definition(&mut self, parent: ScopeId, mut scope: Scope) -> SymbolId {
+        scope.declared = false;
+        self.table.add_symbol(parent, scope)
+    }
+        
+    fn symbolize_body(&mut self, body: Vec<Positioned<Node>>, scope: ScopeId) -> Result<Vec<Positioned<Node>>, SymbolizerError> {
+        let mut new_body = Vec::new();
+        for node in body {
+            new_body.push(self.symbolize_node(node, scope)?);
+        }
+        Ok(new_body)
+    }
+        
+    fn symbolize_branch(&mut self, node: &Positioned<Node>, debug_name: &str, body: Vec<Positioned<Node>>, scope: ScopeId) -> Result<(ScopeId, Vec<Positioned<Node>>), SymbolizerError> {
+        let branch_scope = Scope::new(node.convert(()), ScopeType::Branch {
+            label: None,
+            debug_name: debug_name.to_string(),
+            children: Vec::new() 
+        }, Some(node.convert(AccessModifier::Public)));
+        
+        let branch = self.table.add_child(scope, branch_scope);
+        let body = self.symbolize_body(body, branch)?;
+        Ok((branch, body))
+    }
+        
     /* Symbolize */
-    fn symbolize_function_definition(&mut self, node: Positioned<Node>, scope: MutRef<Scope>) -> Result<(), SymbolizerError> {
-        let Node::FunctionDefinition { name, external, constructor, parameters, return_type, body, access } = node.data.clone() else {
+    fn symbolize_function_definition(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
+        let Node::FunctionDefinition { name, external, constructor, parameters, return_type, body, access, module, .. } = node.data.clone() else {
             unreachable!()
         };
-
-        let return_type_scoped = if let Some(return_type) = return_type {
+        
+        let return_type_scoped = if let Some(return_type) = return_type.clone() {
             Some(Scoped {
                 data: return_type.clone(), 
                 scope: match return_type.data {
-                    DataType::Custom(custom) => if let Some(return_type_scope) = scope.get().get_class(Trace::full(), custom) {
-                        Some(return_type_scope.clone())
+                    DataType::Custom(custom) => if let Some(return_type_scope) = self.table.get_class(scope, Visibility::All, custom) {
+                        Some(return_type_scope)
                     } else {
                         None
                     },
-                    DataType::Function { .. } => None,
+                    _ => None,
                 }
                 }
             )
         } else {
             None
         };
-
+        
         let function_scope = Scope::new(node.convert(()), ScopeType::Function { 
             name: name.clone(), 
             params: parameters.clone(), 
             children: Vec::new(), 
             return_type: return_type_scoped, 
-            external,
+            external: external.clone(),
             constructor,
             implementation: false
-        }, Some(scope.clone()), self.trace.clone(), access);
-
+        }, access.clone());
+        
         // Check if unique
-        if let Some(previous) = scope.get().enter_function(Trace::full(), name.data.clone(), true, true) {
-            return Err(SymbolizerError::SymbolAlreadyDefined(name, previous.get().pos.clone()));
+        if let Some(previous) = self.table.enter_function(scope, Visibility::All, name.data.clone(), true, true) {
+            return Err(SymbolizerError::SymbolAlreadyDefined(Box::new(name), Box::new(self.table.get(previous).pos.clone())));
         }
         
-        scope.get().add_child(function_scope);
-
+        let function_scope_ref = self.add_definition(scope, function_scope);
+        
         // Symbolize Params
-        let function_scope_ref = scope.get().get_last();
-        for param in parameters {
+        for param in parameters.iter() {
             let param_type_scoped = Some(Scoped {
                 data: param.data_type.clone(), 
                 scope: match param.data_type.data.clone() {
-                    DataType::Custom(inner) => if let Some(param_type_scope) = scope.get().get_class(Trace::full(), inner) {
-                        Some(param_type_scope.clone())
+                    DataType::Custom(inner) => if let Some(param_type_scope) = self.table.get_class(scope, Visibility::All, inner) {
+                        Some(param_type_scope)
                     } else {
                         None
                     },
-                    DataType::Function { .. } => None
+                    _ => None
                 }
             });
-
+        
+            // Parameters are declared for the whole body
             let param_scope = Scope::new(param.get_position(), ScopeType::Variable { 
                 var_type: param.get_position().convert(VarType::Constant), 
                 name: param.name.clone(), 
                 data_type: param_type_scoped, 
-                initialized: true 
-            }, Some(function_scope_ref.clone()), Trace::default(), None);
-
+                initialized: true,
+                external: None
+            }, None);
+        
             // Check if unique
-            if let Some(previous) = scope.get().enter_variable(Trace::full(), param.name.data.clone(), true, false) {
-                return Err(SymbolizerError::SymbolAlreadyDefined(param.name, previous.get().pos.clone()));
+            if let Some(previous) = self.table.enter_variable(scope, Visibility::All, param.name.data.clone(), true, false) {
+                return Err(SymbolizerError::SymbolAlreadyDefined(Box::new(param.name.clone()), Box::new(self.table.get(previous).pos.clone())));
             }
-
-            function_scope_ref.get().add_child(param_scope);
+        
+            self.table.add_symbol(function_scope_ref.scope(), param_scope);
         }
-
+        
         // Symbolize children
-        self.trace = Trace::new(0, self.trace.clone());
-        for node in body {
-            self.symbolize_node(node, function_scope_ref.clone())?;
-            self.trace.index += 1;
-        }
-        self.trace = *self.trace.clone().parent.unwrap();
-
-        Ok(())
+        let body = self.symbolize_body(body, function_scope_ref.scope())?;
+        
+        Ok(node.convert(Node::FunctionDefinition {
+            name,
+            external,
+            constructor,
+            parameters,
+            return_type,
+            body, 
+            access,
+            module,
+            scope: Some(function_scope_ref)
+        }))
     }
-
-    fn symbolize_variable_definition(&mut self, node: Positioned<Node>, scope: MutRef<Scope>) -> Result<(), SymbolizerError> {
-        let Node::VariableDefinition { var_type, name, data_type, value, access } = node.data.clone() else {
+        
+    fn symbolize_variable_definition(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
+        let Node::VariableDefinition { var_type, name, data_type, value, access, external, module, .. } = node.data.clone() else {
             unreachable!()
         };
-
-        let data_type_scoped = if let Some(data_type) = data_type {
+        
+        let data_type_scoped = if let Some(data_type) = data_type.clone() {
             Some(Scoped {
                 data: data_type.clone(), 
                 scope: match data_type.data {
-                    DataType::Custom(inner) => if let Some(data_type_scope) = scope.get().get_class(Trace::full(), inner) {
-                        Some(data_type_scope.clone())
+                    DataType::Custom(inner) => if let Some(data_type_scope) = self.table.get_class(scope, Visibility::All, inner) {
+                        Some(data_type_scope)
                     } else {
                         None
                     },
-                    DataType::Function { .. } => None
+                    _ => None
                 }
             })
         } else {
             None
         };
-
+        
         let variable_scope = Scope::new(node.convert(()), ScopeType::Variable { 
             var_type: var_type.clone(), 
             name: name.clone(), 
             data_type: data_type_scoped, 
-            initialized: value.is_some() 
-        }, Some(scope.clone()), self.trace.clone(), access);
-
+            // Externs are defined on the C side
+            initialized: value.is_some() || external.is_some(),
+            external: external.clone()
+        }, access.clone());
+        
         // Check if unique
-        if let Some(previous) = scope.get().enter_variable(Trace::full(), name.data.clone(), true, true) {
-            return Err(SymbolizerError::SymbolAlreadyDefined(name, previous.get().pos.clone()));
+        if let Some(previous) = self.table.enter_variable(scope, Visibility::All, name.data.clone(), true, true) {
+            return Err(SymbolizerError::SymbolAlreadyDefined(Box::new(name), Box::new(self.table.get(previous).pos.clone())));
         }
-
-        scope.get().add_child(variable_scope);
-
-        Ok(())
+        
+        let variable_scope_ref = self.add_definition(scope, variable_scope);
+        
+        Ok(node.convert(Node::VariableDefinition {
+            var_type,
+            name,
+            data_type,
+            value,
+            access,
+            external,
+            module,
+            scope: Some(variable_scope_ref)
+        }))
     }
-
-    fn symbolize_class_definition(&mut self, node: Positioned<Node>, scope: MutRef<Scope>) -> Result<(), SymbolizerError> {
-        let Node::ClassDefinition { name, body, access, extensions } = node.data.clone() else {
+        
+    fn symbolize_class_definition(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
+        let Node::ClassDefinition { name, body, access, extensions, external, module, .. } = node.data.clone() else {
             unreachable!()
         };
-
-        let linked_space = if let Some(class) = scope.get().enter_space(Trace::full(), name.data.clone()) {
-            let ScopeType::Space { linked_class, .. } = &mut class.get().scope else {
+        
+        let linked_space = if let Some(class) = self.table.enter_space(scope, Visibility::All, name.data.clone()) {
+            let ScopeType::Space { linked_class, .. } = &mut self.table.get_mut(class).scope else {
                 unreachable!()
             };
-
+        
             *linked_class = true;
             true
         } else {
             false
         };
-
+        
         // Process extensions
         let mut extensions_scope = Vec::new();
-        for extension in extensions {
-            if let Some(interface_scope) = scope.get().get_interface(Trace::full(), extension.data.clone()) {
-                extensions_scope.push(interface_scope.clone());
+        for extension in extensions.iter() {
+            if let Some(interface_scope) = self.table.get_interface(scope, Visibility::All, extension.data.clone()) {
+                extensions_scope.push(interface_scope);
             } else {
-                return Err(SymbolizerError::SymbolNotFound(extension));
+                return Err(SymbolizerError::SymbolNotFound(extension.clone()));
             }
         }
-
+        
         let class_scope = Scope::new(node.convert(()), ScopeType::Class { 
             name: name.clone(), 
-            children: Vec::new(),
+            children: Vec::new(), 
             linked_space,
-            extensions: extensions_scope
-        }, Some(scope.clone()), self.trace.clone(), access);
-
+            extensions: extensions_scope,
+            external: external.clone()
+        }, access.clone());
+        
         // Check if unique
-        if let Some(previous) = scope.get().enter_class(Trace::full(), name.data.clone()) {
-            return Err(SymbolizerError::SymbolAlreadyDefined(name, previous.get().pos.clone()));
+        if let Some(previous) = self.table.enter_class(scope, Visibility::All, name.data.clone()) {
+            return Err(SymbolizerError::SymbolAlreadyDefined(Box::new(name), Box::new(self.table.get(previous).pos.clone())));
         }
-
-        scope.get().add_child(class_scope);
-
-        let class_scope_ref = scope.get().get_last();
-
+        
+        let class_scope_ref = self.add_definition(scope, class_scope);
+        
         // Symbolize children
-        self.trace = Trace::new(0, self.trace.clone());
-        for node in body {
-            self.symbolize_node(node, class_scope_ref.clone())?;
-            self.trace.index += 1;
-        }
-        self.trace = *self.trace.clone().parent.unwrap();
-
-        // scope.get().add_child(class_scope);
-
-        Ok(())
+        let body = self.symbolize_body(body, class_scope_ref.scope())?;
+        
+        Ok(node.convert(Node::ClassDefinition {
+            name,
+            body, 
+            access,
+            extensions,
+            external,
+            module,
+            scope: Some(class_scope_ref)
+        }))
     }
-
-    fn symbolize_space_definition(&mut self, node: Positioned<Node>, scope: MutRef<Scope>) -> Result<(), SymbolizerError> {
-        let Node::SpaceDefinition { name, body, access } = node.data.clone() else {
+        
+    fn symbolize_space_definition(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
+        let Node::SpaceDefinition { name, body, access, module, .. } = node.data.clone() else {
             unreachable!()
         };
-
-        let linked_class = if let Some(space) = scope.get().enter_class(Trace::full(), name.data.clone()) {
-            let ScopeType::Class { linked_space, .. } = &mut space.get().scope else {
+        
+        let linked_class = if let Some(space) = self.table.enter_class(scope, Visibility::All, name.data.clone()) {
+            let ScopeType::Class { linked_space, .. } = &mut self.table.get_mut(space).scope else {
                 unreachable!()
             };
-
+        
             *linked_space = true;
             true
         } else {
             false
         };
-
+        
         let space_scope = Scope::new(node.convert(()), ScopeType::Space { 
             name: name.clone(), 
-            children: Vec::new(),
+            children: Vec::new(), 
             linked_class
-        }, Some(scope.clone()), self.trace.clone(), access);
-
+        }, access.clone());
+        
         // Check if unique
-        if let Some(previous) = scope.get().enter_space(Trace::full(), name.data.clone()) {
-            return Err(SymbolizerError::SymbolAlreadyDefined(name, previous.get().pos.clone()));
+        if let Some(previous) = self.table.enter_space(scope, Visibility::All, name.data.clone()) {
+            return Err(SymbolizerError::SymbolAlreadyDefined(Box::new(name), Box::new(self.table.get(previous).pos.clone())));
         }
-
-        scope.get().add_child(space_scope);
-
-        let space_scope_ref = scope.get().get_last();
-
+        
+        let space_scope_ref = self.add_definition(scope, space_scope);
+        
         // Symbolize children
-        self.trace = Trace::new(0, self.trace.clone());
-        for node in body {
-            self.symbolize_node(node, space_scope_ref.clone())?;
-            self.trace.index += 1;
+        let body = self.symbolize_body(body, space_scope_ref.scope())?;
+        
+        Ok(node.convert(Node::SpaceDefinition {
+            name,
+            body, 
+            access,
+            module,
+            scope: Some(space_scope_ref)
+        }))
+    }
+        
+    /// Matches are lowered to if statements by the IR Generator, they get the same branches.
+    fn symbolize_match_statement(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
+        let Node::MatchStatement { expr, branches, else_body, .. } = node.data.clone() else {
+            unreachable!()
+        };
+        
+        let mut scopes = Vec::new();
+        let mut symbolized_branches = Vec::new();
+        for branch in branches {
+            let debug_name = if scopes.is_empty() { "If" } else { "Elif" };
+            let (branch_scope, body) = self.symbolize_branch(&node, debug_name, branch.body, scope)?;
+            scopes.push(branch_scope);
+            symbolized_branches.push(MatchBranch {
+                conditions: branch.conditions,
+                body
+            });
         }
-        self.trace = *self.trace.clone().parent.unwrap();
-
-        // scope.get().add_child(class_scope);
-
-        Ok(())
+        
+        let else_body = if else_body.is_empty() {
+            else_body 
+        } else {
+            let (else_scope, else_body) = self.symbolize_branch(&node, "Else", else_body, scope)?;
+            scopes.push(else_scope);
+            else_body 
+        };
+        
+        Ok(node.convert(Node::MatchStatement {
+            expr,
+            branches: symbolized_branches,
+            else_body,
+            scopes
+        }))
     }
-
-    fn symbolize_if_statement(&mut self, node: Positioned<Node>, scope: MutRef<Scope>) -> Result<(), SymbolizerError> {
-        let Node::IfStatement { body, elif_branches, else_body, .. } = node.data.clone() else {
+        
+    fn symbolize_if_statement(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
+        let Node::IfStatement { condition, body, elif_branches, else_body, .. } = node.data.clone() else {
             unreachable!()
         };
-
+        
         // Symbolize If
-        let if_scope = Scope::new(node.convert(()), ScopeType::Branch { 
-            label: None,
-            debug_name: "If".to_string(),
-            children: Vec::new() 
-        }, Some(scope.clone()), self.trace.clone(), Some(node.convert(AccessModifier::Public)));
+        let (if_scope, body) = self.symbolize_branch(&node, "If", body, scope)?;
+        let mut scopes = vec![if_scope];
         
-        scope.get().add_child(if_scope);
-
-        let if_scope_ref = scope.get().get_last();
-
-        self.trace = Trace::new(0, self.trace.clone());
-        for node in body {
-            self.symbolize_node(node, if_scope_ref.clone())?;
-            self.trace.index += 1;
-        }
-        self.trace = *self.trace.clone().parent.unwrap();
-        self.trace.index += 1;
-
         // Symbolize Elif
+        let mut symbolized_elif_branches = Vec::new();
         for elif_branch in elif_branches {
-            let elif_scope = Scope::new(node.convert(()), ScopeType::Branch { 
-                label: None,
-                debug_name: "Elif".to_string(),
-                children: Vec::new() 
-            }, Some(scope.clone()), self.trace.clone(), Some(node.convert(AccessModifier::Public)));
-        
-            scope.get().add_child(elif_scope);
-    
-            let elif_scope_ref = scope.get().get_last();
-            
-            self.trace = Trace::new(0, self.trace.clone());
-            for node in elif_branch.body {
-                self.symbolize_node(node, elif_scope_ref.clone())?;
-                self.trace.index += 1;
-            }
-            self.trace = *self.trace.clone().parent.unwrap();
-            self.trace.index += 1;
+            let (elif_scope, body) = self.symbolize_branch(&node, "Elif", elif_branch.body, scope)?;
+            scopes.push(elif_scope);
+            symbolized_elif_branches.push(ElifBranch {
+                condition: elif_branch.condition,
+                body
+            });
         }
-
+        
         // Symbolize Else
-        if !else_body.is_empty() {
-            let else_scope = Scope::new(node.convert(()), ScopeType::Branch {
-                label: None,
-                debug_name: "Else".to_string(), 
-                children: Vec::new() 
-            }, Some(scope.clone()), self.trace.clone(), Some(node.convert(AccessModifier::Public)));
-            
-            scope.get().add_child(else_scope);
-    
-            let else_scope_ref = scope.get().get_last();
-    
-            self.trace = Trace::new(0, self.trace.clone());
-            for node in else_body {
-                self.symbolize_node(node, else_scope_ref.clone())?;
-                self.trace.index += 1;
-            }
-            self.trace = *self.trace.clone().parent.unwrap();
-        }
-
-        Ok(())
+        let else_body = if else_body.is_empty() {
+            else_body 
+        } else {
+            let (else_scope, else_body) = self.symbolize_branch(&node, "Else", else_body, scope)?;
+            scopes.push(else_scope);
+            else_body 
+        };
+        
+        Ok(node.convert(Node::IfStatement {
+            condition,
+            body, 
+            elif_branches: symbolized_elif_branches,
+            else_body,
+            scopes
+        }))
     }
-
-    fn symbolize_while_loop(&mut self, node: Positioned<Node>, scope: MutRef<Scope>) -> Result<(), SymbolizerError> {
-        let Node::WhileLoop { body, .. } = node.data.clone() else {
+        
+    fn symbolize_while_loop(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
+        let Node::WhileLoop { condition, body, .. } = node.data.clone() else {
             unreachable!()
         };
-
-        // Symbolize If
-        let while_scope = Scope::new(node.convert(()), ScopeType::Branch { 
-            label: None,
-            debug_name: "While".to_string(),
-            children: Vec::new() 
-        }, Some(scope.clone()), self.trace.clone(), Some(node.convert(AccessModifier::Public)));
         
-        scope.get().add_child(while_scope);
-
-        let while_scope_ref = scope.get().get_last();
-
-        self.trace = Trace::new(0, self.trace.clone());
-        for node in body {
-            self.symbolize_node(node, while_scope_ref.clone())?;
-            self.trace.index += 1;
-        }
-        self.trace = *self.trace.clone().parent.unwrap();
-        self.trace.index += 1;
-
-        Ok(())
+        let (while_scope, body) = self.symbolize_branch(&node, "While", body, scope)?;
+        
+        Ok(node.convert(Node::WhileLoop {
+            condition,
+            body, 
+            scope: Some(while_scope)
+        }))
     }
-
-    fn symbolize_label(&mut self, node: Positioned<Node>, scope: MutRef<Scope>) -> Result<(), SymbolizerError> {
+        
+    fn symbolize_label(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
         let Node::Label { name, inner } = node.data.clone() else {
             unreachable!()
         };
-
-        self.symbolize_node(*inner, scope.clone())?;
-
-        let scope_ref = scope.get();
-        let last_scope = scope_ref.get_last();
-        let ScopeType::Branch { label, .. } = &mut last_scope.get().scope else {
+        
+        let inner = self.symbolize_node(*inner, scope)?;
+        
+        let Node::WhileLoop { scope: Some(while_scope), .. } = &inner.data else {
             unreachable!("There should be a branch inside a label")
         };
-
-        *label = Some(name);
-
-        Ok(())
+        let ScopeType::Branch { label, .. } = &mut self.table.get_mut(*while_scope).scope else {
+            unreachable!()
+        };
+        
+        *label = Some(name.clone());
+        
+        Ok(node.convert(Node::Label {
+            name,
+            inner: Box::new(inner)
+        }))
     }
-
-    fn symbolize_interface_definition(&mut self, node: Positioned<Node>, scope: MutRef<Scope>) -> Result<(), SymbolizerError> {
-        let Node::InterfaceDefinition { name, body, access } = node.data.clone() else {
+        
+    fn symbolize_interface_definition(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
+        let Node::InterfaceDefinition { name, body, access, module, .. } = node.data.clone() else {
             unreachable!()
         };
-
+        
         let interface_scope = Scope::new(node.convert(()), ScopeType::Interface { 
             name: name.clone(), 
-            children: Vec::new()
-        }, Some(scope.clone()), self.trace.clone(), access);
-
+            children: Vec::new() 
+        }, access.clone());
+        
         // Check if unique
-        if let Some(previous) = scope.get().enter_interface(Trace::full(), name.data.clone()) {
-            return Err(SymbolizerError::SymbolAlreadyDefined(name, previous.get().pos.clone()));
+        if let Some(previous) = self.table.enter_interface(scope, Visibility::All, name.data.clone()) {
+            return Err(SymbolizerError::SymbolAlreadyDefined(Box::new(name), Box::new(self.table.get(previous).pos.clone())));
         }
-
-        scope.get().add_child(interface_scope);
-
-        let interface_scope_ref = scope.get().get_last();
-
+        
+        let interface_scope_ref = self.add_definition(scope, interface_scope);
+        
         // Symbolize children
-        self.trace = Trace::new(0, self.trace.clone());
-        for node in body {
-            self.symbolize_node(node, interface_scope_ref.clone())?;
-            self.trace.index += 1;
-        }
-        self.trace = *self.trace.clone().parent.unwrap();
-
-        Ok(())
+        let body = self.symbolize_body(body, interface_scope_ref.scope())?;
+        
+        Ok(node.convert(Node::InterfaceDefinition {
+            name,
+            body, 
+            access,
+            module,
+            scope: Some(interface_scope_ref)
+        }))
     }
-
-    fn symbolize_node(&mut self, node: Positioned<Node>, scope: MutRef<Scope>) -> Result<(), SymbolizerError> {
+        
+    fn symbolize_node(&mut self, node: Positioned<Node>, scope: ScopeId) -> Result<Positioned<Node>, SymbolizerError> {
         match node.data {
             Node::FunctionDefinition { .. } => self.symbolize_function_definition(node, scope),
-            Node::Use(_) => Ok(()), // Ignored (will be moved out by the IR Generator) 
+            Node::Use(_) => Ok(node), // Ignored (will be moved out by the IR Generator)
             Node::VariableDefinition { .. } => self.symbolize_variable_definition(node, scope),
             Node::ClassDefinition { .. } => self.symbolize_class_definition(node, scope),
             Node::SpaceDefinition { .. } => self.symbolize_space_definition(node, scope),
             Node::IfStatement { .. } => self.symbolize_if_statement(node, scope),
+            Node::MatchStatement { .. } => self.symbolize_match_statement(node, scope),
             Node::WhileLoop { .. } => self.symbolize_while_loop(node, scope),
             Node::Label { .. } => self.symbolize_label(node, scope),
             Node::InterfaceDefinition { .. } => self.symbolize_interface_definition(node, scope),
-            _ => Ok(())
+            _ => Ok(node)
         }
     }
-
-    pub fn symbolize(&mut self, root: MutRef<Scope>) -> Result<(), SymbolizerError> {
+        
+    pub fn symbolize(&mut self, root: ScopeId) -> Result<Vec<Positioned<Node>>, SymbolizerError> {
+        let mut ast = Vec::new();
         while let Some(current) = self.current() {
-            self.symbolize_node(current.clone(), root.clone())?;
+            ast.push(self.symbolize_node(current, root)?);
             self.advance();
-            if let Node::Use(_) = current.data {
-                // Don't advance (ignored)
-            } else {
-                self.trace.index += 1;
-            }
         }
         
-        Ok(())
+        Ok(ast)
     }
-
-}
\ No newline at end of file
+        
+}
+        
\ No newline at end of file
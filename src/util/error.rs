@@ -1,6 +1,6 @@
 use colored::{ColoredString, Colorize};
 
-use crate::util::{position::Positioned, source_file::SourceFile};
+use crate::util::{position::{Position, Positioned}, source_file::SourceFile};
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                           Error Type                                           //
@@ -20,6 +20,7 @@ pub enum ErrorType {
 pub struct ErrorFormat {
     error_type: ErrorType,
     messages: Vec<(String, Option<Positioned<()>>)>,
+    hints: Vec<String>,
     step: String,
 }
 
@@ -29,6 +30,7 @@ impl ErrorFormat {
         Self {
             error_type,
             messages: Vec::new(),
+            hints: Vec::new(),
             step: "No Step".to_string()
         }
     }
@@ -38,6 +40,12 @@ impl ErrorFormat {
         self
     }
 
+    /// Printed after the messages, `= help: ...`
+    pub fn add_hint(mut self, hint: String) -> Self {
+        self.hints.push(hint);
+        self
+    }
+
     pub fn set_step(mut self, step: String) -> Self {
         self.step = step;
         self
@@ -50,6 +58,15 @@ impl ErrorFormat {
         }
     }
 
+    /// Display width of the line.
+    fn width(line: &str) -> usize {
+        let mut pos = Position::default();
+        for chr in line.chars() {
+            pos.advance(chr);
+        }
+        pos.column
+    }
+
     pub fn print(self, src: &SourceFile) {
         print!("{} ", self.color_msg(format!("[{}]:", self.step)).bold());
         
@@ -63,12 +80,14 @@ impl ErrorFormat {
                 let mut lines = src.src.lines();
                 let mut line = pos.start.line;
     
-                let mut current_line = lines.nth(line - 1).unwrap();
+                let mut current_line = lines.nth(line - 1).unwrap_or("");
                 while line <= pos.end.line {
-                    let space_offset = (line == pos.start.line).then_some(pos.start.column_index).unwrap_or(0);
-                    let error_length = (line == pos.end.line).then_some(pos.end.column_index.checked_sub(space_offset).unwrap_or(1)).unwrap_or(current_line.len() - space_offset);
+                    // Columns are display columns, tabs being 4 wide
+                    let space_offset = if line == pos.start.line { pos.start.column } else { 0 };
+                    let end_column = if line == pos.end.line { pos.end.column } else { Self::width(current_line) };
+                    let error_length = end_column.saturating_sub(space_offset).max(1);
                 
-                    println!(" {:>5} {} {}", line.to_string().truecolor(81, 81, 255).bold(), "|".truecolor(81, 81, 255).bold(), current_line);
+                    println!(" {:>5} {} {}", line.to_string().truecolor(81, 81, 255).bold(), "|".truecolor(81, 81, 255).bold(), current_line.replace('\t', "    "));
                     print!("       {}", "|".truecolor(81, 81, 255).bold());
                     println!(" {}{}", " ".repeat(space_offset), self.color_msg("^".repeat(error_length).to_string()).bold());
     
@@ -85,6 +104,10 @@ impl ErrorFormat {
         if self.messages.is_empty() {
             println!();
         }
+
+        for hint in self.hints.iter() {
+            println!("       {} {}", "= help:".truecolor(81, 81, 255).bold(), hint);
+        }
     }
 
 }
\ No newline at end of file
@@ -0,0 +1,29 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                                Ids                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Index of a scope in its [`SymbolTable`](crate::symbolizer::scope::SymbolTable), stable for the
+/// whole compilation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ScopeId(pub(crate) usize);
+
+/// Named symbol: function, variable, class, space or interface. Each symbol is the scope of the same
+/// index, [`SymbolId::scope`] gives it, but branches and the root are scopes without a symbol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SymbolId(pub(crate) usize);
+
+impl SymbolId {
+
+    pub fn scope(self) -> ScopeId {
+        ScopeId(self.0)
+    }
+
+}
+
+impl From<SymbolId> for ScopeId {
+
+    fn from(id: SymbolId) -> Self {
+        id.scope()
+    }
+
+}
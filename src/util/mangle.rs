@@ -0,0 +1,223 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Mangling                                            //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+// C names are the path of the symbol (spaces, classes, interfaces, functions) joined with `_`, the
+// underscores of every component being doubled: `A_B.c` is `A__B_c` while `A.B_c` is `A_B__c`.
+// Taly identifiers can neither start nor end with `_` (checked by the Checker), so a run of
+// underscores is always a separator, escaped underscores or both in a way that can be undone.
+// Names starting with `_` are generated by the compiler and cannot clash with escaped ones.
+// Non-ASCII characters are written `_0x<hex>_`, an underscore followed by a digit never being a
+// separator as identifiers cannot start with a digit: `café` is `caf_0xe9_`.
+
+/// C keywords and the runtime functions called by the generated code.
+const C_RESERVED: [&str; 40] = [
+    "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "false", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "true", "typedef",
+    "union", "unsigned", "void", "volatile", "while", "NULL", "malloc", "free"
+];
+
+const IMPLEMENTATION_PREFIX: &str = "_impl_";
+const BASE_FIELD_PREFIX: &str = "_base_";
+
+/// Name of a symbol defined at the root of the module (or of a local).
+pub fn escape(name: &str) -> String {
+    if is_generated(name) {
+        name.to_string()
+    } else {
+        ascii(&name.replace('_', "__"))
+    }
+}
+
+/// Name of a symbol defined in `parent`, which is already mangled.
+pub fn member(parent: &str, name: &str) -> String {
+    format!("{}_{}", parent, ascii(&name.replace('_', "__")))
+}
+
+/// Public root symbol of a library, spelled like in Taly from its escaped name: `MAX__SIDE` is
+/// `MAX_SIDE`.
+pub fn exported(name: &str) -> String {
+    ascii(&demangle(name))
+}
+
+/// Encodes the non-ASCII characters of the name, for names emitted without escaping.
+pub fn ascii(name: &str) -> String {
+    let mut buf = String::new();
+    for chr in name.chars() {
+        if chr.is_ascii() {
+            buf.push(chr);
+        } else {
+            buf.push_str(&format!("_0x{:x}_", chr as u32));
+        }
+    }
+    buf
+}
+
+/// Function called by the wrapper of a method implementing an interface.
+pub fn implementation(name: &str) -> String {
+    format!("{}{}", IMPLEMENTATION_PREFIX, name)
+}
+
+/// Field holding the functions of an implemented interface.
+pub fn base_field(interface: &str) -> String {
+    format!("{}{}", BASE_FIELD_PREFIX, escape(interface))
+}
+
+pub fn temporary(id: usize) -> String {
+    format!("_temp{}", id)
+}
+
+pub fn lambda(id: usize) -> String {
+    format!("_lambda{}", id)
+}
+
+/// Structure holding the captures of a lambda.
+pub fn environment(lambda: &str) -> String {
+    format!("{}_env", lambda)
+}
+
+/// `mylib` and `mylib_` both prefix symbols with `mylib_`.
+pub fn library_prefix(prefix: Option<String>) -> String {
+    match prefix {
+        Some(prefix) if !prefix.is_empty() && !prefix.ends_with('_') => format!("{}_", prefix),
+        Some(prefix) => prefix,
+        None => String::new()
+    }
+}
+
+/// Library symbols are prefixed, the generated marker of implementations staying first.
+pub fn prefixed(prefix: &str, name: &str) -> String {
+    match name.strip_prefix(IMPLEMENTATION_PREFIX) {
+        Some(inner) => implementation(&format!("{}{}", prefix, inner)),
+        None => format!("{}{}", prefix, name)
+    }
+}
+
+pub fn is_generated(name: &str) -> bool {
+    name.starts_with('_') && !name[1..].starts_with(|chr: char| chr.is_ascii_digit())
+}
+
+/// Identifiers the user cannot define, whatever their place.
+pub fn is_reserved(name: &str) -> bool {
+    name.starts_with('_') || name.ends_with('_')
+}
+
+/// Identifiers the user cannot define where they are emitted as is (types, fields, locals, ...).
+pub fn is_c_reserved(name: &str) -> bool {
+    C_RESERVED.contains(&name)
+}
+
+/// Taly path of a C name, for diagnostics: `String_to__c__string` is `String.to_c_string`.
+pub fn demangle(name: &str) -> String {
+    if let Some(inner) = name.strip_prefix(IMPLEMENTATION_PREFIX) {
+        return format!("{} (implementation)", demangle(inner));
+    } else if let Some(inner) = name.strip_prefix(BASE_FIELD_PREFIX) {
+        return format!("{} (base)", demangle(inner));
+    } else if is_generated(name) {
+        return name.to_string();
+    }
+
+    let mut buf = String::new();
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '_' {
+            buf.push(c);
+            continue;
+        }
+
+        let mut count = 1;
+        while chars.peek() == Some(&'_') {
+            chars.next();
+            count += 1;
+        }
+
+        // The last underscore starts an encoded character
+        let encoded = chars.peek().is_some_and(|c| c.is_ascii_digit());
+        if encoded {
+            count -= 1;
+        }
+
+        // An odd run starts with a separator, the component after it being generated
+        if count % 2 == 1 {
+            buf.push('.');
+        }
+        for _ in 0..count / 2 {
+            buf.push('_');
+        }
+
+        if encoded {
+            let hex: String = chars.by_ref().skip(2).take_while(|c| *c != '_').collect();
+            buf.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+        }
+    }
+
+    buf
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Tests                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(components: &[&str]) -> String {
+        let mut name = escape(components[0]);
+        for component in components[1..].iter() {
+            name = member(&name, component);
+        }
+        name
+    }
+
+    #[test]
+    fn paths_do_not_collide() {
+        assert_ne!(path(&["A_B", "c"]), path(&["A", "B_c"]));
+        assert_ne!(path(&["String_create"]), path(&["String", "create"]));
+        assert_ne!(path(&["main_lambda0"]), path(&["main", "_lambda0"]));
+        assert_ne!(implementation(&path(&["String", "to_c_string"])), path(&["String", "to_c_string", "impl"]));
+    }
+
+    #[test]
+    fn readable_names() {
+        assert_eq!(path(&["String", "create"]), "String_create");
+        assert_eq!(path(&["String", "to_c_string"]), "String_to__c__string");
+        assert_eq!(escape("_temp0"), "_temp0");
+        assert_eq!(base_field("ToCString"), "_base_ToCString");
+        assert_eq!(prefixed("lib_", &implementation("String_create")), implementation("lib_String_create"));
+        assert_eq!(exported(&escape("MAX_SIDE")), "MAX_SIDE");
+        assert_eq!(exported(&escape("café_x")), ascii("café_x"));
+    }
+
+    #[test]
+    fn demangle_paths() {
+        assert_eq!(demangle(&path(&["A_B", "c"])), "A_B.c");
+        assert_eq!(demangle(&path(&["A", "B_c"])), "A.B_c");
+        assert_eq!(demangle(&path(&["main", "_lambda0"])), "main._lambda0");
+        assert_eq!(demangle(&implementation(&path(&["String", "to_c_string"]))), "String.to_c_string (implementation)");
+        assert_eq!(demangle("_temp3"), "_temp3");
+    }
+
+    #[test]
+    fn unicode_names() {
+        assert_eq!(path(&["café"]), "caf_0xe9_");
+        assert!(path(&["Été", "x_y"]).chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        assert!(!is_generated(&path(&["été"])));
+        assert_eq!(demangle(&path(&["été", "x_y"])), "été.x_y");
+        assert_eq!(demangle(&path(&["a_é", "é_b"])), "a_é.é_b");
+        assert_eq!(demangle(&path(&["名前", "変数"])), "名前.変数");
+    }
+
+    #[test]
+    fn reserved_names() {
+        assert!(is_reserved("_x"));
+        assert!(is_reserved("x_"));
+        assert!(!is_reserved("to_c_string"));
+        assert!(is_c_reserved("int"));
+        assert!(is_c_reserved("malloc"));
+        assert!(!is_c_reserved("main"));
+    }
+}
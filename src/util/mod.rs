@@ -1,4 +1,6 @@
 pub mod position;
 pub mod source_file;
 pub mod error;
-pub mod reference;
\ No newline at end of file
+pub mod mangle;
+pub mod suggestion;
+pub mod id;
@@ -1,15 +1,24 @@
 use std::fmt::Debug;
 
+use unicode_width::UnicodeWidthChar;
+
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //                                            Position                                            //
 //////////////////////////////////////////////////////////////////////////////////////////////////// 
 
 #[derive(Clone)]
 pub struct Position {
+    /// Index of the char in the source
     pub index: usize,
+    /// Offset in bytes in the source
+    pub byte: usize,
     pub line: usize,
+    /// Display column, wide characters count twice and combining ones not at all
     pub column: usize,
-    pub column_index: usize
+    /// Index of the char in the line
+    pub column_index: usize,
+    /// File of the position, 0 for the source of the session and `n` for its n-th imported module
+    pub source: usize
 }
 
 impl Default for Position {
@@ -17,9 +26,11 @@ impl Default for Position {
     fn default() -> Self {
         Self { 
             index: 0,
+            byte: 0,
             line: 1,
             column: 0,
-            column_index: 0
+            column_index: 0,
+            source: 0
         }
     }
 
@@ -36,10 +47,11 @@ impl Position {
             self.column += 4;
             self.column_index += 1;
         } else {
-            self.column += 1;
+            self.column += chr.width().unwrap_or(0);
             self.column_index += 1;
         }
         self.index += 1;
+        self.byte += chr.len_utf8();
     }
 
 }
@@ -83,4 +95,30 @@ impl<T: Debug> Debug for Positioned<T> {
         self.data.fmt(f)
     }
 
-}
\ No newline at end of file
+}
+
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                              Tests                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advance(src: &str) -> Position {
+        let mut pos = Position::default();
+        for chr in src.chars() {
+            pos.advance(chr);
+        }
+        pos
+    }
+
+    #[test]
+    fn columns_of_wide_and_combining_characters() {
+        let pos = advance("名前 e\u{301}");
+        assert_eq!((pos.index, pos.byte, pos.column_index, pos.column), (5, 10, 5, 6));
+        let pos = advance("\tx\ny");
+        assert_eq!((pos.line, pos.column_index, pos.column, pos.byte), (2, 1, 1, 4));
+    }
+}
@@ -19,24 +19,23 @@ impl SourceFile {
 
     pub fn name_ext(&self) -> String {
         let index = self.path.rfind('/').map(|x| x + 1).unwrap_or(0);
-        self.path[index..(self.path.len() - 1)].to_string()
+        self.path[index..].to_string()
     }
 
     pub fn name(&self) -> String {
         let name_ext = self.name_ext();
 
-        let index = name_ext.rfind('.').unwrap_or(name_ext.len() - 1);
+        let index = name_ext.rfind('.').unwrap_or(name_ext.len());
         name_ext[0..index].to_string()
     }
 
     pub fn ext(&self) -> String {
         let name_ext = self.name_ext();
 
-        let index = name_ext.rfind('.').map(|x| x + 1).unwrap_or(name_ext.len() - 1);
-        if index == name_ext.len() - 1 {
-            return "".to_string();
+        match name_ext.rfind('.') {
+            Some(index) => name_ext[(index + 1)..].to_string(),
+            None => "".to_string()
         }
-        name_ext[index..(name_ext.len() - 1)].to_string()
     }
 
 }
\ No newline at end of file
@@ -0,0 +1,52 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Suggestion                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Levenshtein distance between `a` and `b`, in characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Closest candidate to `name`, if close enough to be a typo (a third of its length at most).
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let max = (name.chars().count() / 3).max(1);
+    candidates.into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, closest};
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("printf", "printf"), 0);
+        assert_eq!(edit_distance("prinft", "printf"), 2);
+        assert_eq!(edit_distance("print", "printf"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn closest_candidate() {
+        assert_eq!(closest("pritnf", ["puts", "printf", "main"]), Some("printf".to_string()));
+        assert_eq!(closest("lenght", ["length", "len"]), Some("length".to_string()));
+        assert_eq!(closest("foo", ["printf", "main"]), None);
+    }
+
+}
@@ -0,0 +1,23 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                             Common                                             //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+//! Helpers shared by the integration tests.
+
+use std::path::{Path, PathBuf};
+
+/// Collects every `.taly` file under the directory, sorted so the order is stable.
+pub fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.map(|entry| entry.unwrap().path()).collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "taly") {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+// Handles only known through pointers
+struct node;
+typedef struct list List;
+
+struct list {
+    struct node *head;
+    List *next;
+};
+
+union value;
+
+struct node *node_next(struct node *node);
+void list_push(List *list, union value *value);
@@ -0,0 +1,60 @@
+#ifndef SHAPES_H
+#define SHAPES_H
+
+#include <stdio.h>
+
+/* Limits */
+#define SHAPES_MAX 64
+#define SHAPES_MASK (0xFFu)
+#define SHAPES_OFFSET -3
+#define SHAPES_NAME "shapes"
+#define SHAPES_SQUARE(x) ((x) * (x))
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum {
+    SHAPE_CIRCLE,
+    SHAPE_SQUARE = 4,
+    SHAPE_TRIANGLE
+} ShapeKind;
+
+struct point {
+    int x;
+    int y;
+};
+
+typedef struct shape {
+    ShapeKind kind;
+    struct point *origin;
+    const char *name;
+    double area;
+    unsigned flags : 3;
+    struct shape *next;
+    int (*compute)(struct shape *self);
+} Shape;
+
+typedef struct registry Registry;
+
+extern int shapes_count;
+extern const char *const shapes_version;
+
+Shape *shape_new(ShapeKind kind, const char *name);
+void shape_free(Shape *shape);
+int shape_move(Shape *, struct point *by);
+float shape_scale(Shape *shape, float factor);
+Registry *registry_open(const char *path, FILE *log);
+int registry_log(Registry *registry, const char *fmt, ...);
+_Bool registry_has(const Registry *registry, int match);
+long shape_area_long(const Shape *shape);
+void *shape_data(Shape *shape, void (*callback)(void *));
+int shapes_sum(int values[], int count);
+static inline int shapes_twice(int x) { return x * 2; }
+int end(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // SHAPES_H
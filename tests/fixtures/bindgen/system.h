@@ -0,0 +1,63 @@
+// Patterns of the glibc headers, which are bound without running the preprocessor
+#ifndef _SYSTEM_H
+#define _SYSTEM_H 1
+
+#include <features.h>
+
+
+__BEGIN_DECLS
+
+typedef struct _IO_FILE FILE;
+typedef long int __off_t;
+
+extern FILE *fopen (const char *__restrict __filename,
+		    const char *__restrict __modes) __wur;
+#ifdef __REDIRECT
+extern FILE *__REDIRECT (tmpfile, (void), tmpfile64) __wur;
+extern int __REDIRECT_NTH (rename_at, (int __oldfd, const char *__old, int __newfd,
+			   const char *__new), renameat) __THROW;
+#endif
+extern int fclose (FILE *__stream) __nonnull ((1));
+extern size_t fread (void *__restrict __ptr, size_t __size,
+		     size_t __n, FILE *__restrict __stream) __wur;
+extern long int ftell (FILE *__stream) __wur;
+extern unsigned int sleep (unsigned int __seconds);
+extern unsigned long int strtoul (const char *__restrict __nptr,
+				  char **__restrict __endptr, int __base)
+     __THROW __nonnull ((1));
+extern void *malloc (size_t __size) __THROW __attribute_malloc__
+     __attribute_alloc_size__ ((1)) __wur;
+
+#ifdef __USE_EXTERN_INLINES
+__extern_inline int
+getc_unlocked (FILE *__fp)
+{
+  return __getc_unlocked_body (__fp);
+}
+#endif
+
+#ifdef __cplusplus
+extern "C++" {
+template <class __T> inline bool
+iszero (__T __val)
+{
+  return __val == 0;
+}
+}
+#endif
+
+extern int fileno (FILE *__stream) __THROW __wur;
+
+typedef struct re_pattern_buffer regex_t;
+extern int regcomp (regex_t *_Restrict_ __preg,
+		    const char *_Restrict_ __pattern,
+		    int __cflags);
+
+/* Not understood, skipped without stopping the bindings */
+extern double __REDIRECT_NTH (__MATH_PRECNAME (cabs), (double __z), __cabs_finite);
+extern double atof (const char *__nptr) __THROW __attribute_pure__ __nonnull ((1));
+extern int abs (int __x) __THROW __attribute__ ((__const__)) __wur;
+
+__END_DECLS
+
+#endif /* system.h */
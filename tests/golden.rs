@@ -0,0 +1,178 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                           Golden Files                                         //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+//! Every `tests/golden/pass/**/<name>.taly` is compiled and each stage is compared to the files
+//! next to it: `<name>.tokens`, `<name>.ast`, `<name>.scope`, `<name>.ir` (checked IR) and the
+//! generated `<name>/<file>.h` and `<name>/<file>.c`.
+//!
+//! Every `tests/golden/fail/**/<name>.taly` must fail with the error of `<name>.err`, written as
+//! `Checker::SymbolNotFound 4:5-4:8` (kind then `line:column` span, columns starting at 1), one
+//! line per error when the parser recovered from several.
+//!
+//! Every `tests/golden/library/<name>.taly` is compiled as the library `<name>` with the prefix
+//! `mylib`, only the generated `<name>/<file>.h` and `<name>/<file>.c` are compared.
+//!
+//! Every `tests/golden/lines/<name>.taly` is compiled with `#line` directives, the generated files
+//! are compared along with the source map of each C file, `<name>/<file>.c.map`.
+//!
+//! `BLESS=1 cargo test --test golden` rewrites the expected files from the current output.
+
+mod common;
+
+use std::{path::{Path, PathBuf}, fmt::Debug};
+
+use common::collect;
+use taly_lang::{util::{source_file::SourceFile, position::Positioned}, session::{session::Session, error::CompileError}, generator::{project::Project, library::{Library, LibraryKind}}};
+
+fn bless() -> bool {
+    std::env::var("BLESS").is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+fn programs(kind: &str) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(kind), &mut files);
+    files
+}
+
+fn read(path: &Path) -> SourceFile {
+    let src = std::fs::read_to_string(path).unwrap_or_else(|err| panic!("Failed to read '{}', {}", path.display(), err));
+    SourceFile::new(path.to_string_lossy().to_string(), src)
+}
+
+/// Compares `actual` to the expected file, or overwrites it in bless mode.
+fn compare(expected: &Path, actual: &str, failures: &mut Vec<String>) {
+    if bless() {
+        std::fs::create_dir_all(expected.parent().unwrap()).unwrap();
+        std::fs::write(expected, actual).unwrap();
+        return;
+    }
+
+    match std::fs::read_to_string(expected) {
+        Ok(content) if content == actual => {},
+        Ok(content) => failures.push(format!("'{}' differs:\n{}", expected.display(), diff(&content, actual))),
+        Err(_) => failures.push(format!("'{}' is missing, run with BLESS=1 to create it", expected.display()))
+    }
+}
+
+/// First differing line, enough to find the change in the file.
+fn diff(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(a), Some(b)) if a == b => line += 1,
+            (None, None) => return "  (trailing whitespace)".to_string(),
+            (a, b) => return format!("  line {}\n  - {}\n  + {}", line, a.unwrap_or("<EOF>"), b.unwrap_or("<EOF>"))
+        }
+    }
+}
+
+fn dump<T: Debug>(items: &[T]) -> String {
+    items.iter().map(|item| format!("{:#?}\n", item)).collect()
+}
+
+fn span(span: Option<Positioned<()>>) -> String {
+    match span {
+        Some(span) => format!("{}:{}-{}:{}", span.start.line, span.start.column_index + 1, span.end.line, span.end.column_index + 1),
+        None => "-".to_string()
+    }
+}
+
+fn describe(err: &CompileError) -> String {
+    err.diagnostics().into_iter().map(|(kind, pos)| format!("{} {}\n", kind, span(pos))).collect()
+}
+
+/// Runs each stage and compares its output, returns the error of the failing stage.
+fn check_program(path: &Path, failures: &mut Vec<String>) -> Result<(), CompileError> {
+    let mut session = Session::new(read(path));
+    let expected = |ext: &str| path.with_extension(ext);
+
+    let tokens = session.tokenize()?;
+    compare(&expected("tokens"), &tokens.iter().map(|token| format!("{:?}\n", token)).collect::<String>(), failures);
+
+    let ast = session.parse(tokens)?;
+    compare(&expected("ast"), &dump(&ast), failures);
+
+    let ast = session.symbolize(ast)?;
+    compare(&expected("scope"), &format!("{:#?}\n", session.scope()), failures);
+
+    let ir_output = session.ir_generate(ast)?;
+    let ir_output = session.check(ir_output)?;
+    compare(&expected("ir"), &format!("{}{}", dump(&ir_output.includes), dump(&ir_output.ast)), failures);
+
+    let ir_output = session.evaluate(ir_output)?;
+    let ir_output = session.post_process(ir_output);
+    let project = session.generate(ir_output)?;
+    compare_project(path, &project, failures);
+    Ok(())
+}
+
+fn compare_project(path: &Path, project: &Project, failures: &mut Vec<String>) {
+    let dir = path.with_extension("");
+    for file in project.files.iter() {
+        compare(&dir.join(format!("{}.h", file.name)), &file.header, failures);
+        compare(&dir.join(format!("{}.c", file.name)), &file.src, failures);
+        if let Some(source_map) = &file.source_map {
+            compare(&dir.join(format!("{}.c.map", file.name)), &source_map.to_json(), failures);
+        }
+    }
+}
+
+#[test]
+fn pass() {
+    let mut failures = Vec::new();
+    for path in programs("pass") {
+        if let Err(err) = check_program(&path, &mut failures) {
+            failures.push(format!("'{}' failed with {}", path.display(), describe(&err)));
+        }
+    }
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n\n"));
+}
+
+#[test]
+fn fail() {
+    let mut failures = Vec::new();
+    for path in programs("fail") {
+        let mut session = Session::new(read(&path));
+        match session.compile() {
+            Ok(_) => failures.push(format!("'{}' compiled but should fail", path.display())),
+            Err(err) => compare(&path.with_extension("err"), &describe(&err), &mut failures)
+        }
+    }
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n\n"));
+}
+
+#[test]
+fn library() {
+    let mut failures = Vec::new();
+    for path in programs("library") {
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        let mut session = Session::new(read(&path));
+        session.set_library(Some(Library::new(name, Some("mylib".to_string()), LibraryKind::Static)));
+        match session.compile() {
+            Ok(project) => compare_project(&path, &project, &mut failures),
+            Err(err) => failures.push(format!("'{}' failed with {}", path.display(), describe(&err)))
+        }
+    }
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n\n"));
+}
+
+#[test]
+fn lines() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut failures = Vec::new();
+    for path in programs("lines") {
+        // The directives name the source, relative to keep the expected files portable
+        let mut source = read(&path);
+        source.path = path.strip_prefix(root).unwrap().to_string_lossy().to_string();
+        let mut session = Session::new(source);
+        session.set_line_directives(true);
+        match session.compile() {
+            Ok(project) => compare_project(&path, &project, &mut failures),
+            Err(err) => failures.push(format!("'{}' failed with {}", path.display(), describe(&err)))
+        }
+    }
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n\n"));
+}
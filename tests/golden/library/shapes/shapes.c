@@ -0,0 +1,39 @@
+#include "shapes.h"
+
+int mylib_created = 0;
+
+static int mylib_checks = 0;
+
+const int mylib_MAX_SIDE = 100;
+
+static const int mylib_UNIT = 1;
+
+static int mylib_scale(int value, int factor);
+
+static int mylib_scale(int value, int factor) { 
+	(mylib_checks += 1);
+	return ((value * factor) * 1);
+}
+
+mylib_Square* mylib_Square_create(int side) { 
+	mylib_Square* self = malloc(sizeof(mylib_Square));
+	((self->side) = side);
+	(mylib_created += 1);
+	return self;
+}
+
+int mylib_Square_area(mylib_Square* self) { 
+	return mylib_scale((self->side), (self->side));
+}
+
+void mylib_Square_destroy(mylib_Square* self) { 
+	free(self);
+}
+
+int mylib_square_area(int side) { 
+	mylib_Square* square = mylib_Square_create(side);
+	int area = mylib_Square_area(square);
+	mylib_Square_destroy(square);
+	return area;
+}
+
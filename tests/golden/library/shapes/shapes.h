@@ -0,0 +1,25 @@
+#ifndef TALY_GEN_C_shapes_H
+#define TALY_GEN_C_shapes_H
+
+#include <stdlib.h>
+#include <stdbool.h>
+
+typedef struct mylib_Square mylib_Square;
+
+struct mylib_Square { 
+	int side;
+};
+
+extern int mylib_created;
+
+extern const int mylib_MAX_SIDE;
+
+mylib_Square* mylib_Square_create(int side);
+
+int mylib_Square_area(mylib_Square* self);
+
+void mylib_Square_destroy(mylib_Square* self);
+
+int mylib_square_area(int side);
+
+#endif // TALY_GEN_C_shapes_H
\ No newline at end of file
@@ -0,0 +1,26 @@
+#include "Counter.h"
+
+#line 9 "tests/golden/lines/debug.taly"
+Counter* Counter_create() { 
+	#line 9 "tests/golden/lines/debug.taly"
+	Counter* self = malloc(sizeof(Counter));
+	#line 10 "tests/golden/lines/debug.taly"
+	((self->count) = 0);
+	#line 9 "tests/golden/lines/debug.taly"
+	return self;
+}
+
+#line 12 "tests/golden/lines/debug.taly"
+int Counter_increment(Counter* self) { 
+	#line 13 "tests/golden/lines/debug.taly"
+	((self->count) += 1);
+	#line 14 "tests/golden/lines/debug.taly"
+	return (self->count);
+}
+
+#line 5 "tests/golden/lines/debug.taly"
+void Counter_destroy(Counter* self) { 
+	#line 5 "tests/golden/lines/debug.taly"
+	free(self);
+}
+
@@ -0,0 +1,20 @@
+#ifndef TALY_GEN_C_Counter_H
+#define TALY_GEN_C_Counter_H
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdbool.h>
+
+typedef struct Counter Counter;
+
+struct Counter { 
+	int count;
+};
+
+Counter* Counter_create();
+
+int Counter_increment(Counter* self);
+
+void Counter_destroy(Counter* self);
+
+#endif // TALY_GEN_C_Counter_H
\ No newline at end of file
@@ -0,0 +1,25 @@
+#include "main.h"
+#include "Counter.h"
+
+#line 16 "tests/golden/lines/debug.taly"
+int main() { 
+	#line 17 "tests/golden/lines/debug.taly"
+	Counter* counter = Counter_create();
+	#line 18 "tests/golden/lines/debug.taly"
+	int i = 0;
+	#line 19 "tests/golden/lines/debug.taly"
+	while ((i < 3)) { 
+		#line 20 "tests/golden/lines/debug.taly"
+		if ((Counter_increment(counter) == 2)) { 
+			#line 21 "tests/golden/lines/debug.taly"
+			printf("second\n");
+		} 
+		#line 23 "tests/golden/lines/debug.taly"
+		(i += 1);
+	} 
+	#line 25 "tests/golden/lines/debug.taly"
+	Counter_destroy(counter);
+	#line 26 "tests/golden/lines/debug.taly"
+	return 0;
+}
+
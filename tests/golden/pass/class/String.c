@@ -0,0 +1,21 @@
+#include "String.h"
+
+String* String_create(const char* c__str) { 
+	String* self = malloc(sizeof(String));
+	(((self->_base_ToCString).ToCString_to__c__string) = &_impl_String_to__c__string);
+	((self->c_str) = c__str);
+	return self;
+}
+
+void String_destroy(String* self) { 
+	free(self);
+}
+
+const char* _impl_String_to__c__string(String* self) { 
+	return (self->c_str);
+}
+
+const char* String_to__c__string(String* self) { 
+	return _impl_String_to__c__string(self);
+}
+
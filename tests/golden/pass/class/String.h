@@ -0,0 +1,25 @@
+#ifndef TALY_GEN_C_String_H
+#define TALY_GEN_C_String_H
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdbool.h>
+
+#include "ToCString.h"
+
+typedef struct String String;
+
+struct String { 
+	ToCString _base_ToCString;
+	const char* c_str;
+};
+
+String* String_create(const char* c__str);
+
+void String_destroy(String* self);
+
+const char* _impl_String_to__c__string(String* self);
+
+const char* String_to__c__string(String* self);
+
+#endif // TALY_GEN_C_String_H
\ No newline at end of file
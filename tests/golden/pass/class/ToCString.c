@@ -0,0 +1,6 @@
+#include "ToCString.h"
+
+const char* ToCString_to__c__string(ToCString* self) { 
+	return ((self->ToCString_to__c__string)(self));
+}
+
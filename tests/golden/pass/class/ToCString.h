@@ -0,0 +1,16 @@
+#ifndef TALY_GEN_C_ToCString_H
+#define TALY_GEN_C_ToCString_H
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdbool.h>
+
+typedef struct ToCString ToCString;
+
+struct ToCString { 
+	const char*(*ToCString_to__c__string)(struct ToCString*);
+};
+
+const char* ToCString_to__c__string(ToCString* self);
+
+#endif // TALY_GEN_C_ToCString_H
\ No newline at end of file
@@ -0,0 +1,10 @@
+#include "main.h"
+#include "String.h"
+
+int main() { 
+	String* str = String_create("Hello");
+	printf(String_to__c__string(str));
+	String_destroy(str);
+	return 0;
+}
+
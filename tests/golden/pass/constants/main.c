@@ -0,0 +1,35 @@
+#include "main.h"
+
+const int KIB = 1024;
+
+const int PAGES = 16;
+
+const int SIZE = 4096;
+
+const char* const NAME = "constants";
+
+const int ONE = 1;
+
+const int TWO = 2;
+
+const char* describe(int value) { 
+	if ((value == 1)) { 
+		return "one";
+	} else if (((value == 2) || (value == 3))) { 
+		return "two or three";
+	} 
+	return "many";
+}
+
+int main() { 
+	int mask = 13;
+	bool enabled = true;
+	printf("%d %d %s %d\n", 13, true, NAME, 16);
+	int KIB = 10;
+	printf("%d %s\n", 20, describe(2));
+	int ONE = 5;
+	(ONE += 1);
+	printf("%d\n", (ONE + 2));
+	return 0;
+}
+
@@ -0,0 +1,21 @@
+#ifndef TALY_GEN_C_main_H
+#define TALY_GEN_C_main_H
+
+#include <stdio.h>
+#include <stdbool.h>
+
+extern const int KIB;
+
+extern const int PAGES;
+
+extern const int SIZE;
+
+extern const char* const NAME;
+
+extern const int ONE;
+
+extern const int TWO;
+
+const char* describe(int value);
+
+#endif // TALY_GEN_C_main_H
\ No newline at end of file
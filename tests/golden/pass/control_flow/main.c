@@ -0,0 +1,19 @@
+#include "main.h"
+
+int sum(int n) { 
+	int total = 0;
+	int i = 0;
+	while ((i < n)) { 
+		if (((i % 2) == 0)) { 
+			(total += i);
+		} 
+		(i += 1);
+	} 
+	return total;
+}
+
+int main() { 
+	printf("%d\n", sum(10));
+	return 0;
+}
+
@@ -0,0 +1,9 @@
+#ifndef TALY_GEN_C_main_H
+#define TALY_GEN_C_main_H
+
+#include <stdio.h>
+#include <stdbool.h>
+
+int sum(int n);
+
+#endif // TALY_GEN_C_main_H
\ No newline at end of file
@@ -0,0 +1,7 @@
+#include "main.h"
+
+int main() { 
+	printf("Hello %d\n", 42);
+	return 0;
+}
+
@@ -0,0 +1,15 @@
+#include "main.h"
+
+int clamp(int value, int low, int high) { 
+	if (((value < low) || (value > high))) { 
+		return low;
+	} 
+	return value;
+}
+
+int main() { 
+	int total = 7;
+	printf("%d %d\n", 7, clamp(7, 0, 10));
+	return 0;
+}
+
@@ -0,0 +1,8 @@
+#ifndef TALY_GEN_C_main_H
+#define TALY_GEN_C_main_H
+
+#include <stdbool.h>
+
+int clamp(int value, int low, int high);
+
+#endif // TALY_GEN_C_main_H
\ No newline at end of file
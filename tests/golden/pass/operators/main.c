@@ -0,0 +1,8 @@
+#include "main.h"
+
+int mask(int value) { 
+	int result = ((value & 3) | (value ^ 4));
+	(result >>= 1);
+	return (~result);
+}
+
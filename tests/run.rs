@@ -0,0 +1,102 @@
+////////////////////////////////////////////////////////////////////////////////////////////////////
+//                                            Execution                                           //
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+//! Every `tests/run/**/<name>.taly` is compiled with the C compiler (`$CC`, `cc` by default),
+//! executed, and its output compared to the comments of the program:
+//!
+//! ```text
+//! # stdout: Hello
+//! # stdout: World
+//! # exit: 0
+//! ```
+//!
+//! Each `# stdout:` comment is one line of the output, the exit code defaults to 0.
+
+mod common;
+
+use std::{path::Path, process::Command};
+
+use common::collect;
+use taly_lang::{util::source_file::SourceFile, session::session::Session};
+
+struct Expected {
+    stdout: Vec<String>,
+    exit: i32
+}
+
+impl Expected {
+
+    fn parse(src: &str) -> Self {
+        let mut expected = Self {
+            stdout: Vec::new(),
+            exit: 0
+        };
+        for line in src.lines() {
+            let line = line.trim_start();
+            if let Some(out) = line.strip_prefix("# stdout:") {
+                expected.stdout.push(out.strip_prefix(' ').unwrap_or(out).to_string());
+            } else if let Some(code) = line.strip_prefix("# exit:") {
+                expected.exit = code.trim().parse().expect("Invalid exit code");
+            }
+        }
+        expected
+    }
+
+}
+
+/// Compiles, builds and runs the program, returns why it failed.
+fn run_program(path: &Path, out_dir: &Path) -> Result<(), String> {
+    let src = std::fs::read_to_string(path).map_err(|err| format!("failed to read, {}", err))?;
+    let expected = Expected::parse(&src);
+
+    let mut session = Session::new(SourceFile::new(path.to_string_lossy().to_string(), src));
+    let project = session.compile().map_err(|err| format!("failed to compile with {}", err.kind()))?;
+
+    let dir = out_dir.join(path.file_stem().unwrap());
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut sources = Vec::new();
+    for file in project.files.iter() {
+        std::fs::write(dir.join(format!("{}.h", file.name)), &file.header).unwrap();
+        std::fs::write(dir.join(format!("{}.c", file.name)), &file.src).unwrap();
+        sources.push(dir.join(format!("{}.c", file.name)));
+    }
+
+    // A missing `return` often goes unnoticed at run time, the value being left in the register
+    let cc = std::env::var("CC").unwrap_or("cc".to_string());
+    let executable = dir.join("main");
+    let output = Command::new(&cc).arg("-Werror=return-type").arg("-o").arg(&executable).args(&sources).output()
+        .map_err(|err| format!("failed to run '{}', {}", cc, err))?;
+    if !output.status.success() {
+        return Err(format!("failed to build:\n{}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    let output = Command::new(&executable).output().map_err(|err| format!("failed to execute, {}", err))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout: Vec<&str> = stdout.lines().collect();
+    if stdout != expected.stdout {
+        return Err(format!("unexpected output:\n  expected: {:?}\n  found:    {:?}", expected.stdout, stdout));
+    }
+    match output.status.code() {
+        Some(code) if code == expected.exit => Ok(()),
+        code => Err(format!("unexpected exit code, expected {}, found {:?}", expected.exit, code))
+    }
+}
+
+#[test]
+#[cfg_attr(not(target_os = "linux"), ignore)]
+fn run() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("run");
+
+    let mut programs = Vec::new();
+    collect(&root.join("tests/run"), &mut programs);
+
+    let mut failures = Vec::new();
+    for path in programs {
+        if let Err(err) = run_program(&path, &out_dir) {
+            failures.push(format!("'{}' {}", path.display(), err));
+        }
+    }
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n\n"));
+}